@@ -0,0 +1,116 @@
+use super::diagnostics::Diagnostic;
+
+/**
+ * @dev Builds a ParseTree from a nested node literal. A node is written as the name of its
+ *      nonterminal, followed by the value that the nonterminal holds in parentheses and then by
+ *      its leaves in brackets, so `tree!(Mapping [Token(Address), Token(Arrow), Token(Bool)])`
+ *      is the tree of `mapping (address => bool)`. Tokens are named without their enum, like
+ *      `Token(FixedMxN(128, 18))`, and so are the tokens held by `ContractDefinition`,
+ *      `StorageLocation` and `StateMutability`. Strings are taken as `&str` or `String`, as in
+ *      `Identifier("owner")`, and `Invalid` and `InvalidPair` hold nonterminals written the same
+ *      way. A tree that was built elsewhere can be used as a leaf by wrapping the expression that
+ *      returns it in braces.
+ */
+#[macro_export]
+macro_rules! tree {
+    // The leaves of a node, which are munched one at a time into a list of boxed trees
+    (@leaves [$($done:expr),*]) => {
+        vec![$($done),*]
+    };
+    (@leaves [$($done:expr),*] { $tree:expr } $(, $($rest:tt)*)?) => {
+        $crate::tree!(@leaves [$($done,)* Box::new($tree)] $($($rest)*)?)
+    };
+    (@leaves [$($done:expr),*] $kind:ident $(($($value:tt)*))? $([$($leaves:tt)*])? $(, $($rest:tt)*)?) => {
+        $crate::tree!(@leaves [$($done,)* Box::new($crate::tree!($kind $(($($value)*))? $([$($leaves)*])?))] $($($rest)*)?)
+    };
+
+    // The nonterminal at the root of a node
+    (@root Token($($token:tt)+)) => {
+        $crate::parse_4_25::NonTerminal::Token($crate::tree!(@token $($token)+))
+    };
+    (@root ContractDefinition($($token:tt)+)) => {
+        $crate::parse_4_25::NonTerminal::ContractDefinition($crate::tree!(@token $($token)+))
+    };
+    (@root StorageLocation($($token:tt)+)) => {
+        $crate::parse_4_25::NonTerminal::StorageLocation($crate::tree!(@token $($token)+))
+    };
+    (@root StateMutability($($token:tt)+)) => {
+        $crate::parse_4_25::NonTerminal::StateMutability($crate::tree!(@token $($token)+))
+    };
+    (@root Invalid($kind:ident $(($($value:tt)*))?)) => {
+        $crate::parse_4_25::NonTerminal::Invalid(Box::new($crate::tree!(@root $kind $(($($value)*))?)))
+    };
+    (@root InvalidPair($expected:ident $(($($expected_value:tt)*))?, $actual:ident $(($($actual_value:tt)*))?)) => {
+        $crate::parse_4_25::NonTerminal::InvalidPair(
+            Box::new($crate::tree!(@root $expected $(($($expected_value)*))?)),
+            Box::new($crate::tree!(@root $actual $(($($actual_value)*))?))
+        )
+    };
+    (@root $kind:ident($value:expr)) => {
+        $crate::parse_4_25::NonTerminal::$kind($crate::builder::Value::value($value))
+    };
+    (@root $kind:ident) => {
+        $crate::parse_4_25::NonTerminal::$kind
+    };
+
+    // A token, along with the values that it holds
+    (@token $name:ident) => {
+        $crate::lex_4_25::Token::$name
+    };
+    (@token $name:ident($($value:expr),+ $(,)?)) => {
+        $crate::lex_4_25::Token::$name($($crate::builder::Value::value($value)),+)
+    };
+
+    ($kind:ident $(($($value:tt)*))? [$($leaves:tt)*]) => {
+        $crate::parse_4_25::ParseTree {
+            root: $crate::tree!(@root $kind $(($($value)*))?),
+            leaves: $crate::tree!(@leaves [] $($leaves)*)
+        }
+    };
+    ($kind:ident $(($($value:tt)*))?) => {
+        $crate::parse_4_25::ParseTree {
+            root: $crate::tree!(@root $kind $(($($value)*))?),
+            leaves: vec![]
+        }
+    };
+}
+
+/**
+ * @dev Converts the values written in a `tree!` literal into the types that nonterminals and
+ *      tokens hold, so that strings can be written without `String::from` and diagnostics
+ *      without `Box::new`.
+ */
+#[doc(hidden)]
+pub trait Value<T> {
+    fn value(self) -> T;
+}
+
+impl Value<String> for &str {
+    fn value(self) -> String {
+        String::from(self)
+    }
+}
+
+impl Value<String> for String {
+    fn value(self) -> String {
+        self
+    }
+}
+
+impl Value<usize> for usize {
+    fn value(self) -> usize {
+        self
+    }
+}
+
+impl Value<Box<Diagnostic>> for Diagnostic {
+    fn value(self) -> Box<Diagnostic> {
+        Box::new(self)
+    }
+}
+
+impl Value<Box<Diagnostic>> for Box<Diagnostic> {
+    fn value(self) -> Box<Diagnostic> {
+        self
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt;
+use super::diagnostics::Severity;
+use super::lex_4_25::Token;
+use super::parse_4_25::NonTerminal;
+
+/*** Codes ***/
+
+/**
+ * @dev The stable identifier of a kind of diagnostic that the lexer or parser can report. Codes
+ *      are written as `SF` followed by four digits, like `SF0001`, and are never reused or
+ *      renumbered, so they can be used to search for and suppress diagnostics.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Code(pub u16);
+
+impl Code {
+    // Reads a code without regard to case, with or without its `SF` prefix, so `sf0001` and
+    // `0001` are both `SF0001`
+    pub fn parse(text: &str) -> Option<Code> {
+        let text = text.trim().to_uppercase();
+        let digits = text.strip_prefix("SF").unwrap_or(&text);
+        match digits.len() == 4 && digits.chars().all(|c| c.is_ascii_digit()) {
+            true => digits.parse::<u16>().ok().map(Code),
+            false => None
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SF{:04}", self.0)
+    }
+}
+
+// Codes are written as their text in JSON, so that they read the same as in rendered diagnostics
+#[cfg(feature = "serde")]
+impl serde::Serialize for Code {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Code {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Code, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Code::parse(&text).ok_or_else(|| serde::de::Error::custom(format!("`{}` isn't an error code", text)))
+    }
+}
+
+pub const EXPECTED_SEMICOLON: Code = Code(1);
+pub const EXPECTED_CLOSING_DELIMITER: Code = Code(2);
+pub const EXPECTED_OPENING_DELIMITER: Code = Code(3);
+pub const EXPECTED_IDENTIFIER: Code = Code(4);
+pub const EXPECTED_TYPE_NAME: Code = Code(5);
+pub const EXPECTED_EXPRESSION: Code = Code(6);
+pub const EXPECTED_KEYWORD: Code = Code(7);
+pub const EXPECTED_VERSION: Code = Code(8);
+pub const LEFTOVER_INPUT: Code = Code(9);
+pub const MALFORMED_CONSTRUCT: Code = Code(10);
+pub const INVALID_TOKEN: Code = Code(11);
+pub const INVALID_MAPPING_KEY: Code = Code(12);
+pub const RESERVED_KEYWORD: Code = Code(13);
+pub const MISSPELLED_KEYWORD: Code = Code(14);
+pub const NEWER_SYNTAX: Code = Code(15);
+pub const UNSUPPORTED_SYNTAX: Code = Code(16);
+pub const NESTED_TOO_DEEPLY: Code = Code(17);
+
+/**
+ * @dev A kind of diagnostic that the lexer or parser can report, along with a long-form
+ *      explanation of it.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Explanation {
+    pub code: Code,
+    // The severity that diagnostics with this code are reported at by default
+    pub severity: Severity,
+    // A short summary of the problem
+    pub title: &'static str,
+    // Markdown that describes the problem, with an example of Solidity that causes it in a
+    // `solidity,error` block and the same example fixed in a `solidity` block
+    pub text: &'static str,
+}
+
+const CATALOG: [Explanation; 17] = [
+    Explanation { code: EXPECTED_SEMICOLON, severity: Severity::Error, title: "expected `;`", text: include_str!("codes/SF0001.md") },
+    Explanation { code: EXPECTED_CLOSING_DELIMITER, severity: Severity::Error, title: "expected a closing delimiter", text: include_str!("codes/SF0002.md") },
+    Explanation { code: EXPECTED_OPENING_DELIMITER, severity: Severity::Error, title: "expected an opening delimiter", text: include_str!("codes/SF0003.md") },
+    Explanation { code: EXPECTED_IDENTIFIER, severity: Severity::Error, title: "expected an identifier", text: include_str!("codes/SF0004.md") },
+    Explanation { code: EXPECTED_TYPE_NAME, severity: Severity::Error, title: "expected a type name", text: include_str!("codes/SF0005.md") },
+    Explanation { code: EXPECTED_EXPRESSION, severity: Severity::Error, title: "expected an expression", text: include_str!("codes/SF0006.md") },
+    Explanation { code: EXPECTED_KEYWORD, severity: Severity::Error, title: "expected a keyword or operator", text: include_str!("codes/SF0007.md") },
+    Explanation { code: EXPECTED_VERSION, severity: Severity::Error, title: "expected a version in a pragma", text: include_str!("codes/SF0008.md") },
+    Explanation { code: LEFTOVER_INPUT, severity: Severity::Error, title: "unexpected input after a complete construct", text: include_str!("codes/SF0009.md") },
+    Explanation { code: MALFORMED_CONSTRUCT, severity: Severity::Error, title: "malformed construct", text: include_str!("codes/SF0010.md") },
+    Explanation { code: INVALID_TOKEN, severity: Severity::Error, title: "invalid token", text: include_str!("codes/SF0011.md") },
+    Explanation { code: INVALID_MAPPING_KEY, severity: Severity::Error, title: "invalid mapping key type", text: include_str!("codes/SF0012.md") },
+    Explanation { code: RESERVED_KEYWORD, severity: Severity::Error, title: "reserved keyword used as an identifier", text: include_str!("codes/SF0013.md") },
+    Explanation { code: MISSPELLED_KEYWORD, severity: Severity::Warning, title: "misspelled keyword", text: include_str!("codes/SF0014.md") },
+    Explanation { code: NEWER_SYNTAX, severity: Severity::Error, title: "syntax from a newer version of Solidity", text: include_str!("codes/SF0015.md") },
+    Explanation { code: UNSUPPORTED_SYNTAX, severity: Severity::Error, title: "syntax that solfix doesn't support yet", text: include_str!("codes/SF0016.md") },
+    Explanation { code: NESTED_TOO_DEEPLY, severity: Severity::Error, title: "input nested too deeply", text: include_str!("codes/SF0017.md") },
+];
+
+// Returns every code, in order
+pub fn catalog() -> &'static [Explanation] {
+    &CATALOG
+}
+
+/**
+ * @dev Looks up the explanation of a code, which is read the same way as by `Code::parse`.
+ * @param code The code to look up.
+ * @return The code's entry in the catalog, if there is one.
+ */
+pub fn explain(code: &str) -> Option<&'static Explanation> {
+    let code = Code::parse(code)?;
+    CATALOG.iter().find(|entry| entry.code == code)
+}
+
+/**
+ * @dev Returns the code of the error for an Invalid or InvalidPair leaf, which depends on the
+ *      nonterminal that the parser expected to find.
+ * @param expected The nonterminal that was expected.
+ * @return The code of the error.
+ */
+pub fn expected_code(expected: &NonTerminal) -> Code {
+    match expected {
+        NonTerminal::Token(Token::Semicolon) => EXPECTED_SEMICOLON,
+        NonTerminal::Token(Token::CloseBrace)   |
+        NonTerminal::Token(Token::CloseBracket) |
+        NonTerminal::Token(Token::CloseParenthesis) => EXPECTED_CLOSING_DELIMITER,
+        NonTerminal::Token(Token::OpenBrace)   |
+        NonTerminal::Token(Token::OpenBracket) |
+        NonTerminal::Token(Token::OpenParenthesis) => EXPECTED_OPENING_DELIMITER,
+        NonTerminal::Token(Token::Identifier(..)) |
+        NonTerminal::Identifier(..) => EXPECTED_IDENTIFIER,
+        NonTerminal::Token(Token::Version(..)) => EXPECTED_VERSION,
+        NonTerminal::Token(Token::EOF) |
+        NonTerminal::SourceUnit => LEFTOVER_INPUT,
+        NonTerminal::Token(..) => EXPECTED_KEYWORD,
+        NonTerminal::TypeName           |
+        NonTerminal::ElementaryTypeName |
+        NonTerminal::UserDefinedTypeName => EXPECTED_TYPE_NAME,
+        NonTerminal::Expression        |
+        NonTerminal::PrimaryExpression |
+        NonTerminal::FunctionCall => EXPECTED_EXPRESSION,
+        NonTerminal::PragmaDirective              |
+        NonTerminal::ImportDirective              |
+        NonTerminal::ContractDefinition(..)       |
+        NonTerminal::ContractPart                 |
+        NonTerminal::InheritanceList              |
+        NonTerminal::InheritanceSpecifier         |
+        NonTerminal::StateVariableDeclaration     |
+        NonTerminal::UsingForDeclaration          |
+        NonTerminal::StructDefinition             |
+        NonTerminal::ModifierDefinition           |
+        NonTerminal::ModifierInvocation           |
+        NonTerminal::FunctionDefinition           |
+        NonTerminal::ConstructorDefinition        |
+        NonTerminal::FallbackFunctionDefinition   |
+        NonTerminal::EventDefinition              |
+        NonTerminal::EnumValue(..)                |
+        NonTerminal::EnumValueList                |
+        NonTerminal::EnumDefinition               |
+        NonTerminal::ParameterList                |
+        NonTerminal::Parameter                    |
+        NonTerminal::EventParameterList           |
+        NonTerminal::EventParameter               |
+        NonTerminal::FunctionTypeParameterList    |
+        NonTerminal::FunctionTypeParameter        |
+        NonTerminal::VariableDeclaration          |
+        NonTerminal::Mapping                      |
+        NonTerminal::ArrayTypeName                |
+        NonTerminal::FunctionTypeName             |
+        NonTerminal::StorageLocation(..)          |
+        NonTerminal::StateMutability(..)          |
+        NonTerminal::Block                        |
+        NonTerminal::Statement                    |
+        NonTerminal::ExpressionStatement          |
+        NonTerminal::IfStatement                  |
+        NonTerminal::WhileStatement               |
+        NonTerminal::PlaceholderStatement         |
+        NonTerminal::SimpleStatement              |
+        NonTerminal::ForStatement                 |
+        NonTerminal::InlineAssemblyStatement      |
+        NonTerminal::DoWhileStatement             |
+        NonTerminal::Continue                     |
+        NonTerminal::Break                        |
+        NonTerminal::Return                       |
+        NonTerminal::Throw                        |
+        NonTerminal::EmitStatement                |
+        NonTerminal::VariableDefinition           |
+        NonTerminal::IdentifierList               |
+        NonTerminal::ExpressionList               |
+        NonTerminal::NameValueList                |
+        NonTerminal::FunctionCallArguments        |
+        NonTerminal::NewExpression                |
+        NonTerminal::MemberAccess                 |
+        NonTerminal::IndexAccess                  |
+        NonTerminal::BooleanLiteral(..)           |
+        NonTerminal::NumberLiteral(..)            |
+        NonTerminal::NumberUnit                   |
+        NonTerminal::HexLiteral(..)               |
+        NonTerminal::StringLiteral(..)            |
+        NonTerminal::HexNumber                    |
+        NonTerminal::DecimalNumber                |
+        NonTerminal::TupleExpression              |
+        NonTerminal::InlineArrayExpression        |
+        NonTerminal::ElementaryTypeNameExpression |
+        NonTerminal::Int                          |
+        NonTerminal::Uint                         |
+        NonTerminal::Byte                         |
+        NonTerminal::Fixed                        |
+        NonTerminal::Ufixed                       |
+        NonTerminal::InlineAssemblyBlock          |
+        NonTerminal::AssemblyItem                 |
+        NonTerminal::AssemblyLocalBinding         |
+        NonTerminal::AssemblyAssignment           |
+        NonTerminal::AssemblyLabel                |
+        NonTerminal::FunctionalAssemblyExpression |
+        NonTerminal::Invalid(..)                  |
+        NonTerminal::InvalidPair(..)              |
+        NonTerminal::Error(..)                    |
+        NonTerminal::Empty => MALFORMED_CONSTRUCT
+    }
+}
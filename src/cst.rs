@@ -0,0 +1,819 @@
+use std::collections::{ HashMap, HashSet };
+use std::fmt;
+use std::rc::Rc;
+use super::diagnostics::Span;
+use super::lex_4_25;
+use super::navigation::NodeId;
+use super::parse_4_25::{ NonTerminal, ParseTree };
+
+/*** Green Tree ***/
+
+/**
+ * @dev A token of the concrete syntax tree along with the text that it was read from. Whitespace
+ *      and comments are kept as trivia tokens, so that every character of the input belongs to
+ *      exactly one token.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct GreenToken {
+    pub kind: lex_4_25::Token,
+    pub text: String,
+}
+
+impl GreenToken {
+    // Returns the number of characters in this token
+    pub fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    // Returns the number of characters in this element
+    pub fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.len(),
+            GreenElement::Token(token) => token.len()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/**
+ * @dev An immutable node of the concrete syntax tree. Green nodes don't know where they are in the
+ *      input, so a subtree can be shared between trees or replaced without rebuilding the rest of
+ *      the tree. SyntaxNode adds the positions back in.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct GreenNode {
+    pub kind: NonTerminal,
+    pub children: Vec<GreenElement>,
+    len: usize,
+}
+
+impl GreenNode {
+    pub fn new(kind: NonTerminal, children: Vec<GreenElement>) -> GreenNode {
+        let len = children.iter().map(|child| child.len()).sum();
+        GreenNode { kind, children, len }
+    }
+
+    // Returns the number of characters in this node
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn write_source(&self, source: &mut String) {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.write_source(source),
+                GreenElement::Token(token) => source.push_str(&token.text)
+            }
+        }
+    }
+}
+
+/*** Syntax Tree ***/
+
+/**
+ * @dev A node of the concrete syntax tree along with its position in the input. Syntax nodes are
+ *      cheap to clone and are created as the tree is walked.
+ */
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<SyntaxNodeData>);
+
+struct SyntaxNodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    offset: usize,
+}
+
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    offset: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> SyntaxNode {
+        SyntaxNode(Rc::new(SyntaxNodeData { green, parent: None, offset: 0 }))
+    }
+
+    pub fn kind(&self) -> &NonTerminal {
+        &self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    // Returns the characters of the input that this node covers
+    pub fn span(&self) -> Span {
+        Span::new(self.0.offset, self.0.offset + self.0.green.len())
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    // Returns the child nodes and tokens of this node in the order that they appear in the input
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        let mut children = vec![];
+        for child in &self.0.green.children {
+            children.push(match child {
+                GreenElement::Node(node) => SyntaxElement::Node(SyntaxNode(Rc::new(SyntaxNodeData {
+                    green: node.clone(),
+                    parent: Some(self.clone()),
+                    offset,
+                }))),
+                GreenElement::Token(token) => SyntaxElement::Token(SyntaxToken {
+                    green: token.clone(),
+                    parent: self.clone(),
+                    offset,
+                })
+            });
+            offset += child.len();
+        }
+        children
+    }
+
+    // Returns the child nodes of this node, leaving out its tokens
+    pub fn children(&self) -> Vec<SyntaxNode> {
+        self.children_with_tokens().into_iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None
+        }).collect()
+    }
+
+    // Returns the descendant with id. The child nodes of a syntax tree line up with the leaves of
+    // the parse tree that it was built from, so the id of a parse tree node finds its syntax node.
+    pub fn node(&self, id: &NodeId) -> Option<SyntaxNode> {
+        let mut node = self.clone();
+        for index in id.path() {
+            node = node.children().into_iter().nth(*index)?;
+        }
+        Some(node)
+    }
+
+    // Returns every token in this subtree in the order that they appear in the input
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = vec![];
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens(&self, tokens: &mut Vec<SyntaxToken>) {
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token)
+            }
+        }
+    }
+
+    // Returns the source text of this node. The source of a tree that was built with its trivia is
+    // exactly the input that it was read from.
+    pub fn to_source(&self) -> String {
+        let mut source = String::new();
+        self.0.green.write_source(&mut source);
+        source
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &SyntaxNode) -> bool {
+        Rc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SyntaxNode").field("kind", self.kind()).field("span", &self.span()).finish()
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> &lex_4_25::Token {
+        &self.green.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.offset, self.offset + self.green.len())
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        self.green.kind.is_trivia()
+    }
+}
+
+impl PartialEq for SyntaxToken {
+    fn eq(&self, other: &SyntaxToken) -> bool {
+        Rc::ptr_eq(&self.green, &other.green) && self.offset == other.offset
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SyntaxToken").field("kind", self.kind()).field("span", &self.span()).finish()
+    }
+}
+
+// Syntax trees are serialized along with the spans of their nodes and tokens. They can't be
+// deserialized, since they are views of a green tree rather than values of their own.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SyntaxNode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut node = serializer.serialize_struct("SyntaxNode", 3)?;
+        node.serialize_field("node", self.kind())?;
+        node.serialize_field("span", &self.span())?;
+        node.serialize_field("children", &self.children_with_tokens())?;
+        node.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SyntaxToken {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut token = serializer.serialize_struct("SyntaxToken", 3)?;
+        token.serialize_field("token", self.kind())?;
+        token.serialize_field("text", self.text())?;
+        token.serialize_field("span", &self.span())?;
+        token.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SyntaxElement {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            SyntaxElement::Node(node) => node.serialize(serializer),
+            SyntaxElement::Token(token) => token.serialize(serializer)
+        }
+    }
+}
+
+/**
+ * @dev A parse tree along with the syntax tree that was built from it. It serializes to the same
+ *      JSON as the parse tree with the `start` and `end` of each node added, which the parse tree
+ *      doesn't record on its own. The offsets count characters, from the first token of the node
+ *      to one past its last token, and a node without any tokens starts and ends where it is.
+ *      Deserializing the JSON as a ParseTree ignores the offsets.
+ */
+#[cfg(feature = "serde")]
+pub struct SpannedTree<'a> {
+    tree: &'a ParseTree,
+    syntax: SyntaxNode,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> SpannedTree<'a> {
+    pub fn new(input: &Vec<char>, tree: &'a ParseTree) -> SpannedTree<'a> {
+        SpannedTree { tree, syntax: build(input, tree, true) }
+    }
+
+    // Returns the characters from the first to the last token of the node, leaving out trivia
+    fn span(&self) -> Span {
+        let tokens = self.syntax.tokens().into_iter().filter(|token| !token.is_trivia()).collect::<Vec<SyntaxToken>>();
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => Span::new(first.span().start, last.span().end),
+            _ => Span::new(self.syntax.span().start, self.syntax.span().start)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SpannedTree<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{ Error, SerializeMap };
+        let mut node = serializer.serialize_map(None)?;
+        // The nonterminal is flattened into the node, as it is in the JSON of a ParseTree
+        if let serde_json::Value::Object(fields) = serde_json::to_value(&self.tree.root).map_err(S::Error::custom)? {
+            for (key, value) in fields {
+                node.serialize_entry(&key, &value)?;
+            }
+        }
+        let span = self.span();
+        node.serialize_entry("start", &span.start)?;
+        node.serialize_entry("end", &span.end)?;
+        // Each leaf of the parse tree has one child node in the syntax tree
+        let children = self.tree.leaves.iter()
+            .zip(self.syntax.children())
+            .map(|(leaf, syntax)| SpannedTree { tree: leaf, syntax })
+            .collect::<Vec<SpannedTree>>();
+        node.serialize_entry("children", &children)?;
+        node.end()
+    }
+}
+
+/*** Token Record ***/
+
+/**
+ * @dev The tokens that a parse read, along with where they are. The parser reads every token
+ *      through its Context, which records it here, so a token that is peeked at and then read is
+ *      only lexed once, and the concrete syntax tree can be built from the tokens without lexing
+ *      the input again.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct TokenRecord {
+    // Each token that was read, keyed by the position that the lexer read it from, along with its
+    // span and the position of the lexer after it
+    read: HashMap<usize, (lex_4_25::Token, Span, usize)>,
+}
+
+impl TokenRecord {
+    // Reads the next token like lex_4_25::next_token_with_span, lexing it only if it hasn't been
+    // read from current_ptr before
+    pub(crate) fn next_token_with_span(&mut self, input: &Vec<char>, current_ptr: &mut usize) -> (lex_4_25::Token, Span) {
+        let start = *current_ptr;
+        if let Some((token, span, end)) = self.read.get(&start) {
+            *current_ptr = *end;
+            return (token.clone(), *span);
+        }
+        let (token, span) = lex_4_25::next_token_with_span(input, current_ptr);
+        self.read.insert(start, (token.clone(), span, *current_ptr));
+        (token, span)
+    }
+
+    // Returns the tokens that were read, keyed by where they start. Illegal tokens are left out,
+    // since the lexer doesn't always move past them.
+    fn by_start(&self) -> HashMap<usize, (lex_4_25::Token, Span)> {
+        self.read.values()
+            .filter(|(token, span, _)| *token != lex_4_25::Token::Illegal && *token != lex_4_25::Token::EOF && span.start < span.end)
+            .map(|(token, span, _)| (span.start, (token.clone(), *span)))
+            .collect()
+    }
+}
+
+/*** Building ***/
+
+/**
+ * @dev Builds the concrete syntax tree for the input that tree was parsed from. The parse tree
+ *      doesn't record where its nonterminals are, so the tokens that it holds are matched against
+ *      the tokens of the input in order. The tokens that the parse tree leaves out, like
+ *      semicolons and parentheses, are given to the nonterminal that they delimit, or otherwise to
+ *      the nonterminal that surrounds them.
+ * @param input The characters that tree was parsed from.
+ * @param tree The parse tree of the whole input.
+ * @param retain_trivia Whether whitespace and comments are kept. The tree only reproduces the
+ *        input exactly if they are.
+ * @return The root of the concrete syntax tree.
+ */
+pub fn build(input: &Vec<char>, tree: &ParseTree, retain_trivia: bool) -> SyntaxNode {
+    build_with_tokens(input, tree, &TokenRecord::default(), retain_trivia)
+}
+
+/**
+ * @dev Builds the concrete syntax tree for the input that tree was parsed from, like build, out of
+ *      the tokens that the parse recorded. Only the whitespace, comments and any tokens that the
+ *      parse didn't read are lexed. Where each node is in the tokens is worked out in one pass
+ *      over the tree before it is built, so building takes time linear in the length of the input.
+ * @param input The characters that tree was parsed from.
+ * @param tree The parse tree of the whole input.
+ * @param tokens The tokens that the parse of tree read.
+ * @param retain_trivia Whether whitespace and comments are kept.
+ * @return The root of the concrete syntax tree.
+ */
+pub fn build_with_tokens(input: &Vec<char>, tree: &ParseTree, tokens: &TokenRecord, retain_trivia: bool) -> SyntaxNode {
+    let mut builder = Builder::new(input, tree, tokens, retain_trivia);
+    let mut root = Frame::default();
+    builder.add_children(tree, &mut root);
+    // Anything that is left over belongs to the root
+    let end = builder.tokens.len();
+    builder.emit_until(end, &mut root);
+    SyntaxNode::new_root(Rc::new(GreenNode::new(tree.root.clone(), root.children)))
+}
+
+// The children of a node that is being built
+#[derive(Default)]
+struct Frame {
+    children: Vec<GreenElement>,
+    // The number of opening delimiters in children that haven't been closed yet
+    open: isize,
+}
+
+// Where a node of the parse tree is in the tokens of the input, which is worked out for every node
+// before the tree is built
+#[derive(Default)]
+struct Layout {
+    // The token that the root of the node holds
+    anchor: Option<usize>,
+    // Whether the first leaf of the node comes before the token in its root
+    leaf_first: bool,
+    // The first and last tokens that belong to one of the node's leaves or to its root
+    first: Option<usize>,
+    last: Option<usize>,
+    // The leading delimiters of the node and of the nodes that begin where it begins
+    edge: Vec<lex_4_25::Token>,
+    // The leading delimiters of the leaves that the node starts with
+    inner: Vec<lex_4_25::Token>,
+}
+
+struct Builder {
+    // Every token of the input, including trivia
+    raw: Vec<(lex_4_25::Token, String)>,
+    // The indices in raw of the tokens that aren't trivia
+    tokens: Vec<usize>,
+    // The indices in tokens of each kind of token, in order
+    positions: HashMap<lex_4_25::Token, Vec<usize>>,
+    // The index of the delimiter that closes each opening delimiter
+    closing: Vec<Option<usize>>,
+    // The kinds of tokens that the parse tree holds
+    anchors: HashSet<lex_4_25::Token>,
+    // The layout of each node of the parse tree, keyed by its address
+    layouts: HashMap<*const ParseTree, Layout>,
+    // The next token that hasn't been added to the tree
+    next_raw: usize,
+    next: usize,
+    retain_trivia: bool,
+}
+
+impl Builder {
+    fn new(input: &Vec<char>, tree: &ParseTree, record: &TokenRecord, retain_trivia: bool) -> Builder {
+        let mut raw = vec![];
+        let mut tokens = vec![];
+        let recorded = record.by_start();
+        let current_ptr = &mut 0;
+        loop {
+            let next = match recorded.get(current_ptr) {
+                Some((token, span)) => {
+                    *current_ptr = span.end;
+                    (token.clone(), *span)
+                }
+                None => lex_4_25::next_token_or_trivia(input, current_ptr)
+            };
+            match next {
+                (lex_4_25::Token::EOF, _) => break,
+                (token, span) => {
+                    if !token.is_trivia() {
+                        tokens.push(raw.len());
+                    }
+                    raw.push((token, span.text(input)));
+                }
+            }
+        }
+        let mut positions: HashMap<lex_4_25::Token, Vec<usize>> = HashMap::new();
+        let mut closing = vec![None; tokens.len()];
+        let mut opened = vec![];
+        for (index, raw_index) in tokens.iter().enumerate() {
+            let token = &raw[*raw_index].0;
+            positions.entry(token.clone()).or_default().push(index);
+            match token {
+                lex_4_25::Token::CloseBrace |
+                lex_4_25::Token::CloseBracket |
+                lex_4_25::Token::CloseParenthesis => {
+                    if let Some(open) = opened.pop() {
+                        closing[open] = Some(index);
+                    }
+                }
+                token if is_opening_delimiter(token) => opened.push(index),
+                _ => ()
+            }
+        }
+        let mut builder = Builder {
+            raw,
+            tokens,
+            positions,
+            closing,
+            anchors: tree.preorder().filter_map(|(_, node)| anchor(&node.root)).collect(),
+            layouts: HashMap::new(),
+            next_raw: 0,
+            next: 0,
+            retain_trivia,
+        };
+        builder.lay_out(tree, &mut 0);
+        builder
+    }
+
+    fn token(&self, index: usize) -> Option<&lex_4_25::Token> {
+        self.tokens.get(index).map(|raw| &self.raw[*raw].0)
+    }
+
+    fn layout(&self, tree: &ParseTree) -> &Layout {
+        &self.layouts[&(tree as *const ParseTree)]
+    }
+
+    // Adds the tokens before the token at index to frame, along with the trivia in front of it
+    fn emit_until(&mut self, index: usize, frame: &mut Frame) {
+        let end = self.tokens.get(index).cloned().unwrap_or(self.raw.len());
+        self.emit_raw_until(end, frame);
+    }
+
+    // Adds the tokens up to and including the token at index to frame
+    fn emit_through(&mut self, index: usize, frame: &mut Frame) {
+        let end = self.tokens.get(index).map_or(self.raw.len(), |raw| raw + 1);
+        self.emit_raw_until(end, frame);
+    }
+
+    fn emit_raw_until(&mut self, end: usize, frame: &mut Frame) {
+        while self.next_raw < end {
+            let (token, text) = self.raw[self.next_raw].clone();
+            self.next_raw += 1;
+            if token.is_trivia() {
+                if !self.retain_trivia {
+                    continue;
+                }
+            } else {
+                self.next += 1;
+                frame.open += match token {
+                    lex_4_25::Token::OpenBrace |
+                    lex_4_25::Token::OpenBracket |
+                    lex_4_25::Token::OpenParenthesis => 1,
+                    lex_4_25::Token::CloseBrace |
+                    lex_4_25::Token::CloseBracket |
+                    lex_4_25::Token::CloseParenthesis => -1,
+                    _ => 0
+                };
+            }
+            frame.children.push(GreenElement::Token(Rc::new(GreenToken { kind: token, text })));
+        }
+    }
+
+    // Adds the next token to frame
+    fn emit_next(&mut self, frame: &mut Frame) {
+        let next = self.next;
+        self.emit_through(next, frame);
+    }
+
+    // Returns the index of the first token at or after from that is token
+    fn find_from(&self, from: usize, token: &lex_4_25::Token) -> Option<usize> {
+        let indices = self.positions.get(token)?;
+        indices.get(indices.partition_point(|index| *index < from)).cloned()
+    }
+
+    // Returns whether the first leaf of tree comes before the token in its root, as the left
+    // operand of binary and postfix operators does. A prefix operator is the first token of its
+    // node, so only tokens that the parse tree leaves out can come between position and it.
+    fn leaf_comes_first(&self, tree: &ParseTree, position: usize) -> bool {
+        match (&tree.root, anchor(&tree.root)) {
+            (NonTerminal::Token(..), Some(token)) if tree.leaves.len() == 1 => {
+                match self.find_from(position, &token) {
+                    Some(index) => (position..index).any(|index| self.token(index).is_some_and(|token| self.anchors.contains(token))),
+                    None => true
+                }
+            }
+            (NonTerminal::Token(..), Some(_)) => !tree.leaves.is_empty(),
+            _ => false
+        }
+    }
+
+    /**
+     * @dev Works out the layout of tree and of its descendants. The tokens that the parse tree
+     *      holds are in the same order as the tokens of the input, so each one is matched with the
+     *      first token of the input after the one that was matched before it, which visits every
+     *      node once.
+     * @param tree The node to lay out.
+     * @param cursor The index of the first token that hasn't been matched yet.
+     */
+    fn lay_out(&mut self, tree: &ParseTree, cursor: &mut usize) {
+        let leaf_first = self.leaf_comes_first(tree, *cursor);
+        let mut leaves = tree.leaves.iter();
+        if leaf_first {
+            if let Some(leaf) = leaves.next() {
+                self.lay_out(leaf, cursor);
+            }
+        }
+        let anchor_index = anchor(&tree.root).and_then(|token| self.find_from(*cursor, &token));
+        if let Some(index) = anchor_index {
+            *cursor = index + 1;
+        }
+        for leaf in leaves {
+            self.lay_out(leaf, cursor);
+        }
+        let mut first = anchor_index;
+        let mut last = anchor_index;
+        for leaf in &tree.leaves {
+            let layout = self.layout(leaf);
+            first = match (first, layout.first) {
+                (Some(first), Some(leaf)) => Some(first.min(leaf)),
+                (first, leaf) => first.or(leaf)
+            };
+            last = last.max(layout.last);
+        }
+        let mut inner = vec![];
+        if let (Some(_), NonTerminal::Token(..)) | (None, _) = (anchor(&tree.root), &tree.root) {
+            for leaf in &tree.leaves {
+                let layout = self.layout(leaf);
+                for token in &layout.edge {
+                    if !inner.contains(token) {
+                        inner.push(token.clone());
+                    }
+                }
+                if layout.first.is_some() {
+                    break;
+                }
+            }
+        }
+        let mut edge = leading_delimiters(&tree.root);
+        edge.extend(inner.iter().filter(|token| !leading_delimiters(&tree.root).contains(token)).cloned());
+        let layout = Layout { anchor: anchor_index, leaf_first, first, last, edge, inner };
+        self.layouts.insert(tree as *const ParseTree, layout);
+    }
+
+    // Builds the node for tree and adds it to parent
+    fn add_node(&mut self, tree: &ParseTree, parent: &mut Frame) {
+        let leading = leading_delimiters(&tree.root);
+        let first = self.layout(tree).first;
+        if let Some(first) = first {
+            // The delimiters right in front of the first token may belong to tree or to one of the
+            // nodes that start with it. Anything before them belongs to the parent.
+            let edge = &self.layout(tree).edge;
+            let mut start = first;
+            let mut unmatched: usize = 0;
+            for index in (self.next..first).rev() {
+                match self.token(index) {
+                    Some(lex_4_25::Token::CloseBrace) |
+                    Some(lex_4_25::Token::CloseBracket) |
+                    Some(lex_4_25::Token::CloseParenthesis) => unmatched += 1,
+                    Some(token) if edge.contains(token) => {
+                        if is_opening_delimiter(token) && unmatched > 0 {
+                            unmatched -= 1;
+                        }
+                        if unmatched == 0 {
+                            start = index;
+                        }
+                    }
+                    // Parentheses that the parse tree leaves out, like those around `(a + b) * c`,
+                    // belong to the outermost node inside them, which may be tree or a leaf. Braces
+                    // are only ever left out around the members of a struct, which belong to it.
+                    Some(token) if is_opening_delimiter(token) && *token != lex_4_25::Token::OpenBrace => {
+                        if unmatched > 0 {
+                            unmatched -= 1;
+                        } else {
+                            start = index;
+                        }
+                    }
+                    _ => break
+                }
+            }
+            self.emit_until(start, parent);
+        }
+        let mut frame = Frame::default();
+        for delimiter in &leading {
+            if self.token(self.next) == Some(delimiter) {
+                self.emit_next(&mut frame);
+            }
+        }
+        if let Some(first) = first {
+            let last = self.layout(tree).last;
+            while self.next < first {
+                match self.token(self.next) {
+                    Some(token) if is_opening_delimiter(token) && !self.layout(tree).inner.contains(token) &&
+                                   self.closing[self.next] > last => {
+                        self.emit_next(&mut frame);
+                    }
+                    _ => break
+                }
+            }
+        }
+        self.add_children(tree, &mut frame);
+        // The brackets of a dynamically sized array come after its element type
+        if tree.root == NonTerminal::ArrayTypeName && tree.leaves.len() == 1 &&
+           self.token(self.next) == Some(&lex_4_25::Token::OpenBracket) {
+            self.emit_next(&mut frame);
+        }
+        // Close the delimiters that were opened in this node
+        while frame.open > 0 {
+            match self.token(self.next) {
+                Some(lex_4_25::Token::CloseBrace) |
+                Some(lex_4_25::Token::CloseBracket) |
+                Some(lex_4_25::Token::CloseParenthesis) |
+                Some(lex_4_25::Token::Comma) |
+                Some(lex_4_25::Token::Semicolon) => self.emit_next(&mut frame),
+                _ => break
+            }
+        }
+        if ends_with_semicolon(&tree.root) && self.token(self.next) == Some(&lex_4_25::Token::Semicolon) {
+            self.emit_next(&mut frame);
+        }
+        parent.children.push(GreenElement::Node(Rc::new(GreenNode::new(tree.root.clone(), frame.children))));
+    }
+
+    // Adds the token in tree's root and the nodes for its leaves to frame, in the order that they
+    // appear in the input
+    fn add_children(&mut self, tree: &ParseTree, frame: &mut Frame) {
+        let (anchor, leaf_first) = {
+            let layout = self.layout(tree);
+            (layout.anchor, layout.leaf_first)
+        };
+        // Operators come after their first operand, unless they are prefix operators
+        let mut leaves = tree.leaves.iter();
+        if leaf_first {
+            if let Some(leaf) = leaves.next() {
+                self.add_node(leaf, frame);
+            }
+        }
+        if let Some(index) = anchor.filter(|index| *index >= self.next) {
+            self.emit_through(index, frame);
+        }
+        for leaf in leaves {
+            self.add_node(leaf, frame);
+        }
+    }
+}
+
+// Returns the token of the input that a nonterminal holds, if it holds one
+fn anchor(nonterminal: &NonTerminal) -> Option<lex_4_25::Token> {
+    match nonterminal {
+        NonTerminal::ContractDefinition(token) |
+        NonTerminal::StorageLocation(token)    |
+        NonTerminal::StateMutability(token)    |
+        NonTerminal::Token(token) => Some(token.clone()),
+        NonTerminal::Identifier(name) |
+        NonTerminal::EnumValue(name) => Some(lex_4_25::Token::Identifier(name.clone())),
+        _ => None
+    }
+}
+
+// Returns the tokens that a nonterminal begins with that the parse tree leaves out
+fn leading_delimiters(nonterminal: &NonTerminal) -> Vec<lex_4_25::Token> {
+    match nonterminal {
+        NonTerminal::PragmaDirective => vec![lex_4_25::Token::Pragma],
+        NonTerminal::ImportDirective => vec![lex_4_25::Token::Import],
+        NonTerminal::InheritanceList => vec![lex_4_25::Token::Is],
+        NonTerminal::UsingForDeclaration => vec![lex_4_25::Token::Using],
+        NonTerminal::StructDefinition => vec![lex_4_25::Token::Struct],
+        NonTerminal::ModifierDefinition => vec![lex_4_25::Token::Modifier],
+        NonTerminal::FunctionDefinition         |
+        NonTerminal::FallbackFunctionDefinition |
+        NonTerminal::FunctionTypeName => vec![lex_4_25::Token::Function],
+        NonTerminal::ConstructorDefinition => vec![lex_4_25::Token::Constructor],
+        NonTerminal::EventDefinition => vec![lex_4_25::Token::Event],
+        NonTerminal::EnumDefinition => vec![lex_4_25::Token::Enum],
+        NonTerminal::Mapping => vec![lex_4_25::Token::Mapping, lex_4_25::Token::OpenParenthesis],
+        NonTerminal::Return => vec![lex_4_25::Token::Return],
+        NonTerminal::EmitStatement => vec![lex_4_25::Token::Emit],
+        NonTerminal::NewExpression => vec![lex_4_25::Token::New],
+        NonTerminal::ContractPart  |
+        NonTerminal::EnumValueList |
+        NonTerminal::Block         |
+        NonTerminal::NameValueList => vec![lex_4_25::Token::OpenBrace],
+        NonTerminal::ParameterList             |
+        NonTerminal::EventParameterList        |
+        NonTerminal::FunctionTypeParameterList |
+        NonTerminal::FunctionCallArguments     |
+        NonTerminal::TupleExpression => vec![lex_4_25::Token::OpenParenthesis],
+        NonTerminal::InlineArrayExpression => vec![lex_4_25::Token::OpenBracket],
+        NonTerminal::Invalid(expected) => leading_delimiters(expected),
+        _ => vec![]
+    }
+}
+
+// Returns whether a nonterminal ends with a semicolon that the parse tree leaves out
+fn ends_with_semicolon(nonterminal: &NonTerminal) -> bool {
+    match nonterminal {
+        NonTerminal::PragmaDirective            |
+        NonTerminal::ImportDirective            |
+        NonTerminal::StateVariableDeclaration   |
+        NonTerminal::UsingForDeclaration        |
+        NonTerminal::VariableDeclaration        |
+        NonTerminal::FunctionDefinition         |
+        NonTerminal::ConstructorDefinition      |
+        NonTerminal::FallbackFunctionDefinition |
+        NonTerminal::EventDefinition            |
+        NonTerminal::Return                     |
+        NonTerminal::EmitStatement => true,
+        NonTerminal::Invalid(expected) => ends_with_semicolon(expected),
+        _ => false
+    }
+}
+
+fn is_opening_delimiter(token: &lex_4_25::Token) -> bool {
+    matches!(
+        token,
+        lex_4_25::Token::OpenBrace |
+        lex_4_25::Token::OpenBracket |
+        lex_4_25::Token::OpenParenthesis
+    )
+}
@@ -0,0 +1,129 @@
+use super::codes::Code;
+
+/**
+ * A range of characters in the parser input. Offsets are indices into the `Vec<char>` that the
+ * lexer reads, so `start` is the first character of the range and `end` is one past the last.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    // Returns the characters of the input that this span covers
+    pub fn text(&self, input: &[char]) -> String {
+        let end = self.end.min(input.len());
+        let start = self.start.min(end);
+        input[start..end].iter().collect()
+    }
+
+    // Returns this span moved offset characters further into the input
+    pub fn shifted(&self, offset: isize) -> Span {
+        Span::new((self.start as isize + offset) as usize, (self.end as isize + offset) as usize)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/**
+ * @dev A machine-applicable fix. Replacing the characters covered by span with replacement
+ *      resolves the Diagnostic that the suggestion is attached to.
+ */
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Suggestion {
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl Suggestion {
+    // Returns a copy of the input with this suggestion applied
+    pub fn apply(&self, input: &[char]) -> Vec<char> {
+        let end = self.span.end.min(input.len());
+        let start = self.span.start.min(end);
+        let mut fixed = input[..start].to_vec();
+        fixed.extend(self.replacement.chars());
+        fixed.extend_from_slice(&input[end..]);
+        fixed
+    }
+}
+
+/**
+ * @dev A message about a problem in the input, tied to the span where the problem was found.
+ *      Diagnostics are recorded in the ParseTree as NonTerminal::Error leaves so that they stay
+ *      next to the nonterminal that could not be parsed. The span always counts characters. A
+ *      solc AST only holds the byte offsets of its nodes, so when solc::import_ast reports a node
+ *      without the source that it points into, the span is empty at the start of the input and a
+ *      note gives the byte offsets instead.
+ */
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    pub severity: Severity,
+    // The stable code of the kind of problem, like `SF0001`, which `codes::explain` describes
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub code: Option<Code>,
+    pub message: String,
+    pub span: Span,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn error(message: String, span: Span) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code: None,
+            message,
+            span,
+            notes: vec![],
+            help: None,
+            suggestions: vec![],
+        }
+    }
+
+    pub fn warning(message: String, span: Span) -> Diagnostic {
+        Diagnostic { severity: Severity::Warning, ..Diagnostic::error(message, span) }
+    }
+
+    pub fn with_code(mut self, code: Code) -> Diagnostic {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Diagnostic {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Diagnostic {
+        self.help = Some(help);
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Diagnostic {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    // Moves this diagnostic and its suggestions offset characters further into the input
+    pub fn shift(&mut self, offset: isize) {
+        self.span = self.span.shifted(offset);
+        for suggestion in self.suggestions.iter_mut() {
+            suggestion.span = suggestion.span.shifted(offset);
+        }
+    }
+}
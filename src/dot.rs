@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+use super::navigation::NodeId;
+use super::parse_4_25::{ NonTerminal, ParseTree };
+
+/**
+ * @dev The settings that control which parts of a parse tree are drawn.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DotOptions {
+    // The deepest nodes to draw, counted from the nodes that drawing starts at, which are at
+    // depth zero. The descendants that are left out are summarized by a single node. Every node
+    // is drawn if this is None.
+    pub max_depth: Option<usize>,
+    // The nodes whose subtrees are drawn, along with their descendants. The whole tree is drawn
+    // if this is empty.
+    pub roots: Vec<NodeId>,
+}
+
+/**
+ * @dev Renders a parse tree in the DOT language of Graphviz, so that it can be drawn with
+ *      `dot -Tsvg`. Each nonterminal is a node that is labelled with its kind and with the text
+ *      that it holds, like an identifier's name or a literal's value, and its leaves are drawn
+ *      from left to right in order. Invalid nonterminals and errors are drawn in red. The names
+ *      of the nodes are taken from their ids, so the node for `NodeId` [2, 0] is `n_2_0`.
+ * @param tree The tree to render.
+ * @param options The parts of the tree to render.
+ * @return The DOT source of a directed graph.
+ */
+pub fn render(tree: &ParseTree, options: &DotOptions) -> String {
+    let mut dot = String::from("digraph ParseTree {\n");
+    dot.push_str("    ordering=out;\n");
+    dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+    let mut roots = match options.roots.is_empty() {
+        true => vec![NodeId::root()],
+        false => options.roots.clone()
+    };
+    // A root that is given more than once is only drawn once
+    let mut seen = HashSet::new();
+    roots.retain(|id| seen.insert(id.clone()));
+    // Roots inside the subtree of another root are drawn along with that subtree
+    let inside = |id: &NodeId| roots.iter().any(|other| other != id && id.path().starts_with(other.path()));
+    for id in roots.iter().filter(|id| !inside(id)) {
+        if let Some(node) = tree.node(id) {
+            render_node(&mut dot, id, node, 0, options);
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn render_node(dot: &mut String, id: &NodeId, tree: &ParseTree, depth: usize, options: &DotOptions) {
+    let name = node_name(id);
+    let style = match tree.is_error() {
+        true => ", color=red, fontcolor=red",
+        false => ""
+    };
+    dot.push_str(&format!("    {} [label=\"{}\"{}];\n", name, escape(&label(&tree.root)), style));
+    if tree.leaves.is_empty() {
+        return;
+    }
+    if options.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+        let hidden = tree.preorder().count() - 1;
+        let plural = if hidden == 1 { "" } else { "s" };
+        dot.push_str(&format!("    {}_hidden [label=\"{} more node{}\", style=dashed];\n", name, hidden, plural));
+        dot.push_str(&format!("    {} -> {}_hidden [style=dashed];\n", name, name));
+        return;
+    }
+    for (index, leaf) in tree.leaves.iter().enumerate() {
+        let child = id.child(index);
+        render_node(dot, &child, leaf, depth + 1, options);
+        dot.push_str(&format!("    {} -> {};\n", name, node_name(&child)));
+    }
+}
+
+// Returns the DOT name of the node with the given id
+fn node_name(id: &NodeId) -> String {
+    let mut name = String::from("n");
+    for index in id.path() {
+        name.push_str(&format!("_{}", index));
+    }
+    name
+}
+
+// Returns the label of a nonterminal, which is its kind followed by the text it holds on a line
+// of its own
+fn label(nonterminal: &NonTerminal) -> String {
+    let text = match nonterminal {
+        NonTerminal::ContractDefinition(token) |
+        NonTerminal::StorageLocation(token)    |
+        NonTerminal::StateMutability(token)    |
+        NonTerminal::Token(token) => token.to_source(),
+        NonTerminal::EnumValue(text)      |
+        NonTerminal::BooleanLiteral(text) |
+        NonTerminal::NumberLiteral(text)  |
+        NonTerminal::HexLiteral(text)     |
+        NonTerminal::StringLiteral(text)  |
+        NonTerminal::Identifier(text) => text.clone(),
+        NonTerminal::Invalid(expected) => format!("expected {}", brief(expected)),
+        NonTerminal::InvalidPair(expected, actual) => format!("expected {}, found {}", brief(expected), brief(actual)),
+        NonTerminal::Error(diagnostic) => diagnostic.message.clone(),
+        _ => String::new()
+    };
+    match text.is_empty() {
+        true => nonterminal.kind_name().to_string(),
+        false => format!("{}\n{}", nonterminal.kind_name(), text)
+    }
+}
+
+// Returns a nonterminal's text if it holds any, and its kind otherwise
+fn brief(nonterminal: &NonTerminal) -> String {
+    let label = label(nonterminal);
+    match label.split_once('\n') {
+        Some((_, text)) => format!("`{}`", text),
+        None => label
+    }
+}
+
+// Escapes text so that it can be written in a quoted DOT string
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c)
+        }
+    }
+    escaped
+}
@@ -0,0 +1,342 @@
+use serde_json::{ json, Value };
+use super::codes;
+use super::diagnostics::{ Diagnostic, Severity, Span };
+use super::report::{ self, RenderOptions };
+use super::source_map::{ Encoding, FileId, SourceFile, SourceMap };
+
+/**
+ * @dev A file that was checked, along with the diagnostics that were found in it.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileDiagnostics {
+    // The file in the SourceMap that the spans of the diagnostics refer to
+    pub file: FileId,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/**
+ * @dev The formats that diagnostics can be written in.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    // Rendered diagnostics with source snippets, like rustc prints
+    Human,
+    // A SARIF 2.1.0 log, for code scanning dashboards
+    Sarif,
+    // One JSON object for each diagnostic, one per line
+    Json,
+    // Checkstyle XML
+    Checkstyle,
+    // GitHub Actions workflow commands, which annotate pull requests
+    Github,
+}
+
+impl Format {
+    // Returns the format with the given name, as it is written on the command line
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "human" => Some(Format::Human),
+            "sarif" => Some(Format::Sarif),
+            "json" => Some(Format::Json),
+            "checkstyle" => Some(Format::Checkstyle),
+            "github" => Some(Format::Github),
+            _ => None
+        }
+    }
+
+    // Writes the diagnostics of every file in this format
+    pub fn emit(&self, sources: &SourceMap, files: &[FileDiagnostics], options: &RenderOptions) -> String {
+        match self {
+            Format::Human => human(sources, files, options),
+            Format::Sarif => format!("{}\n", serde_json::to_string_pretty(&sarif(sources, files)).expect("JSON values can always be serialized")),
+            Format::Json => json_lines(sources, files),
+            Format::Checkstyle => checkstyle(sources, files),
+            Format::Github => github(sources, files)
+        }
+    }
+}
+
+// The line and column, both counted from one, of the start and end of a span
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+impl Region {
+    fn new(file: &SourceFile, span: Span) -> Region {
+        let start = file.line_column(span.start, Encoding::Utf32);
+        let end = file.line_column(span.end.max(span.start), Encoding::Utf32);
+        Region { start_line: start.line + 1, start_column: start.column + 1, end_line: end.line + 1, end_column: end.column + 1 }
+    }
+}
+
+fn severity_name(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning"
+    }
+}
+
+/*** Human ***/
+
+fn human(sources: &SourceMap, files: &[FileDiagnostics], options: &RenderOptions) -> String {
+    let mut text = String::new();
+    for FileDiagnostics { file, diagnostics } in files {
+        for diagnostic in diagnostics {
+            text.push_str(&report::render(diagnostic, sources.file(*file), options));
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/*** SARIF ***/
+
+/**
+ * @dev Builds a SARIF 2.1.0 log of the diagnostics of every file. The log has a single run whose
+ *      rules are the codes of the catalog, with their explanations as help and their severities
+ *      as default levels, and a result for each diagnostic. Columns count Unicode code points,
+ *      which the run records in its columnKind, and suggestions become fixes.
+ * @param sources The files that the diagnostics refer to.
+ * @param files The diagnostics of each file that was checked.
+ * @return The SARIF log.
+ */
+pub fn sarif(sources: &SourceMap, files: &[FileDiagnostics]) -> Value {
+    let catalog = codes::catalog();
+    let rules = catalog.iter().map(|entry| json!({
+        "id": entry.code.to_string(),
+        "shortDescription": { "text": entry.title },
+        "fullDescription": { "text": entry.title },
+        "help": { "text": entry.text, "markdown": entry.text },
+        "defaultConfiguration": { "level": severity_name(&entry.severity) }
+    })).collect::<Vec<Value>>();
+    let mut results = vec![];
+    for FileDiagnostics { file, diagnostics } in files {
+        let file = sources.file(*file);
+        for diagnostic in diagnostics {
+            let mut message = diagnostic.message.clone();
+            for note in &diagnostic.notes {
+                message.push_str(&format!("\nnote: {}", note));
+            }
+            if let Some(help) = &diagnostic.help {
+                message.push_str(&format!("\nhelp: {}", help));
+            }
+            let mut result = json!({
+                "level": severity_name(&diagnostic.severity),
+                "message": { "text": message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": file.path() },
+                        "region": sarif_region(file, diagnostic.span)
+                    }
+                }]
+            });
+            if let Some(code) = diagnostic.code {
+                result["ruleId"] = json!(code.to_string());
+                if let Some(index) = catalog.iter().position(|entry| entry.code == code) {
+                    result["ruleIndex"] = json!(index);
+                }
+            }
+            if !diagnostic.suggestions.is_empty() {
+                result["fixes"] = Value::Array(diagnostic.suggestions.iter().map(|suggestion| json!({
+                    "description": { "text": suggestion.message },
+                    "artifactChanges": [{
+                        "artifactLocation": { "uri": file.path() },
+                        "replacements": [{
+                            "deletedRegion": sarif_region(file, suggestion.span),
+                            "insertedContent": { "text": suggestion.replacement }
+                        }]
+                    }]
+                })).collect());
+            }
+            results.push(result);
+        }
+    }
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "solfix",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results
+        }]
+    })
+}
+
+// Returns the SARIF region of a span, in which the end column is one past the last character
+fn sarif_region(file: &SourceFile, span: Span) -> Value {
+    let region = Region::new(file, span);
+    json!({
+        "startLine": region.start_line,
+        "startColumn": region.start_column,
+        "endLine": region.end_line,
+        "endColumn": region.end_column,
+        "charOffset": span.start,
+        "charLength": span.end.saturating_sub(span.start)
+    })
+}
+
+/*** JSON ***/
+
+/**
+ * @dev Writes each diagnostic as a JSON object on a line of its own. Each object holds the file
+ *      name, severity, code, message, notes, help and suggestions of a diagnostic, along with the
+ *      line and column of the start and end of its span, all counted from one.
+ * @param sources The files that the diagnostics refer to.
+ * @param files The diagnostics of each file that was checked.
+ * @return The JSON objects, each followed by a newline.
+ */
+pub fn json_lines(sources: &SourceMap, files: &[FileDiagnostics]) -> String {
+    let mut text = String::new();
+    for FileDiagnostics { file, diagnostics } in files {
+        let file = sources.file(*file);
+        for diagnostic in diagnostics {
+            let region = Region::new(file, diagnostic.span);
+            let suggestions = diagnostic.suggestions.iter().map(|suggestion| {
+                let region = Region::new(file, suggestion.span);
+                json!({
+                    "message": suggestion.message,
+                    "line": region.start_line,
+                    "column": region.start_column,
+                    "endLine": region.end_line,
+                    "endColumn": region.end_column,
+                    "replacement": suggestion.replacement
+                })
+            }).collect::<Vec<Value>>();
+            let object = json!({
+                "file": file.path(),
+                "line": region.start_line,
+                "column": region.start_column,
+                "endLine": region.end_line,
+                "endColumn": region.end_column,
+                "severity": severity_name(&diagnostic.severity),
+                "code": diagnostic.code.map(|code| code.to_string()),
+                "message": diagnostic.message,
+                "notes": diagnostic.notes,
+                "help": diagnostic.help,
+                "suggestions": suggestions
+            });
+            text.push_str(&object.to_string());
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/*** Checkstyle ***/
+
+/**
+ * @dev Writes the diagnostics as a checkstyle XML report, with a file element for each file and
+ *      an error element for each diagnostic. The source of an error is its code, prefixed with
+ *      `solfix.`.
+ * @param sources The files that the diagnostics refer to.
+ * @param files The diagnostics of each file that was checked.
+ * @return The XML document.
+ */
+pub fn checkstyle(sources: &SourceMap, files: &[FileDiagnostics]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n");
+    for FileDiagnostics { file, diagnostics } in files {
+        let file = sources.file(*file);
+        xml.push_str(&format!("  <file name=\"{}\">\n", escape_xml(file.path())));
+        for diagnostic in diagnostics {
+            let region = Region::new(file, diagnostic.span);
+            let source = match diagnostic.code {
+                Some(code) => format!(" source=\"solfix.{}\"", code),
+                None => String::new()
+            };
+            xml.push_str(&format!(
+                "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\"{}/>\n",
+                region.start_line,
+                region.start_column,
+                severity_name(&diagnostic.severity),
+                escape_xml(&diagnostic.message),
+                source
+            ));
+        }
+        xml.push_str("  </file>\n");
+    }
+    xml.push_str("</checkstyle>\n");
+    xml
+}
+
+// Escapes text so that it can be written in a quoted XML attribute
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c)
+        }
+    }
+    escaped
+}
+
+/*** GitHub ***/
+
+/**
+ * @dev Writes each diagnostic as a GitHub Actions workflow command, like
+ *      `::error file=A.sol,line=3,col=15,endLine=3,endColumn=15,title=SF0001::expected `;``, which
+ *      annotates the line in pull requests. Notes and help follow the message on lines of their
+ *      own.
+ * @param sources The files that the diagnostics refer to.
+ * @param files The diagnostics of each file that was checked.
+ * @return The workflow commands, each followed by a newline.
+ */
+pub fn github(sources: &SourceMap, files: &[FileDiagnostics]) -> String {
+    let mut text = String::new();
+    for FileDiagnostics { file, diagnostics } in files {
+        let file = sources.file(*file);
+        for diagnostic in diagnostics {
+            let region = Region::new(file, diagnostic.span);
+            let mut properties = vec![
+                format!("file={}", escape_property(file.path())),
+                format!("line={}", region.start_line),
+                format!("col={}", region.start_column),
+                format!("endLine={}", region.end_line),
+                format!("endColumn={}", region.end_column)
+            ];
+            if let Some(code) = diagnostic.code {
+                properties.push(format!("title={}", code));
+            }
+            let mut message = diagnostic.message.clone();
+            for note in &diagnostic.notes {
+                message.push_str(&format!("\nnote: {}", note));
+            }
+            if let Some(help) = &diagnostic.help {
+                message.push_str(&format!("\nhelp: {}", help));
+            }
+            text.push_str(&format!(
+                "::{} {}::{}\n",
+                severity_name(&diagnostic.severity),
+                properties.join(","),
+                escape_data(&message)
+            ));
+        }
+    }
+    text
+}
+
+// Escapes the message of a workflow command
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+// Escapes a property of a workflow command, which also can't hold the `:` and `,` that separate
+// properties
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
@@ -0,0 +1,424 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use super::diagnostics::Span;
+use super::lex_4_25;
+use super::navigation::NodeId;
+use super::parse_4_25::{ self, ParseTree };
+use super::parser::{ Context, ParserOptions };
+
+/**
+ * @dev A change to a source file. The characters covered by span are replaced with text.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(span: Span, text: &str) -> TextEdit {
+        TextEdit { span, text: String::from(text) }
+    }
+
+    // Returns a copy of the input with this edit applied
+    pub fn apply(&self, input: &[char]) -> Vec<char> {
+        let end = self.span.end.min(input.len());
+        let start = self.span.start.min(end);
+        let mut edited = input[..start].to_vec();
+        edited.extend(self.text.chars());
+        edited.extend_from_slice(&input[end..]);
+        edited
+    }
+
+    // Returns how many characters longer the input is after this edit
+    fn offset(&self) -> isize {
+        self.text.chars().count() as isize - (self.span.end - self.span.start) as isize
+    }
+}
+
+/**
+ * @dev A parsed source file along with the nonterminals that can be reused when it is edited.
+ *      Contract members and blocks are reused as long as none of the input that was read to parse
+ *      them has changed, so an edit inside of one function body only reads that body again. An
+ *      edit that changes the names that the source declares means that nothing is reused, since
+ *      those names decide which identifiers are reported as misspellings and which mapping keys
+ *      are structs. The names are kept along with where they are declared, so finding them again
+ *      only lexes the input around the edit.
+ */
+#[derive(Clone, Debug)]
+pub struct ParsedSource {
+    source: Vec<char>,
+    tree: ParseTree,
+    options: ParserOptions,
+    // The names that the source declares, which decide whether identifiers are reported as
+    // misspellings, along with where their keywords start
+    declared: Vec<(usize, lex_4_25::Token, String)>,
+    nodes: Rc<Vec<ReusableNode>>,
+    // The ids of the contract members and blocks of the tree, and the nodes that they identify
+    anchors: HashMap<NodeId, usize>,
+    anchored: HashMap<usize, NodeId>,
+    next_id: usize,
+    reused: usize,
+    rescanned: usize,
+}
+
+impl ParsedSource {
+    pub fn source(&self) -> String {
+        self.source.iter().collect()
+    }
+
+    pub(crate) fn input(&self) -> &Vec<char> {
+        &self.source
+    }
+
+    pub fn tree(&self) -> &ParseTree {
+        &self.tree
+    }
+
+    pub fn into_tree(self) -> ParseTree {
+        self.tree
+    }
+
+    // Returns how many contract members and blocks were reused from the previous parse
+    pub fn reused_nodes(&self) -> usize {
+        self.reused
+    }
+
+    // Returns how many characters were lexed again to find the names that the source declares
+    pub fn rescanned_chars(&self) -> usize {
+        self.rescanned
+    }
+
+    /*** Stable Ids ***/
+
+    // Returns the stable id of the node with id, if the tree has one
+    pub fn stable_id(&self, id: &NodeId) -> Option<StableId> {
+        self.tree.node(id)?;
+        let path = id.path();
+        let anchor = (0..=path.len()).rev().find_map(|length| {
+            let prefix = NodeId::root().descendant(&path[..length]);
+            self.anchors.get(&prefix).map(|anchor| (*anchor, length))
+        });
+        Some(match anchor {
+            Some((anchor, length)) => StableId { anchor: Some(anchor), path: path[length..].to_vec() },
+            None => StableId { anchor: None, path: path.to_vec() }
+        })
+    }
+
+    // Returns the id of the node that a stable id identifies, if the tree still has it
+    pub fn node_id(&self, id: &StableId) -> Option<NodeId> {
+        let anchor = match id.anchor {
+            Some(anchor) => self.anchored.get(&anchor)?.clone(),
+            None => NodeId::root()
+        };
+        let node = anchor.descendant(&id.path);
+        self.tree.node(&node).map(|_| node)
+    }
+
+    // Returns the node that a stable id identifies, if the tree still has it
+    pub fn stable_node(&self, id: &StableId) -> Option<&ParseTree> {
+        self.tree.node(&self.node_id(id)?)
+    }
+
+    // Returns the stable id of the node that the node with id is a leaf of
+    pub fn stable_parent(&self, id: &StableId) -> Option<StableId> {
+        self.stable_id(&self.node_id(id)?.parent()?)
+    }
+}
+
+/**
+ * @dev Identifies a node of a ParsedSource in a way that is kept across reparses. Each contract
+ *      member and block is given a number when it is first parsed, which it keeps when it is
+ *      reused and when it is parsed again after an edit that comes after its first token, such as
+ *      an edit inside of a function body. Other nodes are identified by the path of leaf indices
+ *      from the closest contract member or block that holds them, or from the root if there isn't
+ *      one.
+ */
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct StableId {
+    anchor: Option<usize>,
+    path: Vec<usize>,
+}
+
+// The kinds of nonterminals that can be reused
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Reusable {
+    ContractMember,
+    Block,
+}
+
+#[derive(Clone, Debug)]
+struct ReusableNode {
+    id: usize,
+    kind: Reusable,
+    depth: usize,
+    // The position of the lexer before and after the node was parsed
+    span: Span,
+    // Where the first token of the node is
+    first_token: Span,
+    // One past the furthest character that was looked at while the node was parsed
+    read_end: usize,
+    tree: ParseTree,
+    // The index of the first node that was recorded while this one was parsed. The nodes that are
+    // nested in this one sit between that index and its own.
+    nested: usize,
+}
+
+impl ReusableNode {
+    fn shifted(&self, offset: isize, nested: usize) -> ReusableNode {
+        let mut tree = self.tree.clone();
+        if offset != 0 {
+            tree.shift_spans(offset);
+        }
+        ReusableNode {
+            id: self.id,
+            kind: self.kind,
+            depth: self.depth,
+            span: self.span.shifted(offset),
+            first_token: self.first_token.shifted(offset),
+            read_end: (self.read_end as isize + offset) as usize,
+            tree,
+            nested,
+        }
+    }
+}
+
+// The state of an incremental parse, which the parse functions reach through their Context
+#[derive(Debug)]
+pub(crate) struct Session {
+    previous: Rc<Vec<ReusableNode>>,
+    // The nodes of the previous parse that can be reused, keyed by their kind, where they start in
+    // the edited input and how deeply they are nested, along with how far they have moved
+    reusable: HashMap<(Reusable, usize, usize), (usize, isize)>,
+    // The ids of the nodes of the previous parse, keyed by where their first tokens are in the
+    // edited input, which nodes that are parsed again from the same token keep
+    ids: HashMap<(Reusable, usize, usize), usize>,
+    next_id: usize,
+    nodes: Vec<ReusableNode>,
+    reused: usize,
+}
+
+/**
+ * @dev Parses source with parse while recording the nonterminals that can be reused later on.
+ * @param source The input that parse reads.
+ * @param options The options that parse runs with.
+ * @param previous The previous parse of the input and the edit that was made to it, if there is one.
+ * @param parse Parses the input with the given Context, which holds the incremental session.
+ * @return The parse of the input.
+ */
+pub(crate) fn parse_reusable<F: FnOnce(&Vec<char>, &Context) -> ParseTree>(
+    source: Vec<char>,
+    options: &ParserOptions,
+    previous: Option<(&ParsedSource, &TextEdit)>,
+    parse: F
+) -> ParsedSource {
+    let (declared, rescanned) = match previous {
+        Some((previous, edit)) => rescan_declarations(&source, &previous.declared, edit),
+        None => (parse_4_25::declared_names(&source, 0, |_| false).0, source.len())
+    };
+    let names = declared.iter().map(|(_, keyword, name)| (keyword.clone(), name.clone())).collect::<Vec<_>>();
+    let mut session = Session {
+        previous: Rc::new(vec![]),
+        reusable: HashMap::new(),
+        ids: HashMap::new(),
+        next_id: 0,
+        nodes: vec![],
+        reused: 0,
+    };
+    if let Some((previous, edit)) = previous {
+        session.ids = kept_ids(&previous.nodes, edit);
+        session.next_id = previous.next_id;
+        let unchanged = previous.declared.iter().map(|(_, keyword, name)| (keyword, name)).eq(names.iter().map(|(keyword, name)| (keyword, name)));
+        if previous.options == *options && unchanged {
+            session.previous = previous.nodes.clone();
+            session.reusable = reusable_nodes(&previous.nodes, edit);
+        }
+    }
+    let context = Context::new(options).with_declared(names).with_session(session);
+    let tree = parse(&source, &context);
+    let session = context.into_session().expect("the context was given a session");
+    let anchors = anchor_nodes(&tree, &session.nodes);
+    let anchored = anchors.iter().map(|(node, id)| (*id, node.clone())).collect();
+    ParsedSource {
+        source,
+        tree,
+        options: options.clone(),
+        declared,
+        nodes: Rc::new(session.nodes),
+        anchors,
+        anchored,
+        next_id: session.next_id,
+        reused: session.reused,
+        rescanned,
+    }
+}
+
+/**
+ * @dev Finds the names that an edited source declares from the names that it declared before
+ *      the edit. Only the input from the last declaration before the edit is lexed again, up to
+ *      the first token after the edit that a previous declaration started at, since from there on
+ *      the tokens are the same as before.
+ * @param source The edited input.
+ * @param previous The declarations of the input before the edit, with where their keywords start.
+ * @param edit The edit that was made to the input.
+ * @return The declarations of the edited input, along with how many characters were lexed again.
+ */
+fn rescan_declarations(
+    source: &Vec<char>,
+    previous: &[(usize, lex_4_25::Token, String)],
+    edit: &TextEdit
+) -> (Vec<(usize, lex_4_25::Token, String)>, usize) {
+    let offset = edit.offset();
+    let shifted = |start: usize| (start as isize + offset) as usize;
+    // The name of the last declaration before the edit may have been edited, so it is read again
+    let kept = previous.iter().rposition(|(start, _, _)| *start < edit.span.start);
+    let start = kept.map_or(0, |index| previous[index].0);
+    let after = &previous[previous.iter().position(|(start, _, _)| *start >= edit.span.end).unwrap_or(previous.len())..];
+    let edit_end = shifted(edit.span.end);
+    let (found, stopped) = parse_4_25::declared_names(source, start, |position| {
+        position >= edit_end && after.binary_search_by_key(&position, |(start, _, _)| shifted(*start)).is_ok()
+    });
+    let mut declared = previous[..kept.unwrap_or(0)].to_vec();
+    declared.extend(found);
+    declared.extend(after.iter()
+        .filter(|(start, _, _)| shifted(*start) >= stopped)
+        .map(|(start, keyword, name)| (shifted(*start), keyword.clone(), name.clone())));
+    (declared, stopped - start)
+}
+
+// Returns the nodes that don't depend on the part of the input that edit replaces
+fn reusable_nodes(nodes: &[ReusableNode], edit: &TextEdit) -> HashMap<(Reusable, usize, usize), (usize, isize)> {
+    let offset = edit.offset();
+    let mut reusable = HashMap::new();
+    for (index, node) in nodes.iter().enumerate() {
+        if node.read_end <= edit.span.start {
+            reusable.insert((node.kind, node.span.start, node.depth), (index, 0));
+        } else if node.span.start >= edit.span.end {
+            let start = (node.span.start as isize + offset) as usize;
+            reusable.insert((node.kind, start, node.depth), (index, offset));
+        }
+    }
+    reusable
+}
+
+// Returns the ids of the nodes whose first token is outside of the part of the input that edit
+// replaces, keyed by where that token is in the edited input
+fn kept_ids(nodes: &[ReusableNode], edit: &TextEdit) -> HashMap<(Reusable, usize, usize), usize> {
+    let offset = edit.offset();
+    let mut ids = HashMap::new();
+    for node in nodes {
+        if node.first_token.end < edit.span.start {
+            ids.insert((node.kind, node.first_token.start, node.depth), node.id);
+        } else if node.first_token.start >= edit.span.end {
+            let start = (node.first_token.start as isize + offset) as usize;
+            ids.insert((node.kind, start, node.depth), node.id);
+        }
+    }
+    ids
+}
+
+/**
+ * @dev Finds the nodes of a tree that the recorded contract members and blocks ended up as. The
+ *      nodes were recorded in the order that they were finished, so they are found in postorder.
+ *      Nothing is found past a recorded node that was changed after it was parsed, which only
+ *      happens when the tree is cut off at its first error.
+ * @param tree The tree that was parsed.
+ * @param nodes The contract members and blocks that were recorded while parsing it.
+ * @return The ids of the nodes of the tree that were recorded.
+ */
+fn anchor_nodes(tree: &ParseTree, nodes: &[ReusableNode]) -> HashMap<NodeId, usize> {
+    let mut anchors = HashMap::new();
+    let mut remaining = nodes.iter().peekable();
+    for (id, tree) in tree.postorder() {
+        let node = match remaining.peek() {
+            Some(node) => node,
+            None => break
+        };
+        if tree.root == node.tree.root && *tree == node.tree {
+            anchors.insert(id, node.id);
+            remaining.next();
+        }
+    }
+    anchors
+}
+
+/**
+ * @dev Parses a nonterminal that can be reused. If an incremental parse is running, a node from
+ *      the previous parse that starts at current_ptr is reused if it can be, and otherwise the
+ *      new node is recorded for the next parse.
+ * @param kind The kind of nonterminal that parse reads.
+ * @param input The characters from the input that should be read
+ * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @param parse Parses the nonterminal.
+ * @return The ParseTree of the nonterminal.
+ */
+pub(crate) fn reuse_or_parse(
+    kind: Reusable,
+    input: &Vec<char>,
+    current_ptr: &mut usize,
+    context: &Context,
+    parse: fn(&Vec<char>, &mut usize, &Context) -> ParseTree
+) -> ParseTree {
+    let session = match context.session() {
+        Some(session) => session,
+        None => return parse(input, current_ptr, context)
+    };
+    let start = *current_ptr;
+    let depth = context.depth();
+    if let Some(tree) = reuse(&mut session.borrow_mut(), kind, start, depth, current_ptr) {
+        return tree;
+    }
+    let nested = session.borrow().nodes.len();
+    let tree = parse(input, current_ptr, context);
+    let (_, first_token) = lex_4_25::next_token_with_span(input, &mut start.clone());
+    let mut session = session.borrow_mut();
+    let id = match session.ids.get(&(kind, first_token.start, depth)) {
+        Some(id) => *id,
+        None => {
+            session.next_id += 1;
+            session.next_id - 1
+        }
+    };
+    session.nodes.push(ReusableNode {
+        id,
+        kind,
+        depth,
+        span: Span::new(start, *current_ptr),
+        first_token,
+        read_end: read_end(input, *current_ptr),
+        tree: tree.clone(),
+        nested,
+    });
+    tree
+}
+
+/**
+ * @dev Returns one past the furthest character that may have been looked at while parsing a node
+ *      that ends at end. The parse functions only ever peek at the token that follows the input
+ *      that they have read, and the lexer looks at most MAX_LOOKAHEAD characters past the end of
+ *      a token, so nothing after that can change how the node was parsed.
+ * @param input The characters from the input that were read.
+ * @param end The position of the lexer after the node was parsed.
+ */
+fn read_end(input: &Vec<char>, end: usize) -> usize {
+    let mut cursor = end;
+    lex_4_25::next_token(input, &mut cursor);
+    cursor + lex_4_25::MAX_LOOKAHEAD
+}
+
+// Reuses the node of the previous parse that starts at start, along with the nodes nested in it
+fn reuse(session: &mut Session, kind: Reusable, start: usize, depth: usize, current_ptr: &mut usize) -> Option<ParseTree> {
+    let (index, offset) = *session.reusable.get(&(kind, start, depth))?;
+    let previous = session.previous.clone();
+    let node = &previous[index];
+    let base = session.nodes.len();
+    for nested in &previous[node.nested..=index] {
+        let shifted = nested.shifted(offset, base + nested.nested - node.nested);
+        session.nodes.push(shifted);
+    }
+    session.reused += 1;
+    let reused = session.nodes.last()?;
+    *current_ptr = reused.span.end;
+    Some(reused.tree.clone())
+}
@@ -1,8 +1,17 @@
 use regex::Regex;
+use super::diagnostics::Span;
 
-#[derive(Clone, Debug, PartialEq)]
+// The furthest that the lexer looks past the end of a token, which it does to tell tokens like `>`
+// and `>>>=` apart
+pub(crate) const MAX_LOOKAHEAD: usize = 3;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum Token {
+    Abstract,
     Address,
+    After,
     AndEquals,
     Anonymous,
     Arrow,
@@ -49,6 +58,9 @@ pub enum Token {
     Bytes30,
     Bytes31,
     Bytes32,
+    Calldata,
+    Case,
+    Catch,
     CloseBrace,
     CloseBracket,
     CloseParenthesis,
@@ -57,11 +69,13 @@ pub enum Token {
     CommentMulti,
     CommentSingle,
     Constant,
+    Constructor,
     Continue,
     Contract,
     Days,
     DecimalNumber(String),
     Decrement,
+    Default,
     Delete,
     Divide,
     DivideEquals,
@@ -78,8 +92,10 @@ pub enum Token {
     Exclamation,
     External,
     False,
+    Final,
     Finney,
     Fixed,
+    FixedMxN(usize, usize),
     For,
     From,
     Function,
@@ -93,8 +109,10 @@ pub enum Token {
     If,
     Illegal,
     Import,
+    In,
     Increment,
     Indexed,
+    Inline,
     Int,
     Int8,
     Int16,
@@ -138,6 +156,7 @@ pub enum Token {
     LogicalAnd,
     LogicalOr,
     Mapping,
+    Match,
     Memory,
     Minus,
     MinusEquals,
@@ -150,6 +169,8 @@ pub enum Token {
     New,
     NoMatch,
     NotEquals,
+    Null,
+    Of,
     OpenBrace,
     OpenBracket,
     OpenParenthesis,
@@ -164,6 +185,7 @@ pub enum Token {
     Public,
     Pure,
     Question,
+    Relocatable,
     Return,
     Returns,
     Seconds,
@@ -173,15 +195,21 @@ pub enum Token {
     ShiftRight,
     ShiftRightEquals,
     StateVariable,
+    Static,
     Storage,
     String,
     StringLiteral(String),
     Struct,
+    Switch,
     Szabo,
     Throw,
     Tilda,
     True,
+    Try,
+    Type,
+    Typeof,
     Ufixed,
+    UfixedMxN(usize, usize),
     Uint,
     Uint8,
     Uint16,
@@ -223,14 +251,18 @@ pub enum Token {
     Weeks,
     Wei,
     While,
+    Whitespace,
     XorEquals,
     Years,
 }
 
+// The predicates list the Tokens that they accept one per arm, which reads more easily than a
+// single matches! pattern
+#[allow(clippy::match_like_matches_macro)]
 impl Token {
     // Returns whether the Token is a unit
     pub fn is_number_unit(&self) -> bool {
-        return match self {
+        match self {
             Token::Days => true,
             Token::Ether => true,
             Token::Finney => true,
@@ -247,7 +279,7 @@ impl Token {
 
     // Returns whether the Token is an int
     pub fn is_int(&self) -> bool {
-        return match self {
+        match self {
             Token::Int => true,
             Token::Int8 => true,
             Token::Int16 => true,
@@ -287,7 +319,7 @@ impl Token {
 
     // Returns whether the token is an unsigned integer
     pub fn is_uint(&self) -> bool {
-        return match self {
+        match self {
             Token::Uint => true,
             Token::Uint8 => true,
             Token::Uint16 => true,
@@ -327,7 +359,7 @@ impl Token {
 
     // Returns whether the Token is a byte, bytes, or bytesXX
     pub fn is_byte(&self) -> bool {
-        return match self {
+        match self {
             Token::Byte => true,
             Token::Bytes => true,
             Token::Bytes1 => true,
@@ -366,10 +398,74 @@ impl Token {
         }
     }
 
+    // Returns whether the Token is whitespace or a comment, which the parser skips
+    pub fn is_trivia(&self) -> bool {
+        match self {
+            Token::CommentMulti  |
+            Token::CommentSingle |
+            Token::NoMatch       |
+            Token::Whitespace => true,
+            _ => false
+        }
+    }
+
+    // Returns whether the Token is a keyword that Solidity reserves for future use
+    pub fn is_reserved(&self) -> bool {
+        match self {
+            Token::Abstract => true,
+            Token::After => true,
+            Token::Case => true,
+            Token::Catch => true,
+            Token::Default => true,
+            Token::Final => true,
+            Token::In => true,
+            Token::Inline => true,
+            Token::Match => true,
+            Token::Null => true,
+            Token::Of => true,
+            Token::Relocatable => true,
+            Token::Static => true,
+            Token::Switch => true,
+            Token::Try => true,
+            Token::Type => true,
+            Token::Typeof => true,
+            _ => false
+        }
+    }
+
+    // Returns whether the Token is a signed fixed point type
+    pub fn is_fixed(&self) -> bool {
+        match self {
+            Token::Fixed => true,
+            Token::FixedMxN(..) => true,
+            _ => false
+        }
+    }
+
+    // Returns whether the Token is an unsigned fixed point type
+    pub fn is_ufixed(&self) -> bool {
+        match self {
+            Token::Ufixed => true,
+            Token::UfixedMxN(..) => true,
+            _ => false
+        }
+    }
+
+    // Returns the (M, N) dimensions of a fixed point type, where M is the number of bits and N is
+    // the number of decimal places. The bare fixed and ufixed keywords are aliases for
+    // fixed128x18 and ufixed128x18.
+    pub fn fixed_dimensions(&self) -> Option<(usize, usize)> {
+        match self {
+            Token::Fixed | Token::Ufixed => Some((128, 18)),
+            Token::FixedMxN(m, n) | Token::UfixedMxN(m, n) => Some((*m, *n)),
+            _ => None
+        }
+    }
+
     // Returns whether the token represents an elementary type
-    // (address, bool, string, var, int, uint, byte)
+    // (address, bool, string, var, int, uint, byte, fixed, ufixed)
     pub fn is_elementary_type(&self) -> bool {
-        return match self {
+        match self {
             Token::Address => true,
             Token::Bool => true,
             Token::String => true,
@@ -377,15 +473,91 @@ impl Token {
             int if int.is_int() => true,
             uint if uint.is_uint() => true,
             byte if byte.is_byte() => true,
+            fixed if fixed.is_fixed() => true,
+            ufixed if ufixed.is_ufixed() => true,
             _ => false
         }
     }
+
+    // Returns the source text that lexes to this token. Tokens that the lexer never produces from
+    // source text, such as EOF and Illegal, are rendered as an empty string.
+    pub fn to_source(&self) -> String {
+        let source = match self {
+            Token::AndEquals => "&=",
+            Token::Arrow => "=>",
+            Token::Assignment => "=",
+            Token::ASMAssign => ":=",
+            Token::BitwiseAnd => "&",
+            Token::BitwiseOr => "|",
+            Token::BitwiseXor => "^",
+            Token::CloseBrace => "}",
+            Token::CloseBracket => "]",
+            Token::CloseParenthesis => ")",
+            Token::Colon => ":",
+            Token::Comma => ",",
+            Token::Decrement => "--",
+            Token::Divide => "/",
+            Token::DivideEquals => "/=",
+            Token::Dot => ".",
+            Token::Equals => "==",
+            Token::Exclamation => "!",
+            Token::GreaterThan => ">",
+            Token::GreaterThanOrEquals => ">=",
+            Token::Increment => "++",
+            Token::LessThan => "<",
+            Token::LessThanOrEquals => "<=",
+            Token::LogicalAnd => "&&",
+            Token::LogicalOr => "||",
+            Token::Minus => "-",
+            Token::MinusEquals => "-=",
+            Token::ModEquals => "%=",
+            Token::Modulus => "%",
+            Token::Multiply => "*",
+            Token::MultiplyEquals => "*=",
+            Token::NotEquals => "!=",
+            Token::OpenBrace => "{",
+            Token::OpenBracket => "[",
+            Token::OpenParenthesis => "(",
+            Token::OrEquals => "|=",
+            Token::Plus => "+",
+            Token::PlusEquals => "+=",
+            Token::Power => "**",
+            Token::Question => "?",
+            Token::Semicolon => ";",
+            Token::ShiftLeft => "<<",
+            Token::ShiftLeftEquals => "<<=",
+            Token::ShiftRight => ">>",
+            Token::ShiftRightEquals => ">>=",
+            Token::Tilda => "~",
+            Token::XorEquals => "^=",
+            Token::DecimalNumber(value) |
+            Token::HexLiteral(value)    |
+            Token::HexNumber(value)     |
+            Token::Identifier(value)    |
+            Token::StringLiteral(value) |
+            Token::Version(value) => return value.clone(),
+            Token::FixedMxN(m, n) => return format!("fixed{}x{}", m, n),
+            Token::UfixedMxN(m, n) => return format!("ufixed{}x{}", m, n),
+            Token::CommentMulti     |
+            Token::CommentSingle    |
+            Token::EOF              |
+            Token::EventParameter   |
+            Token::Illegal          |
+            Token::NoMatch          |
+            Token::Parameter        |
+            Token::StateVariable    |
+            Token::UserDefinedTypeName |
+            Token::Whitespace => "",
+            // Every remaining token is a keyword, and each keyword is spelled like its variant.
+            keyword => return format!("{:?}", keyword).to_lowercase()
+        };
+        String::from(source)
+    }
 }
 
 trait LineMatch {
     fn match_idx(&self, idx: usize, val: char) -> bool;
     fn is_digit_at(&self, idx: usize) -> bool;
-    fn is_rational_at(&self, idx: usize) -> bool;
     fn is_hex_digit_at(&self, idx: usize) -> bool;
     fn is_hex_delim_at(&self, idx: usize) -> bool;
     fn is_whitespace_at(&self, idx: usize) -> bool;
@@ -401,21 +573,14 @@ impl LineMatch for Vec<char> {
 
     fn is_digit_at(&self, idx: usize) -> bool {
         match self.get(idx) {
-            Some(v) => v.is_digit(10),
+            Some(v) => v.is_ascii_digit(),
             None => false
         }
     }
 
     fn is_hex_digit_at(&self, idx: usize) -> bool {
         match self.get(idx) {
-            Some(v) => v.is_digit(16),
-            None => false
-        }
-    }
-
-    fn is_rational_at(&self, idx: usize) -> bool {
-        match self.get(idx) {
-            Some(v) => v.is_rational(),
+            Some(v) => v.is_ascii_hexdigit(),
             None => false
         }
     }
@@ -440,72 +605,82 @@ trait CharExt {
     fn starts_iden_or_keyword(&self) -> bool;
     fn is_iden_or_keyword_part(&self) -> bool;
     fn is_whitespace(&self) -> bool;
-    fn is_rational(&self) -> bool;
 }
 
 impl CharExt for char {
     // Not allowed: Leading 0, leading 'e'
     fn starts_rational(&self) -> bool {
-        return (self.is_digit(10) || *self == '.') && *self != '0';
+        (self.is_ascii_digit() || *self == '.') && *self != '0'
     }
 
     // If self could be the first character of an identifier, returns true
     fn starts_iden_or_keyword(&self) -> bool {
-        return *self == '_' || *self == '$' || self.is_ascii_alphabetic();
+        *self == '_' || *self == '$' || self.is_ascii_alphabetic()
     }
 
     // If self could be a component of an identifier or keyword, returns true
     fn is_iden_or_keyword_part(&self) -> bool {
-        return self.starts_iden_or_keyword() || self.is_digit(10);
-    }
-
-    // Returns true if the char could be a part of a rational literal
-    fn is_rational(&self) -> bool {
-        return
-            *self == 'e' ||
-            *self == 'E' ||
-            *self == '.' ||
-            self.is_digit(10);
+        self.starts_iden_or_keyword() || self.is_ascii_digit()
     }
 
     // If self is whitespace, returns true
     fn is_whitespace(&self) -> bool {
-        return *self == ' ' || *self == '\n' || *self == '\t' || *self == '\r';
+        *self == ' ' || *self == '\n' || *self == '\t' || *self == '\r'
     }
 }
 
+#[cfg(test)]
 trait AsString {
     fn as_string(&self) -> String;
 }
 
+#[cfg(test)]
 impl AsString for Vec<char> {
     fn as_string(&self) -> String {
-        return self.into_iter().collect();
+        self.iter().collect()
     }
 }
 
 pub fn to_chars(string: &str) -> Vec<char> {
-    return string.chars().collect::<Vec<char>>();
+    string.chars().collect::<Vec<char>>()
 }
 
 pub fn to_identifier(string: &str) -> Token {
-    return Token::Identifier(string.to_string());
+    Token::Identifier(string.to_string())
 }
 
 pub fn to_string_literal(string: &str) -> Token {
-    return Token::StringLiteral(string.to_string());
+    Token::StringLiteral(string.to_string())
 }
 
 pub fn to_decimal_number(string: &str) -> Token {
-    return Token::DecimalNumber(string.to_string());
+    Token::DecimalNumber(string.to_string())
 }
 
 pub fn to_hex_number(string: &str) -> Token {
-    return Token::HexNumber(string.to_string());
+    Token::HexNumber(string.to_string())
 }
 
 pub fn to_hex_literal(string: &str) -> Token {
-    return Token::HexLiteral(string.to_string());
+    Token::HexLiteral(string.to_string())
+}
+
+/**
+ * Returns the keywords that name elementary types, including every sized int, uint and bytes
+ * type. Fixed point types are not listed, since there are too many combinations of M and N.
+ */
+pub fn elementary_type_keywords() -> Vec<String> {
+    let mut keywords = vec![
+        "address", "bool", "string", "var", "byte", "bytes", "int", "uint", "fixed", "ufixed"
+    ].into_iter().map(String::from).collect::<Vec<String>>();
+    for bits in (8..=256).step_by(8) {
+        keywords.push(format!("int{}", bits));
+        keywords.push(format!("uint{}", bits));
+    }
+    for size in 1..=32 {
+        keywords.push(format!("bytes{}", size));
+    }
+    keywords
 }
 
 /**
@@ -518,8 +693,12 @@ fn match_collected(collected: String) -> Token {
     let hex_re = Regex::new(r"^0x[0-9a-fA-F]*$").unwrap();
     let hex_literal_re = Regex::new(r#"^hex(\\"([0-9a-fA-F]{2})*\\"|'([0-9a-fA-F]{2})*')$"#).unwrap();
     let version_re = Regex::new(r"^\^?[0-9]+\.[0-9]+\.[0-9]+").unwrap();
-    return match collected.as_ref() {
+    // Like solc, sizes with leading zeros don't name a type, so those words are identifiers
+    let fixed_re = Regex::new(r"^u?fixed[1-9][0-9]*x(0|[1-9][0-9]*)$").unwrap();
+    match collected.as_ref() {
+        "abstract" => Token::Abstract,
         "address" => Token::Address,
+        "after" => Token::After,
         "anonymous" => Token::Anonymous,
         "as" => Token::As,
         "assembly" => Token::Assembly,
@@ -559,10 +738,15 @@ fn match_collected(collected: String) -> Token {
         "bytes30" => Token::Bytes30,
         "bytes31" => Token::Bytes31,
         "bytes32" => Token::Bytes32,
+        "calldata" => Token::Calldata,
+        "case" => Token::Case,
+        "catch" => Token::Catch,
         "constant" => Token::Constant,
+        "constructor" => Token::Constructor,
         "continue" => Token::Continue,
         "contract" => Token::Contract,
         "days" => Token::Days,
+        "default" => Token::Default,
         "delete" => Token::Delete,
         "do" => Token::Do,
         "else" => Token::Else,
@@ -572,6 +756,7 @@ fn match_collected(collected: String) -> Token {
         "event" => Token::Event,
         "external" => Token::External,
         "false" => Token::False,
+        "final" => Token::Final,
         "finney" => Token::Finney,
         "fixed" => Token::Fixed,
         "for" => Token::For,
@@ -581,7 +766,9 @@ fn match_collected(collected: String) -> Token {
         "hours" => Token::Hours,
         "if" => Token::If,
         "import" => Token::Import,
+        "in" => Token::In,
         "indexed" => Token::Indexed,
+        "inline" => Token::Inline,
         "int" => Token::Int,
         "int8" => Token::Int8,
         "int16" => Token::Int16,
@@ -621,24 +808,33 @@ fn match_collected(collected: String) -> Token {
         "let" => Token::Let,
         "library" => Token::Library,
         "mapping" => Token::Mapping,
+        "match" => Token::Match,
         "memory" => Token::Memory,
         "minutes" => Token::Minutes,
         "modifier" => Token::Modifier,
         "new" => Token::New,
+        "null" => Token::Null,
+        "of" => Token::Of,
         "payable" => Token::Payable,
         "pragma" => Token::Pragma,
         "private" => Token::Private,
         "public" => Token::Public,
         "pure" => Token::Pure,
+        "relocatable" => Token::Relocatable,
         "return" => Token::Return,
         "returns" => Token::Returns,
         "seconds" => Token::Seconds,
+        "static" => Token::Static,
         "storage" => Token::Storage,
         "string" => Token::String,
         "struct" => Token::Struct,
+        "switch" => Token::Switch,
         "szabo" => Token::Szabo,
         "throw" => Token::Throw,
         "true" => Token::True,
+        "try" => Token::Try,
+        "type" => Token::Type,
+        "typeof" => Token::Typeof,
         "ufixed" => Token::Ufixed,
         "uint" => Token::Uint,
         "uint8" => Token::Uint8,
@@ -681,6 +877,7 @@ fn match_collected(collected: String) -> Token {
         "while" => Token::While,
         "years" => Token::Years,
         "_" => to_identifier("_"),
+        fixed if fixed_re.is_match(fixed) => match_fixed(fixed),
         id if id_re.is_match(id) => Token::Identifier(id.to_string()),
         hex if hex_re.is_match(hex) => Token::HexNumber(hex.to_string()),
         num if decimal_re.is_match(num) => Token::DecimalNumber(num.to_string()),
@@ -690,270 +887,302 @@ fn match_collected(collected: String) -> Token {
     }
 }
 
+/**
+ * Matches a collected fixedMxN or ufixedMxN keyword with its corresponding Token
+ * M must be a multiple of 8 in 8..256 and N must be in 0..80, otherwise Token::Illegal
+ * is returned
+ */
+fn match_fixed(collected: &str) -> Token {
+    let unsigned = collected.starts_with('u');
+    let dimensions = collected.trim_start_matches('u').trim_start_matches("fixed");
+    let mut parts = dimensions.split('x');
+    let m = parts.next().and_then(|m| m.parse::<usize>().ok());
+    let n = parts.next().and_then(|n| n.parse::<usize>().ok());
+    match (m, n) {
+        (Some(m), Some(n)) if (8..=256).contains(&m) && m % 8 == 0 && n <= 80 => {
+            if unsigned {
+                Token::UfixedMxN(m, n)
+            } else {
+                Token::FixedMxN(m, n)
+            }
+        }
+        _ => Token::Illegal
+    }
+}
+
 /**
  * Matches . at line[*cur] with Token::Dot
  */
 fn match_period(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.is_digit_at(*cur + 1) {
-        return match_rational(line, cur);
+        match_rational(line, cur)
     } else {
-        return Token::Dot;
+        Token::Dot
     }
 }
 
 /**
  * Matches : at line[*cur] with its corresponding Token
- * :  | Colon
- * := | ASMAssign
+ * `:`  | Colon
+ * `:=` | ASMAssign
  */
 fn match_colon(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::ASMAssign;
+        Token::ASMAssign
     } else {
-        return Token::Colon;
+        Token::Colon
     }
 }
 
 /**
  * Matches = at line[*cur] with its corresponding Token
- * =  | Assignment
- * == | Equals
- * => | Arrow
+ * `=`  | Assignment
+ * `==` | Equals
+ * `=>` | Arrow
  */
 fn match_equals(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::Equals;
+        Token::Equals
     } else if line.match_idx(*cur + 1, '>') {
         *cur += 1;
-        return Token::Arrow;
+        Token::Arrow
     } else {
-        return Token::Assignment;
+        Token::Assignment
     }
 }
 
 /**
  * Matches + at line[*cur] with its corresponding Token
- * +  | Plus
- * ++ | Increment
- * += | PlusEquals
+ * `+`  | Plus
+ * `++` | Increment
+ * `+=` | PlusEquals
  */
 fn match_plus(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '+') {
         *cur += 1;
-        return Token::Increment;
+        Token::Increment
     } else if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::PlusEquals;
+        Token::PlusEquals
     } else {
-        return Token::Plus;
+        Token::Plus
     }
 }
 
 /**
  * Matches - at line[*cur] with its corresponding Token
- * -  | Minus
- * -- | Decrement
- * -= | MinusEquals
+ * `-`  | Minus
+ * `--` | Decrement
+ * `-=` | MinusEquals
  */
 fn match_minus(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '-') {
         *cur += 1;
-        return Token::Decrement;
+        Token::Decrement
     } else if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::MinusEquals;
+        Token::MinusEquals
     } else {
-        return Token::Minus;
+        Token::Minus
     }
 }
 
 /**
  * Matches * at line[*cur] with its corresponding Token
- * *  | Multiply
- * ** | Power
- * *= | MultiplyEquals
+ * `*`  | Multiply
+ * `**` | Power
+ * `*=` | MultiplyEquals
  */
 fn match_star(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '*') {
         *cur += 1;
-        return Token::Power;
+        Token::Power
     } else if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::MultiplyEquals;
+        Token::MultiplyEquals
     } else {
-        return Token::Multiply;
+        Token::Multiply
     }
 }
 
 /**
- * Matches / at line[*cur] with its corresponding Token
- * /  | Divide
- * // | CommentSingle
- * /* | CommentMulti */
- * /= | DivideEquals
+ * Matches / at line[*cur] with its corresponding Token. A single line
+ * comment is matched up to the end of the line and a multi line comment
+ * up to its end, or to the end of the input if it is never closed.
+ * `/`  | Divide
+ * `//` | CommentSingle
+ * `/*` | CommentMulti */
+ * `/=` | DivideEquals
  */
 fn match_slash(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::DivideEquals;
+        Token::DivideEquals
     } else if line.match_idx(*cur + 1, '/') {
         *cur += 1;
-        return Token::CommentSingle;
+        while *cur + 1 < line.len() && line[*cur + 1] != '\n' {
+            *cur += 1;
+        }
+        Token::CommentSingle
     } else if line.match_idx(*cur + 1, '*') {
-        *cur += 1;
-        return Token::CommentMulti;
+        *cur += 2;
+        while *cur + 1 < line.len() && !(line[*cur] == '*' && line[*cur + 1] == '/') {
+            *cur += 1;
+        }
+        *cur = (*cur + 1).min(line.len() - 1);
+        Token::CommentMulti
     } else {
-        return Token::Divide;
+        Token::Divide
     }
 }
 
 /**
  * Matches > at line[*cur] with its corresponding Token
- * >    | GreaterThen
- * >=   | GreaterThanOrEquals
- * >>   | ShiftRight
- * >>=  | ShiftRightEquals
- * >>>  | TODO
- * >>>= | TODO
+ * `>`    | GreaterThen
+ * `>=`   | GreaterThanOrEquals
+ * `>>`   | ShiftRight
+ * `>>=`  | ShiftRightEquals
+ * `>>>`  | TODO
+ * `>>>=` | TODO
  */
 fn match_rarrow(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::GreaterThanOrEquals;
+        Token::GreaterThanOrEquals
     } else if line.match_idx(*cur + 1, '>') {
         if line.match_idx(*cur + 2, '=') {
             *cur += 2;
-            return Token::ShiftRightEquals;
+            Token::ShiftRightEquals
         } else if line.match_idx(*cur + 2, '>') {
             if line.match_idx(*cur + 3, '=') {
                 *cur += 3;
-                return Token::Illegal; // TODO
+                Token::Illegal// TODO
             } else {
                 *cur += 2;
-                return Token::Illegal; // TODO
+                Token::Illegal// TODO
             }
         } else {
             *cur += 1;
-            return Token::ShiftRight;
+            Token::ShiftRight
         }
     } else {
-        return Token::GreaterThan;
+        Token::GreaterThan
     }
 }
 
 /**
  * Matches < at line[*cur] with its corresponding Token
- * <    | LessThen
- * <=   | LessThanOrEquals
- * <<   | ShiftLeft
- * <<=  | ShiftLeftEquals
+ * `<`    | LessThen
+ * `<=`   | LessThanOrEquals
+ * `<<`   | ShiftLeft
+ * `<<=`  | ShiftLeftEquals
  */
 fn match_larrow(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::LessThanOrEquals;
+        Token::LessThanOrEquals
     } else if line.match_idx(*cur + 1, '<') {
         if line.match_idx(*cur + 2, '=') {
             *cur += 2;
-            return Token::ShiftLeftEquals;
+            Token::ShiftLeftEquals
         } else {
             *cur += 1;
-            return Token::ShiftLeft;
+            Token::ShiftLeft
         }
     } else {
-        return Token::LessThan;
+        Token::LessThan
     }
 }
 
 /**
  * Matches ! at line[*cur] with its corresponding Token
- * !  | Exclamation
- * != | NotEquals
+ * `!`  | Exclamation
+ * `!=` | NotEquals
  */
 fn match_exclamation(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::NotEquals;
+        Token::NotEquals
     } else {
-        return Token::Exclamation;
+        Token::Exclamation
     }
 }
 
 /**
  * Matches % at line[*cur] with its corresponding Token
- * %  | Modulus
- * %= | ModEquals
+ * `%`  | Modulus
+ * `%=` | ModEquals
  */
 fn match_percent(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::ModEquals;
+        Token::ModEquals
     } else {
-        return Token::Modulus;
+        Token::Modulus
     }
 }
 
 /**
  * Matches & at line[*cur] with its corresponding Token
- * &  | BitwiseAnd
- * && | LogicalAnd
- * &= | AndEquals
+ * `&`  | BitwiseAnd
+ * `&&` | LogicalAnd
+ * `&=` | AndEquals
  */
 fn match_and(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '&') {
         *cur += 1;
-        return Token::LogicalAnd;
+        Token::LogicalAnd
     } else if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::AndEquals;
+        Token::AndEquals
     } else {
-        return Token::BitwiseAnd;
+        Token::BitwiseAnd
     }
 }
 
 /**
  * Matches | at line[*cur] with its corresponding Token
- * |  | BitwiseOr
- * || | LogicalOr
- * |= | OrEquals
+ * `|`  | BitwiseOr
+ * `||` | LogicalOr
+ * `|=` | OrEquals
  */
 fn match_or(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '|') {
         *cur += 1;
-        return Token::LogicalOr;
+        Token::LogicalOr
     } else if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::OrEquals;
+        Token::OrEquals
     } else {
-        return Token::BitwiseOr;
+        Token::BitwiseOr
     }
 }
 
 /**
  * Matches | at line[*cur] with its corresponding Token
- * ^  | BitwiseXor
- * ^= | XorEquals
+ * `^`  | BitwiseXor
+ * `^=` | XorEquals
  */
 fn match_xor(line: &Vec<char>, cur: &mut usize) -> Token {
     if line.match_idx(*cur + 1, '=') {
         *cur += 1;
-        return Token::XorEquals;
+        Token::XorEquals
     } else {
-        return Token::BitwiseXor;
+        Token::BitwiseXor
     }
 }
 
 /**
  * Matches a string literal at line[*cur] with its corresponding Token
  */
-fn match_string(line: &Vec<char>, cur: &mut usize) -> Token {
+fn match_string(line: &[char], cur: &mut usize) -> Token {
     let first_quote = line[*cur].to_string();
-    let mut collected = String::from(first_quote.clone());
+    let mut collected = first_quote.clone();
     *cur += 1;
     while *cur < line.len() {
-        if line[*cur].to_string() == r"\".to_string() {
+        if line[*cur] == '\\' {
             return Token::Illegal; // TODO handle escapes
         } else if line[*cur].to_string() == first_quote {
             collected.push(line[*cur]);
@@ -1007,7 +1236,7 @@ fn match_identifier_or_keyword(line: &Vec<char>, cur: &mut usize) -> Token {
         *cur += 1;
     }
     *cur -= 1;
-    let mut result = match_collected(collected);
+    let result = match_collected(collected);
     // Special case - found "hex"
     if result == Token::Hex {
         if line.match_idx(*cur + 1, '"') || line.match_idx(*cur + 1, '\'') {
@@ -1017,7 +1246,7 @@ fn match_identifier_or_keyword(line: &Vec<char>, cur: &mut usize) -> Token {
             return Token::Illegal;
         }
     }
-    return result;
+    result
 }
 
 /**
@@ -1042,9 +1271,9 @@ fn match_hex_number(line: &Vec<char>, cur: &mut usize) -> Token {
 
     // Cannot only have '0x'
     if collected.len() <= 2 {
-        return Token::Illegal;
+        Token::Illegal
     } else {
-        return Token::HexNumber(collected);
+        Token::HexNumber(collected)
     }
 }
 
@@ -1083,7 +1312,7 @@ fn match_rational(line: &Vec<char>, cur: &mut usize) -> Token {
                 // (Trailing decimals are allowed, though!)
                 // Allowed: { var a = 14.; }
                 // Not allowed: { var a = 14e; }
-                if *cur + 1 == line.len() || !line[*cur + 1].is_digit(10) {
+                if *cur + 1 == line.len() || !line[*cur + 1].is_ascii_digit() {
                     return Token::Illegal;
                 } else {
                     exponent_found = true;
@@ -1105,70 +1334,125 @@ fn match_rational(line: &Vec<char>, cur: &mut usize) -> Token {
 
 /**
  * Returns the next Token found in the line and increments cur
- * to the end of the Token in the parsed line
+ * to the end of the Token in the parsed line. Whitespace and
+ * comments are skipped, and cur always moves past an Illegal
+ * Token so that the parser can carry on after it. Literals that
+ * run to the end of the line without being closed are Illegal.
  */
 pub fn next_token(line: &Vec<char>, cur: &mut usize) -> Token {
+    next_token_and_start(line, cur).0
+}
+
+// Returns the next Token found in the line along with the index that
+// it starts at, which is past any whitespace and comments before it
+fn next_token_and_start(line: &Vec<char>, cur: &mut usize) -> (Token, usize) {
     loop {
         if *cur >= line.len() {
-            return Token::EOF;
+            return (Token::EOF, line.len());
         }
 
-        let t = match line[*cur] {
-            ';' => Token::Semicolon,
-            '{' => Token::OpenBrace,
-            '}' => Token::CloseBrace,
-            '(' => Token::OpenParenthesis,
-            ')' => Token::CloseParenthesis,
-            '[' => Token::OpenBracket,
-            ']' => Token::CloseBracket,
-            '?' => Token::Question,
-            ',' => Token::Comma,
-            '~' => Token::Tilda,
-            '.' => match_period(line, cur),
-            ':' => match_colon(line, cur),          // : :=
-            '=' => match_equals(line, cur),         // = == =>
-            '+' => match_plus(line, cur),           // + ++ +=
-            '-' => match_minus(line, cur),          // - -- -=
-            '*' => match_star(line, cur),           // * ** *=
-            '/' => match_slash(line, cur),          // / // /* /=
-            '>' => match_rarrow(line, cur),         // > >= >> >>= >>> >>>=
-            '<' => match_larrow(line, cur),         // < <= << <<=
-            '!' => match_exclamation(line, cur),    // ! !=
-            '%' => match_percent(line, cur),        // % %=
-            '&' => match_and(line, cur),            // & && &=
-            '|' => match_or(line, cur),             // | || |=
-            '^' => match_xor(line, cur),            // ^ ^=
-            '"' | '\'' => match_string(line, cur),
-            '0' => {
-                if line.match_idx(*cur + 1, ' ') {
-                    to_decimal_number("0")
-                } else if line.is_hex_delim_at(*cur + 1) {
-                    match_hex_number(line, cur)
-                } else if line.match_idx(*cur + 1, '.') {
-                    match_rational(line, cur)
-                } else {
-                    Token::Illegal
-                }
-            },
-            non if non.is_whitespace() => skip_whitespace(line, cur),
-            num if num.starts_rational() => match_rational(line, cur),
-            chr if chr.starts_iden_or_keyword() => match_identifier_or_keyword(line, cur),
-            _ => Token::Illegal
-        };
+        let start = *cur;
+        let t = match_lexeme(line, cur);
 
-        if t == Token::Illegal {
-            return t;
-        } else {
-            *cur += 1;
-            if t != Token::NoMatch {
-                return t;
-            } else if *cur >= line.len() {
-                return Token::EOF;
-            }
+        *cur = (*cur + 1).min(line.len());
+        if t == Token::EOF {
+            return (Token::Illegal, start);
+        } else if !t.is_trivia() {
+            return (t, start);
+        } else if *cur >= line.len() {
+            return (Token::EOF, line.len());
         }
     }
 }
 
+/**
+ * Matches the lexeme that starts at line[*cur], leaving cur on its
+ * last character. Whitespace is matched as Token::NoMatch.
+ */
+fn match_lexeme(line: &Vec<char>, cur: &mut usize) -> Token {
+    match line[*cur] {
+        ';' => Token::Semicolon,
+        '{' => Token::OpenBrace,
+        '}' => Token::CloseBrace,
+        '(' => Token::OpenParenthesis,
+        ')' => Token::CloseParenthesis,
+        '[' => Token::OpenBracket,
+        ']' => Token::CloseBracket,
+        '?' => Token::Question,
+        ',' => Token::Comma,
+        '~' => Token::Tilda,
+        '.' => match_period(line, cur),
+        ':' => match_colon(line, cur),          // : :=
+        '=' => match_equals(line, cur),         // = == =>
+        '+' => match_plus(line, cur),           // + ++ +=
+        '-' => match_minus(line, cur),          // - -- -=
+        '*' => match_star(line, cur),           // * ** *=
+        '/' => match_slash(line, cur),          // / // /* /=
+        '>' => match_rarrow(line, cur),         // > >= >> >>= >>> >>>=
+        '<' => match_larrow(line, cur),         // < <= << <<=
+        '!' => match_exclamation(line, cur),    // ! !=
+        '%' => match_percent(line, cur),        // % %=
+        '&' => match_and(line, cur),            // & && &=
+        '|' => match_or(line, cur),             // | || |=
+        '^' => match_xor(line, cur),            // ^ ^=
+        '"' | '\'' => match_string(line, cur),
+        '0' => {
+            if line.is_hex_delim_at(*cur + 1) {
+                match_hex_number(line, cur)
+            } else if line.match_idx(*cur + 1, '.') {
+                match_rational(line, cur)
+            } else if !line.get(*cur + 1).is_some_and(|next| next.is_iden_or_keyword_part()) {
+                to_decimal_number("0")
+            } else {
+                Token::Illegal
+            }
+        },
+        non if non.is_whitespace() => skip_whitespace(line, cur),
+        num if num.starts_rational() => match_rational(line, cur),
+        chr if chr.starts_iden_or_keyword() => match_identifier_or_keyword(line, cur),
+        _ => Token::Illegal
+    }
+}
+
+/**
+ * Returns the next Token found in the line along with the Span of
+ * characters that it covers, without skipping whitespace or comments.
+ * Whitespace is returned as Token::Whitespace. Unlike next_token, cur
+ * always moves past an Illegal Token, so every character of the line
+ * is covered by exactly one of the returned Spans. Literals that run
+ * to the end of the line without being closed are Illegal.
+ */
+pub fn next_token_or_trivia(line: &Vec<char>, cur: &mut usize) -> (Token, Span) {
+    let start = *cur;
+    if start >= line.len() {
+        return (Token::EOF, Span::new(line.len(), line.len()));
+    }
+    let token = match match_lexeme(line, cur) {
+        Token::NoMatch => Token::Whitespace,
+        Token::EOF => Token::Illegal,
+        token => token
+    };
+    *cur = ((*cur).max(start) + 1).min(line.len());
+    (token, Span::new(start, *cur))
+}
+
+/**
+ * Returns the next Token found in the line along with the Span of characters that it covers.
+ * Leading whitespace and comments are not included in the Span.
+ */
+pub fn next_token_with_span(line: &Vec<char>, cur: &mut usize) -> (Token, Span) {
+    let (token, start) = next_token_and_start(line, cur);
+    (token, Span::new(start, (*cur).max(start)))
+}
+
+// Return the next token in the line and its Span, without incrementing cur
+pub fn peek_token_with_span(line: &Vec<char>, cur: &mut usize) -> (Token, Span) {
+    let old = *cur;
+    let next = next_token_with_span(line, cur);
+    *cur = old;
+    next
+}
+
 // Return the next token in the line, without incrementing cur
 pub fn peek_token(line: &Vec<char>, cur: &mut usize) -> Token {
     let old = *cur;
@@ -1186,7 +1470,7 @@ mod tests {
     }
 
     fn expect_next_token(s: &Vec<char>, cur: &mut usize, t: Token) {
-        match next_token(&s, cur) {
+        match next_token(s, cur) {
             ref next if *next == t => (),
             actual => fail_test(t, actual)
         };
@@ -1320,16 +1604,59 @@ mod tests {
 
     #[test]
     fn test_comment_single() {
-        let s = to_chars("//");
+        let s = to_chars("// Returns the owner\nowner");
         let cur = &mut 0;
-        expect_next_token(&s, cur, Token::CommentSingle);
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::CommentSingle, Span::new(0, 20)));
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::Whitespace, Span::new(20, 21)));
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::Identifier(String::from("owner")));
     }
 
     #[test]
     fn test_comment_multi() {
-        let s = to_chars("/*");
+        let s = to_chars("/** @dev * / */ owner /*");
+        let cur = &mut 0;
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::CommentMulti, Span::new(0, 15)));
         let cur = &mut 0;
-        expect_next_token(&s, cur, Token::CommentMulti);
+        expect_next_token(&s, cur, Token::Identifier(String::from("owner")));
+        // An unclosed comment runs to the end of the input
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::Whitespace, Span::new(21, 22)));
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::CommentMulti, Span::new(22, 24)));
+        expect_next_token(&s, cur, Token::EOF);
+    }
+
+    #[test]
+    fn test_trivia() {
+        let s = to_chars(" \t#x");
+        let cur = &mut 0;
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::Whitespace, Span::new(0, 2)));
+        // Illegal characters are still covered by a span
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::Illegal, Span::new(2, 3)));
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::Identifier(String::from("x")), Span::new(3, 4)));
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::EOF, Span::new(4, 4)));
+    }
+
+    #[test]
+    fn test_illegal() {
+        let s = to_chars("# a");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::Illegal);
+        expect_next_token(&s, cur, Token::Identifier(String::from("a")));
+        expect_next_token(&s, cur, Token::EOF);
+    }
+
+    #[test]
+    fn test_trivia_unclosed_string() {
+        let s = to_chars("a \"bc");
+        let cur = &mut 0;
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::Identifier(String::from("a")), Span::new(0, 1)));
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::Whitespace, Span::new(1, 2)));
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::Illegal, Span::new(2, 5)));
+        assert_eq!(next_token_or_trivia(&s, cur), (Token::EOF, Span::new(5, 5)));
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::Identifier(String::from("a")));
+        assert_eq!(next_token_with_span(&s, cur), (Token::Illegal, Span::new(2, 5)));
+        expect_next_token(&s, cur, Token::EOF);
     }
 
     #[test]
@@ -1577,6 +1904,18 @@ mod tests {
         expect_next_token(&s, cur, to_decimal_number("0.1"));
     }
 
+    #[test]
+    fn test_numbers_zero() {
+        let s = to_chars("(0);0");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::OpenParenthesis);
+        expect_next_token(&s, cur, to_decimal_number("0"));
+        expect_next_token(&s, cur, Token::CloseParenthesis);
+        expect_next_token(&s, cur, Token::Semicolon);
+        expect_next_token(&s, cur, to_decimal_number("0"));
+        expect_next_token(&s, cur, Token::EOF);
+    }
+
     #[test]
     fn test_numbers_2() {
         let s = to_chars("01234");
@@ -1700,6 +2039,13 @@ mod tests {
         expect_next_token(&s, cur, Token::Bytes32);
     }
 
+    #[test]
+    fn test_calldata() {
+        let s = to_chars("calldata");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::Calldata);
+    }
+
     #[test]
     fn test_constant() {
         let s = to_chars("constant");
@@ -1707,6 +2053,13 @@ mod tests {
         expect_next_token(&s, cur, Token::Constant);
     }
 
+    #[test]
+    fn test_constructor() {
+        let s = to_chars("constructor");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::Constructor);
+    }
+
     #[test]
     fn test_continue() {
         let s = to_chars("continue");
@@ -1805,6 +2158,42 @@ mod tests {
         expect_next_token(&s, cur, Token::Fixed);
     }
 
+    #[test]
+    fn test_fixed128x18() {
+        let s = to_chars("fixed128x18");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::FixedMxN(128, 18));
+    }
+
+    #[test]
+    fn test_fixed8x0() {
+        let s = to_chars("fixed8x0");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::FixedMxN(8, 0));
+    }
+
+    #[test]
+    fn test_fixed_invalid_bits() {
+        let s = to_chars("fixed12x2");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::Illegal);
+    }
+
+    #[test]
+    fn test_fixed_invalid_decimals() {
+        let s = to_chars("fixed256x81");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::Illegal);
+    }
+
+    #[test]
+    fn test_fixed_leading_zeros() {
+        let s = to_chars("fixed0128x18 ufixed128x018");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, to_identifier("fixed0128x18"));
+        expect_next_token(&s, cur, to_identifier("ufixed128x018"));
+    }
+
     #[test]
     fn test_for() {
         let s = to_chars("for");
@@ -2078,6 +2467,20 @@ mod tests {
         expect_next_token(&s, cur, Token::Ufixed);
     }
 
+    #[test]
+    fn test_ufixed256x80() {
+        let s = to_chars("ufixed256x80");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::UfixedMxN(256, 80));
+    }
+
+    #[test]
+    fn test_ufixed_invalid_bits() {
+        let s = to_chars("ufixed264x18");
+        let cur = &mut 0;
+        expect_next_token(&s, cur, Token::Illegal);
+    }
+
     #[test]
     fn test_uint() {
         let s = to_chars("uint");
@@ -2155,6 +2558,74 @@ mod tests {
         expect_next_token(&s, cur, Token::Years);
     }
 
+    #[test]
+    fn test_reserved_keywords() {
+        let reserved = vec![
+            ("abstract", Token::Abstract),
+            ("after", Token::After),
+            ("case", Token::Case),
+            ("catch", Token::Catch),
+            ("default", Token::Default),
+            ("final", Token::Final),
+            ("in", Token::In),
+            ("inline", Token::Inline),
+            ("match", Token::Match),
+            ("null", Token::Null),
+            ("of", Token::Of),
+            ("relocatable", Token::Relocatable),
+            ("static", Token::Static),
+            ("switch", Token::Switch),
+            ("try", Token::Try),
+            ("type", Token::Type),
+            ("typeof", Token::Typeof),
+        ];
+        for (keyword, token) in reserved {
+            let s = to_chars(keyword);
+            let cur = &mut 0;
+            assert!(token.is_reserved());
+            expect_next_token(&s, cur, token);
+        }
+    }
+
+    #[test]
+    fn test_to_source() {
+        let s = to_chars("mapping ( uint8 => fixed8x1 [ ] ) >>= 0x1f interface 'a'");
+        let cur = &mut 0;
+        let mut tokens = vec![];
+        loop {
+            match next_token(&s, cur) {
+                Token::EOF => break,
+                token => tokens.push(token.to_source())
+            }
+        }
+        assert_eq!(tokens.join(" "), s.iter().collect::<String>());
+    }
+
+    #[test]
+    fn test_next_token_with_span() {
+        let s = to_chars("  uint256 type;");
+        let cur = &mut 0;
+        assert_eq!(next_token_with_span(&s, cur), (Token::Uint256, Span::new(2, 9)));
+        assert_eq!(next_token_with_span(&s, cur), (Token::Type, Span::new(10, 14)));
+        assert_eq!(next_token_with_span(&s, cur), (Token::Semicolon, Span::new(14, 15)));
+        // Comments before a token are skipped like whitespace
+        let s = to_chars("uint /* x */ type // y\n;");
+        let cur = &mut 0;
+        assert_eq!(next_token_with_span(&s, cur), (Token::Uint, Span::new(0, 4)));
+        assert_eq!(next_token_with_span(&s, cur), (Token::Type, Span::new(13, 17)));
+        assert_eq!(next_token_with_span(&s, cur), (Token::Semicolon, Span::new(23, 24)));
+        assert_eq!(next_token_with_span(&s, cur), (Token::EOF, Span::new(24, 24)));
+    }
+
+    #[test]
+    fn test_elementary_type_keywords() {
+        for keyword in elementary_type_keywords() {
+            let s = to_chars(&keyword);
+            let cur = &mut 0;
+            assert!(next_token(&s, cur).is_elementary_type(), "{} is not an elementary type", keyword);
+        }
+    }
+
     #[test]
     fn test_placeholder() {
         let s = to_chars("_");
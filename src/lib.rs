@@ -1,2 +1,17 @@
+pub mod builder;
+pub mod codes;
+pub mod cst;
+pub mod diagnostics;
+pub mod dot;
+pub mod emit;
+pub mod incremental;
 pub mod lex_4_25;
+pub mod navigation;
 pub mod parse_4_25;
+pub mod parser;
+pub mod query;
+pub mod report;
+pub mod sexpr;
+pub mod solc;
+pub mod source_map;
+pub mod suggest;
@@ -1,9 +1,368 @@
 use std::env;
 use std::fs;
-use solfix::parse_4_25::parse;
+use std::io::{ self, IsTerminal };
+use std::process;
+use solfix::codes::{ self, Code };
+use solfix::cst;
+use solfix::diagnostics::{ Diagnostic, Severity };
+use solfix::dot::{ self, DotOptions };
+use solfix::emit::{ FileDiagnostics, Format };
+use solfix::navigation::NodeId;
+use solfix::parse_4_25::{ parse, ParseTree };
+use solfix::query::Pattern;
+use solfix::report::{ self, RenderOptions };
+use solfix::solc;
+use solfix::source_map::{ Encoding, SourceFile, SourceMap };
 
 fn main() {
-    let name = env::args().nth(1).expect("Usage: solidity-fix FILE_NAME");
+    let args = env::args().collect::<Vec<String>>();
+    if args.get(1).map(|arg| arg.as_str()) == Some("check") {
+        check(&args[2..]);
+        return;
+    }
+    if args.get(1).map(|arg| arg.as_str()) == Some("explain") {
+        explain(&args[2..]);
+        return;
+    }
+    if args.get(1).map(|arg| arg.as_str()) == Some("query") {
+        query(&args[2..]);
+        return;
+    }
+    if args.get(1).map(|arg| arg.as_str()) == Some("ast") {
+        ast(&args[2..]);
+        return;
+    }
+    if args.get(1).map(|arg| arg.as_str()) == Some("dot") {
+        print_dot(&args[2..]);
+        return;
+    }
+    if args.get(1).map(|arg| arg.as_str()) == Some("import") {
+        import(&args[2..]);
+        return;
+    }
+    let json = args.get(1).map(|arg| arg.as_str()) == Some("--json");
+    let name = args.get(if json { 2 } else { 1 }).expect("Usage: solidity-fix [--json] FILE_NAME");
     let input = fs::read_to_string(name).expect("Unable to open input file");
-    println!("{:?}", parse(input));
+    let mut sources = SourceMap::new();
+    let (id, tree) = parse(&mut sources, name, input);
+    if json {
+        print_json(sources.file(id), &tree);
+    } else {
+        println!("{:?}", tree);
+    }
+    print_diagnostics(sources.file(id), &tree, &default_render_options());
+}
+
+// Prints the parse tree as JSON, along with where each of its nodes starts and ends
+#[cfg(feature = "serde")]
+fn print_json(file: &SourceFile, tree: &ParseTree) {
+    let spanned = cst::SpannedTree::new(file.chars(), tree);
+    println!("{}", serde_json::to_string(&spanned).expect("parse trees can always be serialized"));
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_json(_file: &SourceFile, _tree: &ParseTree) {
+    eprintln!("error: solfix was built without the `serde` feature, so it can't print JSON");
+    process::exit(2);
+}
+
+// Parses each file and prints its diagnostics, exiting with status 1 if any of them is an error.
+// `--color` takes `always`, `never` or `auto`, which colours the output if it goes to a terminal.
+// `--format` takes `human`, `sarif`, `json`, `checkstyle` or `github`, and every format but
+// `human` is printed to stdout, for other tools to read.
+fn check(args: &[String]) {
+    let usage = || -> ! {
+        eprintln!("Usage: solidity-fix check [--color WHEN] [--format FORMAT] FILE_NAME...");
+        process::exit(2);
+    };
+    let mut options = default_render_options();
+    let mut format = Format::Human;
+    let mut names = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--color" => match args.next().map(|when| when.as_str()) {
+                Some("always") => options.color = true,
+                Some("never") => options.color = false,
+                Some("auto") => options.color = default_render_options().color,
+                _ => usage()
+            },
+            "--format" => match args.next().and_then(|name| Format::from_name(name)) {
+                Some(name) => format = name,
+                None => usage()
+            },
+            _ => names.push(arg)
+        }
+    }
+    if names.is_empty() {
+        usage();
+    }
+    let mut sources = SourceMap::new();
+    if format != Format::Human {
+        let files = names.into_iter().map(|name| {
+            let input = fs::read_to_string(name).expect("Unable to open input file");
+            let (file, tree) = parse(&mut sources, name, input);
+            let diagnostics = report::diagnostics(sources.file(file), &tree);
+            FileDiagnostics { file, diagnostics }
+        }).collect::<Vec<FileDiagnostics>>();
+        print!("{}", format.emit(&sources, &files, &options));
+        if files.iter().any(|file| file.diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)) {
+            process::exit(1);
+        }
+        return;
+    }
+    let mut errors = vec![];
+    for name in names {
+        let input = fs::read_to_string(name).expect("Unable to open input file");
+        let (id, tree) = parse(&mut sources, name, input);
+        let diagnostics = print_diagnostics(sources.file(id), &tree, &options);
+        errors.extend(diagnostics.into_iter().filter(|diagnostic| diagnostic.severity == Severity::Error));
+    }
+    if !errors.is_empty() {
+        let plural = if errors.len() == 1 { "" } else { "s" };
+        eprintln!("{}", report::render_message(&Severity::Error, None, &format!("aborting due to {} previous error{}", errors.len(), plural), &options));
+        let mut codes = errors.iter().filter_map(|error| error.code).collect::<Vec<Code>>();
+        codes.sort();
+        codes.dedup();
+        if let Some(first) = codes.first() {
+            let codes = codes.iter().map(|code| code.to_string()).collect::<Vec<String>>();
+            eprintln!("Some errors have detailed explanations: {}.", codes.join(", "));
+            eprintln!("For more information about an error, try `solidity-fix explain {}`.", first);
+        }
+        process::exit(1);
+    }
+}
+
+// Prints the rendered diagnostics of a parse tree and returns them
+fn print_diagnostics(file: &SourceFile, tree: &ParseTree, options: &RenderOptions) -> Vec<Diagnostic> {
+    let diagnostics = report::diagnostics(file, tree);
+    for diagnostic in &diagnostics {
+        eprintln!("{}", report::render(diagnostic, file, options));
+    }
+    diagnostics
+}
+
+// Prints the explanation of an error code, or lists every code if none is given
+fn explain(args: &[String]) {
+    match args {
+        [] => {
+            for entry in codes::catalog() {
+                println!("{}  {}", entry.code, entry.title);
+            }
+        }
+        [code] => match codes::explain(code) {
+            Some(entry) => print!("{}: {}\n\n{}", entry.code, entry.title, entry.text),
+            None => {
+                eprintln!("error: `{}` isn't an error code", code);
+                process::exit(1);
+            }
+        },
+        _ => {
+            eprintln!("Usage: solidity-fix explain [CODE]");
+            process::exit(2);
+        }
+    }
+}
+
+// Colours diagnostics when they are printed to a terminal, unless NO_COLOR is set
+fn default_render_options() -> RenderOptions {
+    RenderOptions { color: io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none() }
+}
+
+// Prints the solc compact AST JSON of a file
+fn ast(args: &[String]) {
+    let name = match args {
+        [name] => name,
+        _ => {
+            eprintln!("Usage: solidity-fix ast FILE_NAME");
+            process::exit(2);
+        }
+    };
+    let input = fs::read_to_string(name).expect("Unable to open input file");
+    let mut sources = SourceMap::new();
+    let (id, tree) = parse(&mut sources, name, input);
+    // The AST of a file with errors would be missing whatever couldn't be parsed
+    if tree.has_errors() {
+        print_diagnostics(sources.file(id), &tree, &default_render_options());
+        process::exit(1);
+    }
+    let ast = solc::export_compact_ast(&tree, sources.file(id));
+    println!("{}", serde_json::to_string_pretty(&ast).expect("JSON values can always be serialized"));
+}
+
+// Prints the parse tree of a file in the DOT language. `--node` draws the subtree at a path of
+// leaf indices like `2.0.1`, and `--select` draws the subtrees that match a query pattern.
+fn print_dot(args: &[String]) {
+    let usage = || -> ! {
+        eprintln!("Usage: solidity-fix dot [--max-depth DEPTH] [--node PATH] [--select PATTERN] FILE_NAME");
+        process::exit(2);
+    };
+    let mut options = DotOptions::default();
+    let mut pattern = None;
+    let mut args = args.iter();
+    let mut name = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-depth" => match args.next().and_then(|depth| depth.parse::<usize>().ok()) {
+                Some(depth) => options.max_depth = Some(depth),
+                None => usage()
+            },
+            "--node" => {
+                let path = args.next().unwrap_or_else(|| usage());
+                let mut id = NodeId::root();
+                for index in path.split('.').filter(|index| !index.is_empty()) {
+                    match index.parse::<usize>() {
+                        Ok(index) => id = id.child(index),
+                        Err(..) => usage()
+                    }
+                }
+                options.roots.push(id);
+            }
+            "--select" => match Pattern::new(args.next().unwrap_or_else(|| usage())) {
+                Ok(selected) => pattern = Some(selected),
+                Err(diagnostic) => {
+                    eprintln!("error: {}", diagnostic.message);
+                    process::exit(2);
+                }
+            },
+            _ if name.is_none() => name = Some(arg),
+            _ => usage()
+        }
+    }
+    let name = name.unwrap_or_else(|| usage());
+    let input = fs::read_to_string(name).expect("Unable to open input file");
+    let (_, tree) = parse(&mut SourceMap::new(), name, input);
+    if let Some(pattern) = pattern {
+        for found in pattern.find_matches(&tree) {
+            options.roots.extend(found.nodes.into_iter().map(|(id, _)| id));
+        }
+        if options.roots.is_empty() {
+            eprintln!("error: nothing in {} matches the pattern", name);
+            process::exit(1);
+        }
+    }
+    if let Some(missing) = options.roots.iter().find(|id| tree.node(id).is_none()) {
+        eprintln!("error: {} has no node at {:?}", name, missing.path());
+        process::exit(2);
+    }
+    print!("{}", dot::render(&tree, &options));
+}
+
+// Prints the parse trees of the sources in a solc AST, or with `--compare`, checks them against
+// the trees that solfix parses from the same files
+fn import(args: &[String]) {
+    let (compare, name) = match args {
+        [flag, name] if flag == "--compare" => (true, name),
+        [name] => (false, name),
+        _ => {
+            eprintln!("Usage: solidity-fix import [--compare] AST_JSON_FILE");
+            process::exit(2);
+        }
+    };
+    let input = fs::read_to_string(name).expect("Unable to open input file");
+    let output = match serde_json::from_str::<serde_json::Value>(&input) {
+        Ok(output) => output,
+        Err(error) => {
+            eprintln!("error: {} isn't valid JSON: {}", name, error);
+            process::exit(2);
+        }
+    };
+    // The sources that can be read are used to put the errors of nodes that can't be imported
+    // where they are in characters, and to compare against
+    let mut files = SourceMap::new();
+    // The output of `--combined-json` and `--standard-json` holds an AST for each source, and
+    // anything else should be the AST of a single source
+    let sources = match output["sources"].as_object() {
+        Some(sources) => {
+            for path in sources.keys() {
+                if let Ok(source) = fs::read_to_string(path) {
+                    files.add(path, source);
+                }
+            }
+            solc::import_sources(&output, &files)
+        }
+        None => {
+            let path = [&output["absolutePath"], &output["attributes"]["absolutePath"]].iter()
+                .find_map(|path| path.as_str())
+                .unwrap_or(name);
+            if let Ok(source) = fs::read_to_string(path) {
+                files.add(path, source);
+            }
+            let file = files.find(path).map(|id| files.file(id));
+            vec![(String::from(path), solc::import_ast(&output, file))]
+        }
+    };
+    let mut failed = false;
+    for (path, tree) in sources {
+        for diagnostic in tree.diagnostics() {
+            eprintln!("error: {}: {}", path, diagnostic.message);
+            failed = true;
+        }
+        if !compare {
+            println!("{}: {:?}", path, tree);
+            continue;
+        }
+        let source = match files.find(&path) {
+            Some(id) => files.file(id).text().to_string(),
+            None => {
+                eprintln!("error: unable to open {}", path);
+                failed = true;
+                continue;
+            }
+        };
+        let (_, parsed) = parse(&mut SourceMap::new(), &path, source);
+        match tree.preorder().zip(parsed.preorder()).find(|((_, imported), (_, parsed))| {
+            imported.root != parsed.root || imported.leaves.len() != parsed.leaves.len()
+        }) {
+            Some(((id, imported), (_, parsed))) => {
+                let difference = match imported.root == parsed.root {
+                    true => format!("{:?} has {} leaves in the AST and {} in the parse", parsed.root, imported.leaves.len(), parsed.leaves.len()),
+                    false => format!("{:?} in the AST, {:?} in the parse", imported.root, parsed.root)
+                };
+                println!("{}: differs at node {:?}: {}", path, id.path(), difference);
+                failed = true;
+            }
+            None => println!("{}: matches", path)
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+// Prints each match of a pattern in the given files, along with what its metavariables are bound to
+fn query(args: &[String]) {
+    if args.len() < 2 {
+        eprintln!("Usage: solidity-fix query PATTERN FILE_NAME...");
+        process::exit(2);
+    }
+    let pattern = match Pattern::new(&args[0]) {
+        Ok(pattern) => pattern,
+        Err(diagnostic) => {
+            eprintln!("error: {}", diagnostic.message);
+            process::exit(2);
+        }
+    };
+    let mut found = false;
+    let mut sources = SourceMap::new();
+    for name in &args[1..] {
+        let input = fs::read_to_string(name).expect("Unable to open input file");
+        let (id, tree) = parse(&mut sources, name, input);
+        let file = sources.file(id);
+        let syntax = cst::build(file.chars(), &tree, true);
+        for found_match in pattern.find_matches(&tree) {
+            found = true;
+            let span = found_match.span(&syntax).unwrap_or_default();
+            let position = file.line_column(span.start, Encoding::Utf32);
+            println!("{}:{}:{}: {}", name, position.line + 1, position.column + 1, span.text(file.chars()));
+            for (metavariable, (id, _)) in &found_match.bindings {
+                let text = syntax.node(id).map_or(String::new(), |node| node.span().text(file.chars()));
+                println!("    {} = {}", metavariable, text);
+            }
+        }
+    }
+    // Like grep, the exit status tells whether anything was found
+    if !found {
+        process::exit(1);
+    }
 }
@@ -0,0 +1,228 @@
+use super::lex_4_25;
+use super::parse_4_25::{ NonTerminal, ParseTree };
+
+/**
+ * @dev Identifies a node of a ParseTree by the leaf indices on the path from the root down to it,
+ *      so the root's id is empty. Ids are positional rather than stable: they find the same node
+ *      in a clone of the tree and in the syntax tree that is built from it, but a node's id
+ *      changes when a leaf is added or removed before it on its path. incremental::StableId
+ *      identifies a node across edits and reparses instead.
+ */
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NodeId(Vec<usize>);
+
+impl NodeId {
+    pub fn root() -> NodeId {
+        NodeId(vec![])
+    }
+
+    // Returns the id of the leaf at index in the node with this id
+    pub fn child(&self, index: usize) -> NodeId {
+        let mut path = self.0.clone();
+        path.push(index);
+        NodeId(path)
+    }
+
+    // Returns the id of the node that this one is a leaf of, or None for the root
+    pub fn parent(&self) -> Option<NodeId> {
+        self.0.split_last().map(|(_, path)| NodeId(path.to_vec()))
+    }
+
+    // Returns the id of the node that path leads to from the node with this id
+    pub fn descendant(&self, path: &[usize]) -> NodeId {
+        let mut descendant = self.0.clone();
+        descendant.extend_from_slice(path);
+        NodeId(descendant)
+    }
+
+    pub fn path(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+/**
+ * @dev Visits a tree and all of its descendants, each node before its leaves. The traversal keeps
+ *      its own stack, so deeply nested trees don't overflow the call stack.
+ */
+pub struct Preorder<'a> {
+    stack: Vec<(NodeId, &'a ParseTree)>,
+}
+
+impl<'a> Iterator for Preorder<'a> {
+    type Item = (NodeId, &'a ParseTree);
+
+    fn next(&mut self) -> Option<(NodeId, &'a ParseTree)> {
+        let (id, tree) = self.stack.pop()?;
+        for (index, leaf) in tree.leaves.iter().enumerate().rev() {
+            self.stack.push((id.child(index), leaf));
+        }
+        Some((id, tree))
+    }
+}
+
+/**
+ * @dev Visits a tree and all of its descendants, each node after its leaves.
+ */
+pub struct Postorder<'a> {
+    // Each node on the path to the next one along with how many of its leaves have been visited
+    stack: Vec<(NodeId, &'a ParseTree, usize)>,
+}
+
+impl<'a> Iterator for Postorder<'a> {
+    type Item = (NodeId, &'a ParseTree);
+
+    fn next(&mut self) -> Option<(NodeId, &'a ParseTree)> {
+        loop {
+            let (id, tree, visited) = self.stack.pop()?;
+            match tree.leaves.get(visited) {
+                Some(leaf) => {
+                    let child = id.child(visited);
+                    self.stack.push((id, tree, visited + 1));
+                    self.stack.push((child, leaf, 0));
+                }
+                None => return Some((id, tree))
+            }
+        }
+    }
+}
+
+impl ParseTree {
+    /*** Traversal ***/
+
+    pub fn preorder(&self) -> Preorder<'_> {
+        Preorder { stack: vec![(NodeId::root(), self)] }
+    }
+
+    pub fn postorder(&self) -> Postorder<'_> {
+        Postorder { stack: vec![(NodeId::root(), self, 0)] }
+    }
+
+    // Returns the leaves of this tree whose root is kind. Nonterminals that hold a value, like
+    // Identifier, only match the same value, so use find_all to match any value.
+    pub fn children_of(&self, kind: &NonTerminal) -> Vec<&ParseTree> {
+        self.leaves.iter().filter(|leaf| leaf.root == *kind).map(|leaf| leaf.as_ref()).collect()
+    }
+
+    // Returns this tree and its descendants that satisfy predicate, in preorder
+    pub fn find_all<P: Fn(&ParseTree) -> bool>(&self, predicate: P) -> Vec<&ParseTree> {
+        self.preorder().map(|(_, tree)| tree).filter(|tree| predicate(tree)).collect()
+    }
+
+    // Returns the first descendant of this tree in preorder whose root is kind
+    pub fn first_descendant(&self, kind: &NonTerminal) -> Option<&ParseTree> {
+        self.preorder().skip(1).map(|(_, tree)| tree).find(|tree| tree.root == *kind)
+    }
+
+    /*** Node Ids ***/
+
+    // Returns the node with id, if this tree has one
+    pub fn node(&self, id: &NodeId) -> Option<&ParseTree> {
+        let mut tree = self;
+        for index in id.path() {
+            tree = tree.leaves.get(*index)?;
+        }
+        Some(tree)
+    }
+
+    // Returns the node that the node with id is a leaf of
+    pub fn parent_of(&self, id: &NodeId) -> Option<&ParseTree> {
+        self.node(&id.parent()?)
+    }
+
+    // Returns the id of node if it is this tree or one of its descendants. Nodes are compared by
+    // address, so an equal tree from somewhere else isn't found.
+    pub fn id_of(&self, node: &ParseTree) -> Option<NodeId> {
+        self.preorder().find(|(_, tree)| std::ptr::eq(*tree, node)).map(|(id, _)| id)
+    }
+
+    /*** Accessors ***/
+
+    // Returns the name of a contract, interface or library definition
+    pub fn contract_name(&self) -> Option<&str> {
+        match self.root {
+            NonTerminal::ContractDefinition(..) => self.leaves.first().and_then(|leaf| leaf.identifier_name()),
+            _ => None
+        }
+    }
+
+    // Returns the members of a contract, interface or library definition, leaving out the leaves
+    // that only record errors
+    pub fn contract_members(&self) -> Vec<&ParseTree> {
+        match self.root {
+            NonTerminal::ContractDefinition(..) => {
+                self.children_of(&NonTerminal::ContractPart).into_iter()
+                    .flat_map(|part| part.leaves.iter())
+                    .filter(|member| !member.is_error())
+                    .map(|member| member.as_ref())
+                    .collect()
+            }
+            _ => vec![]
+        }
+    }
+
+    // Returns the name of a function definition. Constructors and fallback functions don't have
+    // one.
+    pub fn function_name(&self) -> Option<&str> {
+        match self.root {
+            NonTerminal::FunctionDefinition => {
+                self.leaves.iter()
+                    .take_while(|leaf| !leaf.is_parameter_list())
+                    .find_map(|leaf| leaf.identifier_name())
+            }
+            _ => None
+        }
+    }
+
+    // Returns the parameter list of a function, constructor or fallback function definition
+    pub fn function_parameters(&self) -> Option<&ParseTree> {
+        if !self.is_function_definition() {
+            return None;
+        }
+        self.leaves.iter().find(|leaf| leaf.is_parameter_list()).map(|leaf| leaf.as_ref())
+    }
+
+    // Returns the parameter list that follows `returns` in a function definition
+    pub fn function_returns(&self) -> Option<&ParseTree> {
+        if !self.is_function_definition() {
+            return None;
+        }
+        self.leaves.iter()
+            .skip_while(|leaf| leaf.root != NonTerminal::Token(lex_4_25::Token::Returns))
+            .find(|leaf| leaf.is_parameter_list())
+            .map(|leaf| leaf.as_ref())
+    }
+
+    // Returns the body of a function, constructor or fallback function definition. Functions that
+    // are only declared don't have one.
+    pub fn function_body(&self) -> Option<&ParseTree> {
+        if !self.is_function_definition() {
+            return None;
+        }
+        self.children_of(&NonTerminal::Block).into_iter().next()
+    }
+
+    fn identifier_name(&self) -> Option<&str> {
+        match &self.root {
+            NonTerminal::Identifier(name) => Some(name),
+            _ => None
+        }
+    }
+
+    fn is_function_definition(&self) -> bool {
+        matches!(
+            self.root,
+            NonTerminal::FunctionDefinition |
+            NonTerminal::ConstructorDefinition |
+            NonTerminal::FallbackFunctionDefinition
+        )
+    }
+
+    // Returns true if this tree is a ParameterList, even if its parentheses were missing
+    fn is_parameter_list(&self) -> bool {
+        match &self.root {
+            NonTerminal::ParameterList => true,
+            NonTerminal::Invalid(expected) => **expected == NonTerminal::ParameterList,
+            _ => false
+        }
+    }
+}
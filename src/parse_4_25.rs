@@ -1,8 +1,17 @@
+use super::codes::{ self, Code };
+use super::diagnostics::{ Diagnostic, Severity, Span, Suggestion };
+use super::incremental;
 use super::lex_4_25;
+use super::parser::{ Context, DepthGuard, ParserOptions };
+use super::report;
+use super::source_map::{ FileId, SourceMap };
+use super::suggest;
 
 // TODO(jalextowle): Add proper documentation to this enum list. It may be appropriate to add this
 // and the lex_4_25::Token enum to a file called `enumerations.rs` or something similar.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum NonTerminal {
     // Solidity Nonterminals
     SourceUnit,
@@ -18,6 +27,8 @@ pub enum NonTerminal {
     ModifierDefinition,
     ModifierInvocation,
     FunctionDefinition,
+    ConstructorDefinition,
+    FallbackFunctionDefinition,
     EventDefinition,
     EnumValue(String),
     EnumValueList,
@@ -71,6 +82,7 @@ pub enum NonTerminal {
     HexNumber,
     DecimalNumber,
     TupleExpression,
+    InlineArrayExpression,
     ElementaryTypeNameExpression,
     ElementaryTypeName,
     Int,
@@ -89,12 +101,16 @@ pub enum NonTerminal {
     // Error NonTerminals
     Invalid(Box<NonTerminal>),
     InvalidPair(Box<NonTerminal>, Box<NonTerminal>),
+    Error(Box<Diagnostic>),
     Empty,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParseTree {
+    #[cfg_attr(feature = "serde", serde(flatten))]
     pub root: NonTerminal,
+    #[cfg_attr(feature = "serde", serde(rename = "children"))]
     pub leaves: Vec<Box<ParseTree>>
 }
 
@@ -107,9 +123,10 @@ impl ParseTree {
         self.add_leaf(NonTerminal::Invalid(Box::new(expected)));
     }
 
-    // TODO(jalextowle): It would be better to have `expected` and `actual` for debugging
-    fn add_invalid_token(&mut self, expected: lex_4_25::Token) {
-        self.add_leaf(NonTerminal::Invalid(Box::new(NonTerminal::Token(expected))));
+    // Adds an Error with code for the token after start, which was found where expected should
+    // have been
+    fn add_expected_token(&mut self, input: &Vec<char>, start: usize, expected: lex_4_25::Token, code: Code) {
+        self.add_leaf(NonTerminal::expected_token_error(input, start, expected, code));
     }
 
     fn add_token(&mut self, token: lex_4_25::Token) {
@@ -120,28 +137,226 @@ impl ParseTree {
         self.leaves.push(Box::new(other));
     }
 
-    fn remove_left_leaf(&mut self) -> Box<ParseTree> { self.leaves.remove(0) }
-
-    /**
-     * Merge other with self, returning the new parent node
-     * self recieves other.children[0], which is replaced by a pointer to self
-     */
-    fn merge_expressions(mut self, mut other: ParseTree) -> ParseTree {
-        self.leaves.push(other.remove_left_leaf());
-        other.leaves.push(Box::new(self));
-        other.leaves.swap(0, 1);
-        other
-    }
-
     // Returns a new, empty parse tree
     fn empty() -> ParseTree {
         ParseTree { root: NonTerminal::Empty, leaves: vec![] }
     }
+
+    // Moves the diagnostics in this tree offset characters further into the input
+    pub(crate) fn shift_spans(&mut self, offset: isize) {
+        if let NonTerminal::Error(diagnostic) = &mut self.root {
+            diagnostic.shift(offset);
+        }
+        for leaf in self.leaves.iter_mut() {
+            leaf.shift_spans(offset);
+        }
+    }
+
+    // Returns whether this tree contains any Invalid nonterminals or error Diagnostics
+    pub fn has_errors(&self) -> bool {
+        let invalid = match &self.root {
+            NonTerminal::Invalid(..) | NonTerminal::InvalidPair(..) => true,
+            NonTerminal::Error(diagnostic) => diagnostic.severity == Severity::Error,
+            _ => false
+        };
+        invalid || self.leaves.iter().any(|leaf| leaf.has_errors())
+    }
+
+    // Returns true if this tree is a function, constructor or fallback function definition with
+    // a body
+    pub fn is_implemented(&self) -> bool {
+        match self.root {
+            NonTerminal::FunctionDefinition |
+            NonTerminal::ConstructorDefinition |
+            NonTerminal::FallbackFunctionDefinition => {
+                self.leaves.iter().any(|leaf| leaf.root == NonTerminal::Block)
+            }
+            _ => false
+        }
+    }
+
+    // Returns true if this tree is a contract definition that declares a function without
+    // implementing it. Interfaces are always abstract unless they are empty.
+    pub fn is_abstract(&self) -> bool {
+        match self.root {
+            NonTerminal::ContractDefinition(lex_4_25::Token::Interface) => !self.contract_members().is_empty(),
+            NonTerminal::ContractDefinition(..) => {
+                self.leaves.iter()
+                    .filter(|leaf| leaf.root == NonTerminal::ContractPart)
+                    .flat_map(|part| part.leaves.iter())
+                    .any(|leaf| match leaf.root {
+                        NonTerminal::FunctionDefinition |
+                        NonTerminal::ConstructorDefinition |
+                        NonTerminal::FallbackFunctionDefinition => !leaf.is_implemented(),
+                        _ => false
+                    })
+            }
+            _ => false
+        }
+    }
+
+    // Returns the key type of a Mapping tree
+    pub fn key_type(&self) -> Option<&ParseTree> {
+        match self.root {
+            NonTerminal::Mapping => {
+                self.leaves.iter()
+                    .take_while(|leaf| !leaf.is_mapping_arrow())
+                    .find(|leaf| !leaf.is_error())
+                    .map(|leaf| leaf.as_ref())
+            }
+            _ => None
+        }
+    }
+
+    // Returns the value type of a Mapping tree
+    pub fn value_type(&self) -> Option<&ParseTree> {
+        match self.root {
+            NonTerminal::Mapping => {
+                self.leaves.iter()
+                    .skip_while(|leaf| !leaf.is_mapping_arrow())
+                    .skip(1)
+                    .find(|leaf| !leaf.is_error())
+                    .map(|leaf| leaf.as_ref())
+            }
+            _ => None
+        }
+    }
+
+    // Returns true if this tree is the Arrow that separates a mapping's key and value, even if
+    // the Arrow was missing from the input
+    fn is_mapping_arrow(&self) -> bool {
+        match &self.root {
+            NonTerminal::Token(lex_4_25::Token::Arrow) => true,
+            // The Arrow is the only keyword or operator that a mapping expects
+            NonTerminal::Error(diagnostic) => diagnostic.code == Some(codes::EXPECTED_KEYWORD),
+            _ => false
+        }
+    }
+
+    // Returns true if this tree is a leaf that only records an error
+    pub(crate) fn is_error(&self) -> bool {
+        matches!(
+            self.root,
+            NonTerminal::Invalid(..) |
+            NonTerminal::InvalidPair(..) |
+            NonTerminal::Error(..)
+        )
+    }
+
+    // Renders a type name, and any expressions that appear in its array dimensions, as source
+    // text. Parsing the rendered text produces an equal tree.
+    pub fn type_name_source(&self) -> String {
+        match &self.root {
+            NonTerminal::Token(token) => {
+                match (self.leaves.first(), self.leaves.get(1)) {
+                    (Some(left), Some(right)) => format!(
+                        "{} {} {}",
+                        left.operand_source(),
+                        token.to_source(),
+                        right.operand_source()
+                    ),
+                    _ => token.to_source()
+                }
+            }
+            NonTerminal::Identifier(name) => name.clone(),
+            NonTerminal::StorageLocation(token) |
+            NonTerminal::StateMutability(token) => token.to_source(),
+            NonTerminal::ElementaryTypeName => self.leaves_source(" "),
+            NonTerminal::UserDefinedTypeName => self.leaves_source("."),
+            NonTerminal::ArrayTypeName => {
+                let element = self.leaves.first().map(|leaf| leaf.type_name_source()).unwrap_or_default();
+                let length = self.leaves.get(1).map(|leaf| leaf.type_name_source()).unwrap_or_default();
+                format!("{}[{}]", element, length)
+            }
+            NonTerminal::Mapping => {
+                let key = self.key_type().map(|key| key.type_name_source()).unwrap_or_default();
+                let value = self.value_type().map(|value| value.type_name_source()).unwrap_or_default();
+                format!("mapping({} => {})", key, value)
+            }
+            NonTerminal::FunctionTypeName => {
+                let mut source = String::from("function");
+                for leaf in &self.leaves {
+                    match leaf.root {
+                        NonTerminal::FunctionTypeParameterList => source.push_str(&leaf.type_name_source()),
+                        NonTerminal::Token(lex_4_25::Token::Returns) => {
+                            source.push_str(" returns ");
+                            source.push_str(&leaf.leaves_source(""));
+                        }
+                        _ => {
+                            source.push(' ');
+                            source.push_str(&leaf.type_name_source());
+                        }
+                    }
+                }
+                source
+            }
+            NonTerminal::FunctionTypeParameterList => format!("({})", self.leaves_source(", ")),
+            NonTerminal::FunctionTypeParameter => self.leaves_source(" "),
+            _ => self.leaves_source(" ")
+        }
+    }
+
+    // Renders an operand of a binary operation, adding parentheses if the operand is also a
+    // binary operation
+    fn operand_source(&self) -> String {
+        match self.root {
+            NonTerminal::Token(..) if self.leaves.len() == 2 => format!("({})", self.type_name_source()),
+            _ => self.type_name_source()
+        }
+    }
+
+    // Renders the leaves of this tree that are not errors and joins them with separator
+    fn leaves_source(&self, separator: &str) -> String {
+        self.leaves.iter()
+            .filter(|leaf| !leaf.is_error())
+            .map(|leaf| leaf.type_name_source())
+            .collect::<Vec<String>>()
+            .join(separator)
+    }
+
+    // Removes everything that comes after the first error in this tree. Returns true if an error
+    // was found. Warnings aren't errors, so the input after them is kept.
+    pub(crate) fn truncate_at_first_error(&mut self) -> bool {
+        match &self.root {
+            NonTerminal::Invalid(..) | NonTerminal::InvalidPair(..) => {
+                self.leaves.clear();
+                return true;
+            }
+            NonTerminal::Error(diagnostic) if diagnostic.severity == Severity::Error => return true,
+            _ => ()
+        }
+        let mut found = None;
+        for (i, leaf) in self.leaves.iter_mut().enumerate() {
+            if leaf.truncate_at_first_error() {
+                found = Some(i);
+                break;
+            }
+        }
+        match found {
+            Some(i) => {
+                self.leaves.truncate(i + 1);
+                true
+            }
+            None => false
+        }
+    }
+
+    // Returns every Diagnostic recorded in this tree, in the order that they were found
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        if let NonTerminal::Error(diagnostic) = &self.root {
+            diagnostics.push(*diagnostic.clone());
+        }
+        for leaf in &self.leaves {
+            diagnostics.append(&mut leaf.diagnostics());
+        }
+        diagnostics
+    }
 }
 
 impl lex_4_25::Token {
     pub fn next_identifier_name(input: &Vec<char>, current_ptr: &mut usize) -> String {
-        return match lex_4_25::next_token(input, current_ptr) {
+        match lex_4_25::next_token(input, current_ptr) {
             lex_4_25::Token::Identifier(name) => name,
             _ => String::from("")
         }
@@ -162,12 +377,55 @@ impl lex_4_25::Token {
 
 impl NonTerminal {
     pub fn next_to_identifier(input: &Vec<char>, current_ptr: &mut usize) -> NonTerminal {
-        return match lex_4_25::next_token(input, current_ptr) {
-            lex_4_25::Token::Identifier(name) => NonTerminal::Identifier(name),
+        match lex_4_25::next_token_with_span(input, current_ptr) {
+            (lex_4_25::Token::Identifier(name), _) => NonTerminal::Identifier(name),
+            (ref reserved, span) if reserved.is_reserved() => NonTerminal::reserved_keyword_error(input, span),
             _ => NonTerminal::Identifier("".to_string()).to_invalid()
         }
     }
 
+    // Returns an Error reporting that the reserved keyword at span was used as an identifier
+    pub fn reserved_keyword_error(input: &[char], span: Span) -> NonTerminal {
+        let keyword = span.text(input);
+        NonTerminal::Error(Box::new(
+            Diagnostic::error(
+                format!("`{}` is a reserved keyword and cannot be used as an identifier", keyword),
+                span
+            )
+            .with_code(codes::RESERVED_KEYWORD)
+            .with_note(String::from("reserved keywords are set aside for future versions of Solidity"))
+            .with_help(format!("rename `{}` to a name that is not a keyword", keyword))
+        ))
+    }
+
+    // Returns an Error reporting that the token after start was found where expected should have
+    // been. The error is put at start, right after the input that came before it, since that is
+    // where expected belongs. A token that the lexer couldn't read is reported on its own, since
+    // the token itself is the problem.
+    pub fn expected_token_error(input: &Vec<char>, start: usize, expected: lex_4_25::Token, code: Code) -> NonTerminal {
+        let cursor = &mut start.clone();
+        let (found, span) = loop {
+            match lex_4_25::next_token_or_trivia(input, cursor) {
+                (token, _) if token.is_trivia() => (),
+                found => break found
+            }
+        };
+        let diagnostic = match found {
+            lex_4_25::Token::Illegal => {
+                Diagnostic::error(format!("invalid token `{}`", span.text(input)), span).with_code(codes::INVALID_TOKEN)
+            }
+            lex_4_25::Token::EOF => {
+                let message = format!("expected {}, found end of file", report::describe(&NonTerminal::Token(expected)));
+                Diagnostic::error(message, Span::new(start, start)).with_code(code)
+            }
+            _ => {
+                let message = format!("expected {}, found `{}`", report::describe(&NonTerminal::Token(expected)), span.text(input));
+                Diagnostic::error(message, Span::new(start, start)).with_code(code)
+            }
+        };
+        NonTerminal::Error(Box::new(diagnostic))
+    }
+
     pub fn next_token(input: &Vec<char>, current_ptr: &mut usize) -> NonTerminal {
         NonTerminal::Token(lex_4_25::next_token(input, current_ptr))
     }
@@ -187,40 +445,317 @@ impl NonTerminal {
     pub fn to_invalid_token_pair(self, actual: lex_4_25::Token) -> NonTerminal {
         NonTerminal::InvalidPair(Box::new(self), Box::new(actual.to_token()))
     }
+
+    // Returns the name of this nonterminal's kind, without any of the values that it holds
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            NonTerminal::SourceUnit => "SourceUnit",
+            NonTerminal::PragmaDirective => "PragmaDirective",
+            NonTerminal::ImportDirective => "ImportDirective",
+            NonTerminal::ContractDefinition(..) => "ContractDefinition",
+            NonTerminal::ContractPart => "ContractPart",
+            NonTerminal::InheritanceList => "InheritanceList",
+            NonTerminal::InheritanceSpecifier => "InheritanceSpecifier",
+            NonTerminal::StateVariableDeclaration => "StateVariableDeclaration",
+            NonTerminal::UsingForDeclaration => "UsingForDeclaration",
+            NonTerminal::StructDefinition => "StructDefinition",
+            NonTerminal::ModifierDefinition => "ModifierDefinition",
+            NonTerminal::ModifierInvocation => "ModifierInvocation",
+            NonTerminal::FunctionDefinition => "FunctionDefinition",
+            NonTerminal::ConstructorDefinition => "ConstructorDefinition",
+            NonTerminal::FallbackFunctionDefinition => "FallbackFunctionDefinition",
+            NonTerminal::EventDefinition => "EventDefinition",
+            NonTerminal::EnumValue(..) => "EnumValue",
+            NonTerminal::EnumValueList => "EnumValueList",
+            NonTerminal::EnumDefinition => "EnumDefinition",
+            NonTerminal::ParameterList => "ParameterList",
+            NonTerminal::Parameter => "Parameter",
+            NonTerminal::EventParameterList => "EventParameterList",
+            NonTerminal::EventParameter => "EventParameter",
+            NonTerminal::FunctionTypeParameterList => "FunctionTypeParameterList",
+            NonTerminal::FunctionTypeParameter => "FunctionTypeParameter",
+            NonTerminal::VariableDeclaration => "VariableDeclaration",
+            NonTerminal::TypeName => "TypeName",
+            NonTerminal::UserDefinedTypeName => "UserDefinedTypeName",
+            NonTerminal::Mapping => "Mapping",
+            NonTerminal::ArrayTypeName => "ArrayTypeName",
+            NonTerminal::FunctionTypeName => "FunctionTypeName",
+            NonTerminal::StorageLocation(..) => "StorageLocation",
+            NonTerminal::StateMutability(..) => "StateMutability",
+            NonTerminal::Block => "Block",
+            NonTerminal::Statement => "Statement",
+            NonTerminal::ExpressionStatement => "ExpressionStatement",
+            NonTerminal::IfStatement => "IfStatement",
+            NonTerminal::WhileStatement => "WhileStatement",
+            NonTerminal::PlaceholderStatement => "PlaceholderStatement",
+            NonTerminal::SimpleStatement => "SimpleStatement",
+            NonTerminal::ForStatement => "ForStatement",
+            NonTerminal::InlineAssemblyStatement => "InlineAssemblyStatement",
+            NonTerminal::DoWhileStatement => "DoWhileStatement",
+            NonTerminal::Continue => "Continue",
+            NonTerminal::Break => "Break",
+            NonTerminal::Return => "Return",
+            NonTerminal::Throw => "Throw",
+            NonTerminal::EmitStatement => "EmitStatement",
+            NonTerminal::VariableDefinition => "VariableDefinition",
+            NonTerminal::IdentifierList => "IdentifierList",
+            NonTerminal::Expression => "Expression",
+            NonTerminal::PrimaryExpression => "PrimaryExpression",
+            NonTerminal::ExpressionList => "ExpressionList",
+            NonTerminal::NameValueList => "NameValueList",
+            NonTerminal::FunctionCall => "FunctionCall",
+            NonTerminal::FunctionCallArguments => "FunctionCallArguments",
+            NonTerminal::NewExpression => "NewExpression",
+            NonTerminal::MemberAccess => "MemberAccess",
+            NonTerminal::IndexAccess => "IndexAccess",
+            NonTerminal::BooleanLiteral(..) => "BooleanLiteral",
+            NonTerminal::NumberLiteral(..) => "NumberLiteral",
+            NonTerminal::NumberUnit => "NumberUnit",
+            NonTerminal::HexLiteral(..) => "HexLiteral",
+            NonTerminal::StringLiteral(..) => "StringLiteral",
+            NonTerminal::Identifier(..) => "Identifier",
+            NonTerminal::HexNumber => "HexNumber",
+            NonTerminal::DecimalNumber => "DecimalNumber",
+            NonTerminal::TupleExpression => "TupleExpression",
+            NonTerminal::InlineArrayExpression => "InlineArrayExpression",
+            NonTerminal::ElementaryTypeNameExpression => "ElementaryTypeNameExpression",
+            NonTerminal::ElementaryTypeName => "ElementaryTypeName",
+            NonTerminal::Int => "Int",
+            NonTerminal::Uint => "Uint",
+            NonTerminal::Byte => "Byte",
+            NonTerminal::Fixed => "Fixed",
+            NonTerminal::Ufixed => "Ufixed",
+            NonTerminal::InlineAssemblyBlock => "InlineAssemblyBlock",
+            NonTerminal::AssemblyItem => "AssemblyItem",
+            NonTerminal::AssemblyLocalBinding => "AssemblyLocalBinding",
+            NonTerminal::AssemblyAssignment => "AssemblyAssignment",
+            NonTerminal::AssemblyLabel => "AssemblyLabel",
+            NonTerminal::FunctionalAssemblyExpression => "FunctionalAssemblyExpression",
+            NonTerminal::Token(..) => "Token",
+            NonTerminal::Invalid(..) => "Invalid",
+            NonTerminal::InvalidPair(..) => "InvalidPair",
+            NonTerminal::Error(..) => "Error",
+            NonTerminal::Empty => "Empty",
+        }
+    }
+}
+
+/*** Suggestions ***/
+
+// Keywords that can begin a ContractPart
+const CONTRACT_PART_KEYWORDS: [&str; 8] = [
+    "constructor", "enum", "event", "function", "mapping", "modifier", "struct", "using"
+];
+
+// Keywords that can begin a Statement
+const STATEMENT_KEYWORDS: [&str; 12] = [
+    "assembly", "break", "continue", "delete", "do", "emit", "for", "if", "return", "throw", "var", "while"
+];
+
+// Keywords that can appear between a function's parameter list and its body
+const FUNCTION_HEADER_KEYWORDS: [&str; 9] = [
+    "constant", "external", "internal", "payable", "private", "public", "pure", "returns", "view"
+];
+
+/**
+ * @dev Checks whether the identifier at span is a likely misspelling of one of the candidate
+ *      keywords. If it is, returns an Error nonterminal with a did-you-mean hint and a fix that
+ *      replaces the identifier with the keyword. Identifiers that the input declares are never
+ *      treated as misspellings.
+ * @param input The characters from the input that were read.
+ * @param context The state of the parse, which holds the names that the input declares.
+ * @param span The span of the identifier that may be misspelled.
+ * @param candidates The keywords and names that are valid at the identifier's position.
+ * @param diagnostic Builds the Diagnostic for the misspelled word.
+ * @return An Error nonterminal if a close enough candidate was found.
+ */
+fn misspelled_keyword<S: AsRef<str>>(
+    input: &[char],
+    context: &Context,
+    span: Span,
+    candidates: &[S],
+    diagnostic: fn(String, Span) -> Diagnostic
+) -> Option<NonTerminal> {
+    let word = span.text(input);
+    if context.declares(&word) {
+        return None;
+    }
+    let keyword = suggest::best_match(&word, candidates)?;
+    Some(NonTerminal::Error(Box::new(
+        diagnostic(format!("unexpected identifier `{}`", word), span)
+            .with_code(codes::MISSPELLED_KEYWORD)
+            .with_help(format!("did you mean `{}`?", keyword))
+            .with_suggestion(Suggestion {
+                message: format!("replace `{}` with `{}`", word, keyword),
+                span,
+                replacement: keyword.to_string(),
+            })
+    )))
 }
 
 /*** Top-Level ***/
 
+/**
+ * @dev Returns an error if syntax that was introduced in version is used while parsing an older
+ *      version of Solidity.
+ * @param context The state of the parse, which holds the version that is being parsed.
+ * @param span The span of the keyword that introduces the syntax.
+ * @param syntax The name of the syntax, used in the error message.
+ * @param version The first version of Solidity that supports the syntax.
+ */
+fn unsupported_syntax(context: &Context, span: Span, syntax: &str, version: (usize, usize, usize)) -> Option<NonTerminal> {
+    let (major, minor, patch) = context.options().version;
+    if (major, minor, patch) >= version {
+        return None;
+    }
+    let diagnostic = Diagnostic::error(
+        format!("{} requires Solidity {}.{}.{} or later", syntax, version.0, version.1, version.2),
+        span
+    )
+    .with_code(codes::NEWER_SYNTAX)
+    .with_note(format!("the input is being parsed as Solidity {}.{}.{}", major, minor, patch));
+    Some(NonTerminal::Error(Box::new(diagnostic)))
+}
+
+/**
+ * @dev Adds a source file to sources and parses it. The spans in the tree are offsets into the
+ *      characters of the file, which the SourceMap can convert to lines and columns.
+ * @param sources The files of this run, which the file is added to.
+ * @param path The path of the file.
+ * @param text The text of the file.
+ * @return The id of the file in sources, along with its ParseTree.
+ */
+pub fn parse(sources: &mut SourceMap, path: &str, text: String) -> (FileId, ParseTree) {
+    let id = sources.add(path, text);
+    let options = ParserOptions::default();
+    let input = sources.file(id).chars();
+    (id, parse_source_unit(input, &Context::new(&options).with_declarations(input)))
+}
+
+/**
+ * @dev Finds the names that the input declares by looking for the identifier that follows each
+ *      `contract`, `interface`, `library`, `struct`, `enum`, `modifier`, `function` and `event`
+ *      keyword. This only needs the tokens, so it can run before the input is parsed.
+ * @param input The characters from the input that should be read
+ * @param start The position in the input characters where the lexer should start reading, which
+ *        must be the start of a token or of the whitespace and comments before one.
+ * @param stop Returns true for the start of a token at which the search should stop.
+ * @return The start of the keyword, the keyword and the name of each declaration, in the order
+ *         that they appear, along with the start of the token that the search stopped at, or the
+ *         length of the input if it read all of it.
+ */
+pub(crate) fn declared_names<F: Fn(usize) -> bool>(input: &Vec<char>, start: usize, stop: F) -> (Vec<(usize, lex_4_25::Token, String)>, usize) {
+    let mut names = vec![];
+    let current_ptr = &mut start.clone();
+    loop {
+        match lex_4_25::next_token_with_span(input, current_ptr) {
+            (_, span) if stop(span.start) => return (names, span.start),
+            (keyword @ lex_4_25::Token::Contract, span)  |
+            (keyword @ lex_4_25::Token::Interface, span) |
+            (keyword @ lex_4_25::Token::Library, span)   |
+            (keyword @ lex_4_25::Token::Struct, span)    |
+            (keyword @ lex_4_25::Token::Enum, span)      |
+            (keyword @ lex_4_25::Token::Modifier, span)  |
+            (keyword @ lex_4_25::Token::Function, span)  |
+            (keyword @ lex_4_25::Token::Event, span) => {
+                if let lex_4_25::Token::Identifier(name) = lex_4_25::peek_token(input, current_ptr) {
+                    names.push((span.start, keyword, name));
+                }
+            }
+            (lex_4_25::Token::EOF, _) => return (names, input.len()),
+            _ => ()
+        }
+    }
+}
+
 // Parses the input contract and returns its ParseTree
-pub fn parse(input_string: String) -> ParseTree {
+pub(crate) fn parse_source_unit(input: &Vec<char>, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::SourceUnit.to_leaf();
     let current_ptr = &mut 0;
-    let input = &mut input_string.chars().collect::<Vec<char>>();
     while *current_ptr < input.len() {
-        match lex_4_25::peek_token(input, current_ptr) {
+        match context.peek_token(input, current_ptr) {
             lex_4_25::Token::Pragma => {
-                tree.add_tree(parse_pragma_directive(input, current_ptr));
+                tree.add_tree(parse_pragma_directive(input, current_ptr, context));
             }
             lex_4_25::Token::Import => {
-                tree.add_tree(parse_import_directive(input, current_ptr));
+                let (_, span) = context.peek_token_with_span(input, current_ptr);
+                let directive = parse_import_directive(input, current_ptr, context);
+                // Import directives can't be parsed yet and don't read any input, so their
+                // keyword is skipped to keep the loop moving.
+                if directive.root == NonTerminal::Empty {
+                    context.next_token(input, current_ptr);
+                    let message = String::from("`import` directives are not supported yet");
+                    tree.add_leaf(NonTerminal::Error(Box::new(Diagnostic::error(message, span).with_code(codes::UNSUPPORTED_SYNTAX))));
+                } else {
+                    tree.add_tree(directive);
+                }
             }
             lex_4_25::Token::Contract  |
             lex_4_25::Token::Library   |
             lex_4_25::Token::Interface => {
-                tree.add_tree(parse_contract_definition(input, current_ptr));
+                tree.add_tree(parse_contract_definition(input, current_ptr, context));
             }
             lex_4_25::Token::EOF => {
-                lex_4_25::next_token(input, current_ptr);
+                context.next_token(input, current_ptr);
             }
             actual => {
-                lex_4_25::next_token(input, current_ptr);
+                context.next_token(input, current_ptr);
                 tree.root = NonTerminal::SourceUnit.to_invalid_token_pair(actual);
             }
         }
+        if context.stops_after(&tree) {
+            break;
+        }
     }
     tree
 }
 
+/**
+ * @dev Skips a nonterminal that is nested more deeply than the max_depth option allows. Tokens
+ *      are skipped up to the comma, semicolon or closing delimiter that ends the nonterminal, so
+ *      that the enclosing nonterminals can be parsed as usual. A block ends at its closing brace.
+ * @param input The characters from the input that should be read
+ * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @return An Error leaf that covers the skipped input.
+ */
+fn parse_too_deep(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let (_, mut span) = context.peek_token_with_span(input, current_ptr);
+    span.end = span.start;
+    let mut nesting = 0;
+    loop {
+        match context.peek_token(input, current_ptr) {
+            lex_4_25::Token::OpenParenthesis |
+            lex_4_25::Token::OpenBracket     |
+            lex_4_25::Token::OpenBrace => nesting += 1,
+            lex_4_25::Token::CloseParenthesis |
+            lex_4_25::Token::CloseBracket     |
+            lex_4_25::Token::CloseBrace if nesting > 0 => nesting -= 1,
+            lex_4_25::Token::CloseParenthesis |
+            lex_4_25::Token::CloseBracket     |
+            lex_4_25::Token::CloseBrace       |
+            lex_4_25::Token::Comma            |
+            lex_4_25::Token::Semicolon if nesting == 0 => break,
+            lex_4_25::Token::EOF |
+            lex_4_25::Token::Illegal => break,
+            _ => ()
+        }
+        let (token, skipped) = context.next_token_with_span(input, current_ptr);
+        span.end = skipped.end;
+        if token == lex_4_25::Token::CloseBrace && nesting == 0 {
+            break;
+        }
+    }
+    let max_depth = context.options().max_depth;
+    let diagnostic = Diagnostic::error(
+        format!("the input is nested more than {} levels deep", max_depth),
+        span
+    )
+    .with_code(codes::NESTED_TOO_DEEPLY)
+    .with_help(String::from("increase `ParserOptions::max_depth` to parse more deeply nested input"));
+    NonTerminal::Error(Box::new(diagnostic)).to_leaf()
+}
+
 /*** Pragma ***/
 
 /**
@@ -228,38 +763,38 @@ pub fn parse(input_string: String) -> ParseTree {
  *      debugging.
  * @param input The characters from the input that should be read.
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that either represents a PragmaDirective.
  */
-pub fn parse_pragma_directive(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+pub fn parse_pragma_directive(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::PragmaDirective.to_leaf();
     // Expect a Pragma token. If not found, set the root to an invalid token
     // for later debugging.
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Pragma => (),
         _ => tree.root = NonTerminal::PragmaDirective.to_invalid()
     }
     // Expect an Identifier token. If not found, set add an invalid token as a leaf
     // for later debugging.
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Identifier(name) => tree.add_leaf(NonTerminal::Identifier(name)),
         // TODO(jalextowle): We currently expect `solidity`, but this may change.
         _ => tree.add_invalid(NonTerminal::Identifier(String::from("solidity")))
     }
     // If the next token is a BitwiseXor, it is likely part of the version, and it
     // should be added as a leaf to the tree.
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::BitwiseXor => tree.add_token(lex_4_25::next_token(&input, current_ptr)),
-        _ => ()
-    }
+    if context.peek_token(input, current_ptr) == lex_4_25::Token::BitwiseXor { tree.add_token(context.next_token(input, current_ptr)) }
     // Expect a Version token.
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Version(version) => tree.add_token(lex_4_25::Token::Version(version)),
-        _ => tree.add_invalid_token(lex_4_25::Token::Version(String::from("")))
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Version(String::from("")), codes::EXPECTED_VERSION)
     }
     // Expect a Semicolon token.
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Semicolon => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::Semicolon)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Semicolon, codes::EXPECTED_SEMICOLON)
     }
     tree
 }
@@ -270,10 +805,11 @@ pub fn parse_pragma_directive(input: &Vec<char>, current_ptr: &mut usize) -> Par
  * @dev Parses an ImportDirective nonterminal.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that either represents a valid contract defition or detailed error
  *         information.
  */
-fn parse_import_directive(_input: &Vec<char>, _current_ptr: &mut usize) -> ParseTree { ParseTree::empty() }
+fn parse_import_directive(_input: &Vec<char>, _current_ptr: &mut usize, _context: &Context) -> ParseTree { ParseTree::empty() }
 
 /*** Contract ***/
 
@@ -281,14 +817,15 @@ fn parse_import_directive(_input: &Vec<char>, _current_ptr: &mut usize) -> Parse
  * @dev Parses a contract definition nonterminal.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that either represents a valid contract defition or detailed error
  *         information.
  */
-fn parse_contract_definition(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_contract_definition(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = ParseTree::empty();
     // Look at the next token. The expected token is a Contract, Interface, or Library token.
     // Anything else is Invalid.
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Contract => tree.root = NonTerminal::ContractDefinition(lex_4_25::Token::Contract),
         lex_4_25::Token::Interface => tree.root = NonTerminal::ContractDefinition(lex_4_25::Token::Interface),
         lex_4_25::Token::Library => tree.root = NonTerminal::ContractDefinition(lex_4_25::Token::Library),
@@ -296,26 +833,23 @@ fn parse_contract_definition(input: &Vec<char>, current_ptr: &mut usize) -> Pars
     }
     // The next token should be the Contract name. If an identifier is not found, add an Invalid
     // leaf to the ParseTree.
-    match lex_4_25::next_token(input, current_ptr) {
-        lex_4_25::Token::Identifier(name) => tree.add_leaf(NonTerminal::Identifier(name)),
-        _ => tree.add_invalid(NonTerminal::Identifier(String::from("")))
-    }
+    tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
     // Determine if the ContractDefinition specifies an inheritance hierarchy for the contract that
     // is being defined.
     let mut inheritance = false;
-    match lex_4_25::peek_token(input, current_ptr) {
+    match context.peek_token(input, current_ptr) {
         lex_4_25::Token::OpenBrace => (),
         lex_4_25::Token::Is => inheritance = true,
         // TODO(jalextowle): It would be nice to be able to add a list of expected tokens so that
         // this could be completely accurate.
-        _ => tree.add_invalid_token(lex_4_25::Token::Is)
+        _ => tree.add_expected_token(input, *current_ptr, lex_4_25::Token::Is, codes::EXPECTED_KEYWORD)
     }
     if inheritance {
-        tree.add_tree(parse_inheritance_list(input, current_ptr));
+        tree.add_tree(parse_inheritance_list(input, current_ptr, context));
     }
     // If an inheritance hierarchy was defined, parse the inheritance hierarchy.
     // Parse the contract part and add it to the tree.
-    tree.add_tree(parse_contract_part(input, current_ptr));
+    tree.add_tree(parse_contract_part(input, current_ptr, context));
     tree
 }
 
@@ -324,20 +858,21 @@ fn parse_contract_definition(input: &Vec<char>, current_ptr: &mut usize) -> Pars
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an inheritance list.
  */
-fn parse_inheritance_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_inheritance_list(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::InheritanceList.to_leaf();
     // Expect an Is token
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Is => (),
         actual => tree.root = NonTerminal::InheritanceList.to_invalid_token_pair(actual)
     }
     let mut stop = false;
     while !stop {
-        tree.add_tree(parse_inheritance_specifier(input, current_ptr));
-        if let lex_4_25::Token::Comma = lex_4_25::peek_token(input, current_ptr) {
-            lex_4_25::next_token(input, current_ptr);
+        tree.add_tree(parse_inheritance_specifier(input, current_ptr, context));
+        if let lex_4_25::Token::Comma = context.peek_token(input, current_ptr) {
+            context.next_token(input, current_ptr);
         } else {
             stop = true;
         }
@@ -350,17 +885,19 @@ fn parse_inheritance_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTr
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an inheritance list.
  */
-fn parse_inheritance_specifier(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_inheritance_specifier(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::InheritanceSpecifier.to_leaf();
-    tree.add_tree(parse_user_defined_type_name(input, current_ptr));
-    if let lex_4_25::Token::OpenParenthesis = lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::next_token(input, current_ptr);
-        tree.add_tree(parse_expression_list(input, current_ptr));
-        match lex_4_25::next_token(input, current_ptr) {
+    tree.add_tree(parse_user_defined_type_name(input, current_ptr, context));
+    if let lex_4_25::Token::OpenParenthesis = context.peek_token(input, current_ptr) {
+        context.next_token(input, current_ptr);
+        tree.add_tree(parse_expression_list(input, current_ptr, context));
+        let start = *current_ptr;
+        match context.next_token(input, current_ptr) {
             lex_4_25::Token::CloseParenthesis => (),
-            _ => tree.add_invalid_token(lex_4_25::Token::CloseParenthesis)
+            _ => tree.add_expected_token(input, start, lex_4_25::Token::CloseParenthesis, codes::EXPECTED_CLOSING_DELIMITER)
         }
     }
     tree
@@ -371,80 +908,180 @@ fn parse_inheritance_specifier(input: &Vec<char>, current_ptr: &mut usize) -> Pa
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a contract block.
  */
-fn parse_contract_part(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_contract_part(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::ContractPart.to_leaf();
     // Expect an open brace
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::OpenBrace => (),
         actual => tree.add_leaf(lex_4_25::Token::OpenBrace.to_invalid_pair(actual))
     }
     let mut stop = false;
     while !stop {
-        match lex_4_25::peek_token(input, current_ptr) {
-            lex_4_25::Token::Enum => tree.add_tree(parse_enum_definition(input, current_ptr)),
-            lex_4_25::Token::Event => tree.add_tree(parse_event_definition(input, current_ptr)),
-            lex_4_25::Token::Function => tree.add_tree(parse_function_definition(input, current_ptr)),
-            lex_4_25::Token::Modifier => tree.add_tree(parse_modifier_definition(input, current_ptr)),
-            lex_4_25::Token::Using => tree.add_tree(parse_using_for_declaration(input, current_ptr)),
-            lex_4_25::Token::Struct => tree.add_tree(parse_struct_definition(input, current_ptr)),
-            lex_4_25::Token::CloseBrace => stop = true,
-            _ => tree.add_tree(parse_state_variable_declaration(input, current_ptr))
+        match context.peek_token(input, current_ptr) {
+            lex_4_25::Token::CloseBrace |
+            lex_4_25::Token::EOF => stop = true,
+            _ => {
+                tree.add_tree(parse_contract_member(input, current_ptr, context));
+                if tree.leaves.last().is_some_and(|leaf| context.stops_after(leaf)) {
+                    return tree;
+                }
+            }
         }
     }
     // Expect a close brace
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::CloseBrace => (),
         actual => tree.add_leaf(lex_4_25::Token::CloseBrace.to_invalid_pair(actual))
     }
     tree
 }
 
+/**
+ * @dev Parse a single member of a ContractPart, such as a function definition or a state variable
+ *      declaration. If there are issues parsing the member, error information will be recorded
+ *      in the ParseTree to aid with debugging. When the input is reparsed after an edit, a member
+ *      that the edit didn't touch is reused.
+ * @param input The characters from the input that should be read
+ * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @return A ParseTree that represents a contract member.
+ */
+pub fn parse_contract_member(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    incremental::reuse_or_parse(incremental::Reusable::ContractMember, input, current_ptr, context, parse_new_contract_member)
+}
+
+fn parse_new_contract_member(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    match context.peek_token(input, current_ptr) {
+        lex_4_25::Token::Enum => parse_enum_definition(input, current_ptr, context),
+        lex_4_25::Token::Event => parse_event_definition(input, current_ptr, context),
+        lex_4_25::Token::Constructor |
+        lex_4_25::Token::Function => parse_function_definition(input, current_ptr, context),
+        lex_4_25::Token::Modifier => parse_modifier_definition(input, current_ptr, context),
+        lex_4_25::Token::Using => parse_using_for_declaration(input, current_ptr, context),
+        lex_4_25::Token::Struct => parse_struct_definition(input, current_ptr, context),
+        _ => {
+            let (first, span) = context.peek_token_with_span(input, current_ptr);
+            let mut part = parse_state_variable_declaration(input, current_ptr, context);
+            // If the part could not be parsed as a state variable, it may have started with
+            // a misspelled keyword.
+            if let lex_4_25::Token::Identifier(..) = first {
+                if part.has_errors() {
+                    if let Some(error) = misspelled_keyword(input, context, span, &CONTRACT_PART_KEYWORDS, Diagnostic::error) {
+                        part.add_leaf(error);
+                    }
+                }
+            }
+            part
+        }
+    }
+}
+
 /**
  * @dev Parse a StructDefinition nonterminal. If there are issues parsing the nonterminal, error
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a struct definition.
  */
-fn parse_struct_definition(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree { ParseTree::empty() }
+fn parse_struct_definition(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let mut tree = NonTerminal::StructDefinition.to_leaf();
+    // Expect a Struct token
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
+        lex_4_25::Token::Struct => (),
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Struct, codes::EXPECTED_KEYWORD)
+    }
+    // Expect an Identifier
+    tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
+    // Expect an OpenBrace
+    match context.next_token(input, current_ptr) {
+        lex_4_25::Token::OpenBrace => (),
+        actual => tree.add_leaf(lex_4_25::Token::OpenBrace.to_invalid_pair(actual))
+    }
+    let mut stop = false;
+    while !stop {
+        match context.peek_token(input, current_ptr) {
+            lex_4_25::Token::CloseBrace |
+            lex_4_25::Token::EOF => stop = true,
+            _ => {
+                tree.add_tree(parse_variable_declaration(input, current_ptr, context));
+                let start = *current_ptr;
+                match context.next_token(input, current_ptr) {
+                    lex_4_25::Token::Semicolon => (),
+                    _ => {
+                        tree.add_expected_token(input, start, lex_4_25::Token::Semicolon, codes::EXPECTED_SEMICOLON);
+                        stop = true;
+                    }
+                }
+            }
+        }
+    }
+    // Expect a CloseBrace
+    match context.next_token(input, current_ptr) {
+        lex_4_25::Token::CloseBrace => (),
+        actual => tree.add_leaf(lex_4_25::Token::CloseBrace.to_invalid_pair(actual))
+    }
+    tree
+}
+
+/**
+ * @dev Parse a VariableDeclaration nonterminal. If there are issues parsing the nonterminal, error
+ *      information will be recorded in the ParseTree to aid with debugging.
+ * @param input The characters from the input that should be read
+ * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @return A ParseTree that represents a variable declaration.
+ */
+fn parse_variable_declaration(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let mut tree = NonTerminal::VariableDeclaration.to_leaf();
+    tree.add_tree(parse_type_name(input, current_ptr, context));
+    match context.peek_token(input, current_ptr) {
+        lex_4_25::Token::Calldata |
+        lex_4_25::Token::Memory   |
+        lex_4_25::Token::Storage => {
+            tree.add_leaf(NonTerminal::StorageLocation(context.next_token(input, current_ptr)));
+        }
+        _ => ()
+    }
+    tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
+    tree
+}
 
 /**
  * @dev Parse a StateVariableDeclaration nonterminal. If there are issues parsing the nonterminal, error
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a state variable declaration.
  */
-fn parse_state_variable_declaration(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_state_variable_declaration(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::StateVariableDeclaration.to_leaf();
-    tree.add_tree(parse_type_name(input, current_ptr));
+    tree.add_tree(parse_type_name(input, current_ptr, context));
     let mut stop = false;
     while !stop {
-        match lex_4_25::peek_token(input, current_ptr) {
+        match context.peek_token(input, current_ptr) {
             lex_4_25::Token::Constant |
             lex_4_25::Token::Internal |
             lex_4_25::Token::Private  |
-            lex_4_25::Token::Public   => tree.add_token(lex_4_25::next_token(input, current_ptr)),
+            lex_4_25::Token::Public   => tree.add_token(context.next_token(input, current_ptr)),
             _ => stop = true
         }
     }
-    match lex_4_25::next_token(input, current_ptr) {
-        lex_4_25::Token::Identifier(name) => tree.add_leaf(NonTerminal::Identifier(name)),
-        _ => tree.add_invalid(NonTerminal::Identifier(String::from("")))
-    }
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::Assignment => {
-            tree.add_token(lex_4_25::next_token(input, current_ptr));
-            let last = tree.leaves.len() - 1;
-            tree.leaves[last].add_tree(parse_expression(input, current_ptr));
-        }
-        _ => ()
+    tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
+    if context.peek_token(input, current_ptr) == lex_4_25::Token::Assignment {
+        tree.add_token(context.next_token(input, current_ptr));
+        let last = tree.leaves.len() - 1;
+        tree.leaves[last].add_tree(parse_expression(input, current_ptr, context));
     }
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Semicolon => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::Semicolon)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Semicolon, codes::EXPECTED_SEMICOLON)
     }
     tree
 }
@@ -454,19 +1091,17 @@ fn parse_state_variable_declaration(input: &Vec<char>, current_ptr: &mut usize)
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an enum definition.
  */
-fn parse_enum_definition(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_enum_definition(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::EnumDefinition.to_leaf();
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Enum => (),
         _ => tree.root = NonTerminal::EnumDefinition.to_invalid()
     }
-    match lex_4_25::next_token(input, current_ptr) {
-        lex_4_25::Token::Identifier(name) => tree.add_leaf(NonTerminal::Identifier(name)),
-        _ => tree.add_invalid(NonTerminal::Identifier(String::from("")))
-    }
-    tree.add_tree(parse_enum_value_list(input, current_ptr));
+    tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
+    tree.add_tree(parse_enum_value_list(input, current_ptr, context));
     tree
 }
 
@@ -475,41 +1110,47 @@ fn parse_enum_definition(input: &Vec<char>, current_ptr: &mut usize) -> ParseTre
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an enum value list.
  */
-fn parse_enum_value_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_enum_value_list(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::EnumValueList.to_leaf();
     // Expect an open brace to start the enum value list
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::OpenBrace => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::OpenBrace)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::OpenBrace, codes::EXPECTED_OPENING_DELIMITER)
     }
     // Loop until an enum value is no longer expected
     let mut stop = false;
     while !stop {
         // If an identifier is found, add it as an EnumValue to the EnumValueList. Otherwise, break
         // out of the loop.
-        match lex_4_25::peek_token(input, current_ptr) {
+        match context.peek_token(input, current_ptr) {
             lex_4_25::Token::Identifier(name) => {
                 tree.add_leaf(NonTerminal::EnumValue(name));
-                lex_4_25::next_token(input, current_ptr);
+                context.next_token(input, current_ptr);
+            }
+            ref reserved if reserved.is_reserved() => {
+                tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
             }
             _ => stop = true
         }
         // If the loop should continue, check for a comma. If a comma is found, the loop can
         // continue, but if a comma is not found, break out of the loop.
         if !stop {
-            if let lex_4_25::Token::Comma = lex_4_25::peek_token(input, current_ptr) {
-                lex_4_25::next_token(input, current_ptr);
+            if let lex_4_25::Token::Comma = context.peek_token(input, current_ptr) {
+                context.next_token(input, current_ptr);
             } else {
                 stop = true;
             }
         }
     }
     // Expect a close brace to end the enum value list
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::CloseBrace => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::CloseBrace)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::CloseBrace, codes::EXPECTED_CLOSING_DELIMITER)
     }
     tree
 }
@@ -519,34 +1160,35 @@ fn parse_enum_value_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTre
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a using for declaration.
  */
-fn parse_using_for_declaration(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_using_for_declaration(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::UsingForDeclaration.to_leaf();
     // Expect a Using token
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Using => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::Using)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Using, codes::EXPECTED_KEYWORD)
     }
     // Expect an identifier
-    match lex_4_25::next_token(input, current_ptr) {
-        lex_4_25::Token::Identifier(name) => tree.add_leaf(NonTerminal::Identifier(name)),
-        _ => tree.add_invalid_token(lex_4_25::Token::Identifier(String::from("")))
-    }
+    tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
     // Expect a For token
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::For => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::For)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::For, codes::EXPECTED_KEYWORD)
     }
     // Expect a Multiply token or a TypeName
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::Multiply => tree.add_token(lex_4_25::next_token(input, current_ptr)),
-        _ => tree.add_tree(parse_type_name(input, current_ptr))
+    match context.peek_token(input, current_ptr) {
+        lex_4_25::Token::Multiply => tree.add_token(context.next_token(input, current_ptr)),
+        _ => tree.add_tree(parse_type_name(input, current_ptr, context))
     }
     // Expect a Semicolon token
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Semicolon => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::Semicolon)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Semicolon, codes::EXPECTED_SEMICOLON)
     }
     tree
 }
@@ -556,31 +1198,27 @@ fn parse_using_for_declaration(input: &Vec<char>, current_ptr: &mut usize) -> Pa
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a event definition.
  */
-fn parse_event_definition(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_event_definition(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::EventDefinition.to_leaf();
     // Expect an Event token
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Event => (),
         _ => tree.add_invalid(NonTerminal::EventDefinition)
     }
     // Expect an Identifier
-    match lex_4_25::next_token(input, current_ptr) {
-        lex_4_25::Token::Identifier(name) => tree.add_leaf(NonTerminal::Identifier(name)),
-        _ => tree.add_invalid_token(lex_4_25::Token::Identifier("".to_string()))
-    }
+    tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
     // Parse the parameter list of this event
-    tree.add_tree(parse_event_parameter_list(input, current_ptr));
+    tree.add_tree(parse_event_parameter_list(input, current_ptr, context));
     // If the parser finds an Anonymous token, add it to the tree.
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::Anonymous => tree.add_token(lex_4_25::next_token(input, current_ptr)),
-        _ => (),
-    }
+    if context.peek_token(input, current_ptr) == lex_4_25::Token::Anonymous { tree.add_token(context.next_token(input, current_ptr)) }
     // Expect a Semicolon token.
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Semicolon => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::Semicolon)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Semicolon, codes::EXPECTED_SEMICOLON)
     }
     tree
 }
@@ -590,34 +1228,41 @@ fn parse_event_definition(input: &Vec<char>, current_ptr: &mut usize) -> ParseTr
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a event parameter list.
  */
-fn parse_event_parameter_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_event_parameter_list(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::EventParameterList.to_leaf();
     // Expect an OpenParenthesis token.
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::OpenParenthesis => (),
         _ => tree.root = NonTerminal::EventParameterList.to_invalid()
     }
     let mut stop = false;
     while !stop {
-        match lex_4_25::peek_token(input, current_ptr) {
+        let start = *current_ptr;
+        match context.peek_token(input, current_ptr) {
             lex_4_25::Token::CloseParenthesis => stop = true,
-            _ => tree.add_tree(parse_event_parameter(input, current_ptr))
+            _ => tree.add_tree(parse_event_parameter(input, current_ptr, context))
         }
-        match lex_4_25::peek_token(input, current_ptr) {
+        match context.peek_token(input, current_ptr) {
             lex_4_25::Token::Comma => {
-                lex_4_25::next_token(input, current_ptr);
+                context.next_token(input, current_ptr);
             }
             lex_4_25::Token::CloseParenthesis => stop = true,
             // TODO(jalextowle): This would benefit from multiple expected values
-            _ => tree.add_invalid_token(lex_4_25::Token::CloseParenthesis)
+            _ => {
+                tree.add_expected_token(input, *current_ptr, lex_4_25::Token::CloseParenthesis, codes::EXPECTED_CLOSING_DELIMITER);
+                // Give up on the list if the parameter couldn't be read at all
+                stop = *current_ptr == start;
+            }
         }
     }
     // Expect a CloseParenthesis token.
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::CloseParenthesis => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::CloseParenthesis)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::CloseParenthesis, codes::EXPECTED_CLOSING_DELIMITER)
     }
     tree
 }
@@ -627,19 +1272,18 @@ fn parse_event_parameter_list(input: &Vec<char>, current_ptr: &mut usize) -> Par
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an event parameter.
  */
-fn parse_event_parameter(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_event_parameter(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::EventParameter.to_leaf();
-    tree.add_tree(parse_type_name(input, current_ptr));
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::Indexed => tree.add_token(lex_4_25::next_token(input, current_ptr)),
-        _ => ()
-    }
-    match lex_4_25::peek_token(input, current_ptr) {
+    tree.add_tree(parse_type_name(input, current_ptr, context));
+    if context.peek_token(input, current_ptr) == lex_4_25::Token::Indexed { tree.add_token(context.next_token(input, current_ptr)) }
+    match context.peek_token(input, current_ptr) {
         lex_4_25::Token::Identifier(..) => tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr)),
+        ref reserved if reserved.is_reserved() => tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr)),
         lex_4_25::Token::From => {
-            lex_4_25::next_token(input, current_ptr);
+            context.next_token(input, current_ptr);
             tree.add_leaf(NonTerminal::Identifier(String::from("from")));
         }
         _ => ()
@@ -652,67 +1296,82 @@ fn parse_event_parameter(input: &Vec<char>, current_ptr: &mut usize) -> ParseTre
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an modifier definition.
  */
-fn parse_modifier_definition(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_modifier_definition(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::ModifierDefinition.to_leaf();
     // Expect a Modifier token
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Modifier => (),
         _ => tree.add_invalid(NonTerminal::ModifierDefinition)
     }
     // Expect an Identifier
     tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
     // If the next token is an OpenParenthesis, parse a parameter list.
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::OpenParenthesis => tree.add_tree(parse_parameter_list(input, current_ptr)),
-        _ => ()
-    }
-    tree.add_tree(parse_block(input, current_ptr));
+    if context.peek_token(input, current_ptr) == lex_4_25::Token::OpenParenthesis { tree.add_tree(parse_parameter_list(input, current_ptr, context)) }
+    tree.add_tree(parse_block(input, current_ptr, context));
     tree
 }
 
 /**
  * @dev Parse a FunctionDefinition nonterminal. If there are issues parsing the nonterminal, error
- *      information will be recorded in the ParseTree to aid with debugging.
+ *      information will be recorded in the ParseTree to aid with debugging. Constructors declared
+ *      with the `constructor` keyword are recorded as a ConstructorDefinition, and unnamed
+ *      functions are recorded as a FallbackFunctionDefinition. Neither has an Identifier leaf.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a function definition.
  */
-fn parse_function_definition(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_function_definition(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::FunctionDefinition.to_leaf();
-    // Expect a Function token
-    match lex_4_25::next_token(input, current_ptr) {
-        lex_4_25::Token::Function => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::Function)
+    // Expect a Function or Constructor token
+    let start = *current_ptr;
+    match context.next_token_with_span(input, current_ptr) {
+        (lex_4_25::Token::Constructor, span) => {
+            tree.root = NonTerminal::ConstructorDefinition;
+            if let Some(error) = unsupported_syntax(context, span, "`constructor`", (0, 4, 22)) {
+                tree.add_leaf(error);
+            }
+        }
+        (lex_4_25::Token::Function, _) => {
+            // Expect an Identifier unless this is a fallback function
+            match context.peek_token(input, current_ptr) {
+                lex_4_25::Token::OpenParenthesis => tree.root = NonTerminal::FallbackFunctionDefinition,
+                _ => tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr))
+            }
+        }
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Function, codes::EXPECTED_KEYWORD)
     }
-    // Expect an Identifier
-    tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
     // Parse the function parameter list
-    tree.add_tree(parse_parameter_list(input, current_ptr));
+    tree.add_tree(parse_parameter_list(input, current_ptr, context));
     let mut stop = false;
     while !stop {
-        match lex_4_25::peek_token(input, current_ptr) {
+        match context.peek_token(input, current_ptr) {
             lex_4_25::Token::External |
             lex_4_25::Token::Internal |
             lex_4_25::Token::Public   |
-            lex_4_25::Token::Private  => tree.add_token(lex_4_25::next_token(input, current_ptr)),
+            lex_4_25::Token::Private  => tree.add_token(context.next_token(input, current_ptr)),
             lex_4_25::Token::Pure     |
             lex_4_25::Token::Constant |
             lex_4_25::Token::View     |
-            lex_4_25::Token::Payable => tree.add_leaf(NonTerminal::StateMutability(lex_4_25::next_token(input, current_ptr))),
-            lex_4_25::Token::Identifier(..) => tree.add_tree(parse_modifier_invocation(input, current_ptr)),
+            lex_4_25::Token::Payable => tree.add_leaf(NonTerminal::StateMutability(context.next_token(input, current_ptr))),
+            lex_4_25::Token::Identifier(..) => tree.add_tree(parse_modifier_invocation(input, current_ptr, context)),
             _ => stop = true
         }
     }
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::Returns => {
-            tree.add_token(lex_4_25::next_token(input, current_ptr));
-            tree.add_tree(parse_parameter_list(input, current_ptr));
+    if context.peek_token(input, current_ptr) == lex_4_25::Token::Returns {
+        tree.add_token(context.next_token(input, current_ptr));
+        tree.add_tree(parse_parameter_list(input, current_ptr, context));
+    }
+    // Functions without a body end with a Semicolon and have no Block leaf.
+    match context.peek_token(input, current_ptr) {
+        lex_4_25::Token::Semicolon => {
+            context.next_token(input, current_ptr);
         }
-        _ => (),
+        _ => tree.add_tree(parse_block(input, current_ptr, context))
     }
-    tree.add_tree(parse_block(input, current_ptr));
     tree
 }
 
@@ -721,29 +1380,68 @@ fn parse_function_definition(input: &Vec<char>, current_ptr: &mut usize) -> Pars
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an modifier invocation.
  */
-fn parse_modifier_invocation(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree { ParseTree::empty() }
+fn parse_modifier_invocation(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let mut tree = NonTerminal::ModifierInvocation.to_leaf();
+    // Expect an Identifier. Modifiers are declared by the user, so an identifier that looks like
+    // a misspelled function header keyword is only a warning.
+    let (_, span) = context.peek_token_with_span(input, current_ptr);
+    tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
+    // If the next token is an OpenParenthesis, parse the modifier's arguments.
+    if let lex_4_25::Token::OpenParenthesis = context.peek_token(input, current_ptr) {
+        context.next_token(input, current_ptr);
+        tree.add_tree(parse_expression_list(input, current_ptr, context));
+        let start = *current_ptr;
+        match context.next_token(input, current_ptr) {
+            lex_4_25::Token::CloseParenthesis => (),
+            _ => tree.add_expected_token(input, start, lex_4_25::Token::CloseParenthesis, codes::EXPECTED_CLOSING_DELIMITER)
+        }
+    }
+    if let Some(warning) = misspelled_keyword(input, context, span, &FUNCTION_HEADER_KEYWORDS, Diagnostic::warning) {
+        tree.add_leaf(warning);
+    }
+    tree
+}
 
 /**
  * @dev Parse a ParameterList nonterminal. If there are issues parsing the nonterminal, error
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a parameter list.
  */
-fn parse_parameter_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_parameter_list(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::ParameterList.to_leaf();
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::OpenParenthesis => (),
         // TODO(jalextowle): It may make sense to expand ParameterList (and all nonterminals like
         // it) to take in two lex::Tokens that represent the start and end. In this way, error
         // information would be included more cleanly.
         _ => tree.root = tree.root.to_invalid()
     }
-    match lex_4_25::next_token(input, current_ptr) {
+    let mut stop = false;
+    while !stop {
+        match context.peek_token(input, current_ptr) {
+            lex_4_25::Token::CloseParenthesis |
+            lex_4_25::Token::EOF => stop = true,
+            _ => tree.add_tree(parse_parameter(input, current_ptr, context))
+        }
+        if !stop {
+            match context.peek_token(input, current_ptr) {
+                lex_4_25::Token::Comma => {
+                    context.next_token(input, current_ptr);
+                }
+                _ => stop = true
+            }
+        }
+    }
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::CloseParenthesis => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::CloseParenthesis)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::CloseParenthesis, codes::EXPECTED_CLOSING_DELIMITER)
     }
     tree
 }
@@ -753,34 +1451,79 @@ fn parse_parameter_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a parameter.
  */
-fn parse_parameter(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree { ParseTree::empty() }
+fn parse_parameter(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let mut tree = NonTerminal::Parameter.to_leaf();
+    tree.add_tree(parse_type_name(input, current_ptr, context));
+    match context.peek_token(input, current_ptr) {
+        lex_4_25::Token::Calldata |
+        lex_4_25::Token::Memory   |
+        lex_4_25::Token::Storage => {
+            tree.add_leaf(NonTerminal::StorageLocation(context.next_token(input, current_ptr)));
+        }
+        _ => ()
+    }
+    match context.peek_token(input, current_ptr) {
+        lex_4_25::Token::Identifier(..) => tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr)),
+        ref reserved if reserved.is_reserved() => tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr)),
+        _ => ()
+    }
+    tree
+}
 
 /**
  * @dev Parse a Block nonterminal. If there are issues parsing the nonterminal, error
- *      information will be recorded in the ParseTree to aid with debugging.
+ *      information will be recorded in the ParseTree to aid with debugging. When the input is
+ *      reparsed after an edit, a block that the edit didn't touch is reused.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a block.
  */
-fn parse_block(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+pub fn parse_block(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    incremental::reuse_or_parse(incremental::Reusable::Block, input, current_ptr, context, parse_new_block)
+}
+
+fn parse_new_block(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let _depth = match DepthGuard::enter(context) {
+        Some(depth) => depth,
+        None => return parse_too_deep(input, current_ptr, context)
+    };
     let mut tree = NonTerminal::Block.to_leaf();
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::OpenBrace => (),
         _ => tree.root = tree.root.to_invalid()
     }
     let mut stop = false;
     while !stop {
-        match lex_4_25::peek_token(input, current_ptr) {
-            lex_4_25::Token::CloseBrace => stop = true,
-            _ => tree.add_tree(parse_statement(input, current_ptr))
+        match context.peek_token(input, current_ptr) {
+            lex_4_25::Token::CloseBrace |
+            lex_4_25::Token::EOF => stop = true,
+            _ => {
+                let (first, span) = context.peek_token_with_span(input, current_ptr);
+                let statement = parse_statement(input, current_ptr, context);
+                // Statements that can't be parsed yet don't read any input, so their keyword is
+                // skipped to keep the loop moving.
+                if statement.root == NonTerminal::Empty {
+                    context.next_token(input, current_ptr);
+                    let message = format!("`{}` statements are not supported yet", first.to_source());
+                    tree.add_leaf(NonTerminal::Error(Box::new(Diagnostic::error(message, span).with_code(codes::UNSUPPORTED_SYNTAX))));
+                } else {
+                    tree.add_tree(statement);
+                }
+                if tree.leaves.last().is_some_and(|leaf| context.stops_after(leaf)) {
+                    return tree;
+                }
+            }
         }
     }
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::CloseBrace => (),
         // TODO(jalextowle): Change this to be included directly in the root
-        _ => tree.add_invalid_token(lex_4_25::Token::CloseBrace)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::CloseBrace, codes::EXPECTED_CLOSING_DELIMITER)
     }
     tree
 }
@@ -792,24 +1535,39 @@ fn parse_block(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a statement.
  */
-fn parse_statement(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
-    return match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::If => parse_if_statement(input, current_ptr),
-        lex_4_25::Token::While => parse_while_statement(input, current_ptr),
-        lex_4_25::Token::For => parse_for_statement(input, current_ptr),
-        lex_4_25::Token::Assembly => parse_inline_assembly_statement(input, current_ptr),
-        lex_4_25::Token::Do => parse_do_while_statement(input, current_ptr),
-        lex_4_25::Token::Emit => parse_emit_statement(input, current_ptr),
+pub fn parse_statement(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    match context.peek_token(input, current_ptr) {
+        lex_4_25::Token::If => parse_if_statement(input, current_ptr, context),
+        lex_4_25::Token::While => parse_while_statement(input, current_ptr, context),
+        lex_4_25::Token::For => parse_for_statement(input, current_ptr, context),
+        lex_4_25::Token::Assembly => parse_inline_assembly_statement(input, current_ptr, context),
+        lex_4_25::Token::Do => parse_do_while_statement(input, current_ptr, context),
+        lex_4_25::Token::Emit => parse_emit_statement(input, current_ptr, context),
+        lex_4_25::Token::Return => parse_return_statement(input, current_ptr, context),
         // TODO: This actually should be parse_variable_declaration | parse_expression
         _ => {
-            let mut tree = parse_expression(input, current_ptr);
-            match lex_4_25::next_token(input, current_ptr) {
+            let (first, span) = context.peek_token_with_span(input, current_ptr);
+            let mut tree = parse_expression(input, current_ptr, context);
+            let start = *current_ptr;
+            match context.next_token(input, current_ptr) {
                 lex_4_25::Token::Semicolon => (),
                 // TODO(jalextowle): This should likely use a similar strategy of error reporting
                 // to blocks.
-                _ => tree.add_invalid_token(lex_4_25::Token::Semicolon)
+                _ => tree.add_expected_token(input, start, lex_4_25::Token::Semicolon, codes::EXPECTED_SEMICOLON)
+            }
+            // If the statement could not be parsed, it may have started with a misspelled keyword
+            // or elementary type name.
+            if let lex_4_25::Token::Identifier(..) = first {
+                if tree.has_errors() {
+                    let mut candidates = lex_4_25::elementary_type_keywords();
+                    candidates.extend(STATEMENT_KEYWORDS.iter().map(|keyword| keyword.to_string()));
+                    if let Some(error) = misspelled_keyword(input, context, span, &candidates, Diagnostic::error) {
+                        tree.add_leaf(error);
+                    }
+                }
             }
             tree
         }
@@ -821,629 +1579,284 @@ fn parse_statement(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an if statement.
  */
-fn parse_if_statement(_input: &Vec<char>, _current_ptr: &mut usize) -> ParseTree { ParseTree::empty() }
+fn parse_if_statement(_input: &Vec<char>, _current_ptr: &mut usize, _context: &Context) -> ParseTree { ParseTree::empty() }
 
 /**
  * @dev Parse a WhileStatement nonterminal. If there are issues parsing the nonterminal, error
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a while statement.
  */
-fn parse_while_statement(_input: &Vec<char>, _current_ptr: &mut usize) -> ParseTree { ParseTree::empty() }
+fn parse_while_statement(_input: &Vec<char>, _current_ptr: &mut usize, _context: &Context) -> ParseTree { ParseTree::empty() }
 
 /**
  * @dev Parse a ForStatement nonterminal. If there are issues parsing the nonterminal, error
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a for statement.
  */
-fn parse_for_statement(_input: &Vec<char>, _current_ptr: &mut usize) -> ParseTree { ParseTree::empty() }
+fn parse_for_statement(_input: &Vec<char>, _current_ptr: &mut usize, _context: &Context) -> ParseTree { ParseTree::empty() }
 
 /**
  * @dev Parse an InlineAssemblyStatement nonterminal. If there are issues parsing the nonterminal, error
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an inline assembly.
  */
-fn parse_inline_assembly_statement(_input: &Vec<char>, _current_ptr: &mut usize) -> ParseTree { ParseTree::empty() }
+fn parse_inline_assembly_statement(_input: &Vec<char>, _current_ptr: &mut usize, _context: &Context) -> ParseTree { ParseTree::empty() }
 
 /**
  * @dev Parse a DoWhile nonterminal. If there are issues parsing the nonterminal, error
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a do-while loop.
  */
-fn parse_do_while_statement(_input: &Vec<char>, _current_ptr: &mut usize) -> ParseTree { ParseTree::empty() }
+fn parse_do_while_statement(_input: &Vec<char>, _current_ptr: &mut usize, _context: &Context) -> ParseTree { ParseTree::empty() }
 
 /**
  * @dev Parse an EmitStatement nonterminal. If there are issues parsing the nonterminal, error
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an emit statement.
  */
-fn parse_emit_statement(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_emit_statement(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::EmitStatement.to_leaf();
-    match lex_4_25::next_token(input, current_ptr) {
+    let (emit, span) = context.next_token_with_span(input, current_ptr);
+    match emit {
         lex_4_25::Token::Emit => (),
         _ => tree.root = tree.root.to_invalid()
     }
-    tree.add_tree(parse_expression(input, current_ptr));
+    tree.add_tree(parse_expression(input, current_ptr, context));
     match &tree.leaves[0].root {
         NonTerminal::FunctionCall => (),
         _ => tree.add_invalid(NonTerminal::FunctionCall)
     }
-    match lex_4_25::next_token(input, current_ptr) {
+    if let Some(error) = unsupported_syntax(context, span, "`emit`", (0, 4, 21)) {
+        tree.add_leaf(error);
+    }
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Semicolon => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::Semicolon)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Semicolon, codes::EXPECTED_SEMICOLON)
     }
     tree
 }
 
-/*** Expression ***/
-
 /**
- * @note TODO(jalextowle): This function and parse_expression need to be replaced.
- * @dev Parse an Operation nonterminal. If there are issues parsing the nonterminal, error
+ * @dev Parse a Return nonterminal. If there are issues parsing the nonterminal, error
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
- * @return A ParseTree that represents an event parameter.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @return A ParseTree that represents a return statement.
  */
-fn parse_operation(input: &Vec<char>, current_ptr: &mut usize, left: ParseTree) -> ParseTree {
-    let mut tree = ParseTree::empty();
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::Decrement | lex_4_25::Token::Increment => {
-            tree.root = NonTerminal::Token(lex_4_25::next_token(input, current_ptr));
-            tree.add_tree(left);
-            return parse_operation(input, current_ptr, tree);
-        }
-        lex_4_25::Token::OpenBracket => {
-            tree.root = NonTerminal::IndexAccess;
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::Power               |
-                        lex_4_25::Token::Divide              |
-                        lex_4_25::Token::Minus               |
-                        lex_4_25::Token::Modulus             |
-                        lex_4_25::Token::Multiply            |
-                        lex_4_25::Token::Plus                |
-                        lex_4_25::Token::ShiftLeft           |
-                        lex_4_25::Token::ShiftRight          |
-                        lex_4_25::Token::BitwiseAnd          |
-                        lex_4_25::Token::BitwiseXor          |
-                        lex_4_25::Token::BitwiseOr           |
-                        lex_4_25::Token::GreaterThan         |
-                        lex_4_25::Token::GreaterThanOrEquals |
-                        lex_4_25::Token::LessThan            |
-                        lex_4_25::Token::LessThanOrEquals    |
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
-                }
-                NonTerminal::IndexAccess => tree = tree.merge_expressions(right),
-                NonTerminal::MemberAccess => tree = tree.merge_expressions(right),
-                _ => tree.add_invalid(NonTerminal::Expression)
-            }
-            match lex_4_25::next_token(input, current_ptr) {
-                lex_4_25::Token::CloseBracket => return parse_operation(input, current_ptr, tree),
-                _ => tree.add_invalid_token(lex_4_25::Token::CloseBracket)
-            }
-        }
-        lex_4_25::Token::Dot => {
-            lex_4_25::next_token(input, current_ptr);
-            tree.root = NonTerminal::MemberAccess;
-            // TODO(jalextowle): Is there a way to flatten this if it is all MemberAccess?
-            tree.add_tree(left);
-            let right = lex_4_25::next_token(input, current_ptr);
-            match right {
-                lex_4_25::Token::Identifier(name) => tree.add_leaf(NonTerminal::Identifier(name)),
-                _ => tree.add_invalid(NonTerminal::Identifier(String::from("")))
-            }
-            return parse_operation(input, current_ptr, tree);
-        }
-        lex_4_25::Token::OpenParenthesis => {
-            tree.root = NonTerminal::FunctionCall;
-            tree.add_tree(left);
-            tree.add_tree(parse_function_call_arguments(&input, current_ptr));
-            let right = parse_expression(&input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::Power               |
-                        lex_4_25::Token::Divide              |
-                        lex_4_25::Token::Minus               |
-                        lex_4_25::Token::Modulus             |
-                        lex_4_25::Token::Multiply            |
-                        lex_4_25::Token::Plus                |
-                        lex_4_25::Token::ShiftLeft           |
-                        lex_4_25::Token::ShiftRight          |
-                        lex_4_25::Token::BitwiseAnd          |
-                        lex_4_25::Token::BitwiseXor          |
-                        lex_4_25::Token::BitwiseOr           |
-                        lex_4_25::Token::GreaterThan         |
-                        lex_4_25::Token::GreaterThanOrEquals |
-                        lex_4_25::Token::LessThan            |
-                        lex_4_25::Token::LessThanOrEquals    |
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
-                }
-                _ => ()
-            }
-        }
-        lex_4_25::Token::Power => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::Power               |
-                        lex_4_25::Token::Divide              |
-                        lex_4_25::Token::Minus               |
-                        lex_4_25::Token::Modulus             |
-                        lex_4_25::Token::Multiply            |
-                        lex_4_25::Token::Plus                |
-                        lex_4_25::Token::ShiftLeft           |
-                        lex_4_25::Token::ShiftRight          |
-                        lex_4_25::Token::BitwiseAnd          |
-                        lex_4_25::Token::BitwiseXor          |
-                        lex_4_25::Token::BitwiseOr           |
-                        lex_4_25::Token::GreaterThan         |
-                        lex_4_25::Token::GreaterThanOrEquals |
-                        lex_4_25::Token::LessThan            |
-                        lex_4_25::Token::LessThanOrEquals    |
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
-                }
-                _ => tree.add_invalid(NonTerminal::Expression)
-            }
-        }
+fn parse_return_statement(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let mut tree = NonTerminal::Return.to_leaf();
+    match context.next_token(input, current_ptr) {
+        lex_4_25::Token::Return => (),
+        _ => tree.root = tree.root.to_invalid()
+    }
+    // The returned expression is optional
+    match context.peek_token(input, current_ptr) {
+        lex_4_25::Token::Semicolon => (),
+        _ => tree.add_tree(parse_expression(input, current_ptr, context))
+    }
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
+        lex_4_25::Token::Semicolon => (),
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Semicolon, codes::EXPECTED_SEMICOLON)
+    }
+    tree
+}
+
+/*** Expression ***/
+
+// The precedence of the conditional operator and the assignments, which bind the least tightly
+const ASSIGNMENT_PRECEDENCE: usize = 1;
+
+/**
+ * @dev Returns how tightly a binary operator binds its operands, or None if the token isn't a
+ *      binary operator. Operations with a higher precedence are applied first. The conditional
+ *      operator and the assignments are right associative and every other operator is left
+ *      associative.
+ * @param token The token that may be a binary operator.
+ */
+fn binary_precedence(token: &lex_4_25::Token) -> Option<usize> {
+    match token {
+        lex_4_25::Token::Question         |
+        lex_4_25::Token::Assignment       |
+        lex_4_25::Token::OrEquals         |
+        lex_4_25::Token::XorEquals        |
+        lex_4_25::Token::AndEquals        |
+        lex_4_25::Token::ShiftLeftEquals  |
+        lex_4_25::Token::ShiftRightEquals |
+        lex_4_25::Token::PlusEquals       |
+        lex_4_25::Token::MinusEquals      |
+        lex_4_25::Token::ModEquals        |
+        lex_4_25::Token::MultiplyEquals   |
+        lex_4_25::Token::DivideEquals => Some(ASSIGNMENT_PRECEDENCE),
+        lex_4_25::Token::LogicalOr => Some(2),
+        lex_4_25::Token::LogicalAnd => Some(3),
+        lex_4_25::Token::Equals | lex_4_25::Token::NotEquals => Some(4),
+        lex_4_25::Token::GreaterThan         |
+        lex_4_25::Token::LessThan            |
+        lex_4_25::Token::GreaterThanOrEquals |
+        lex_4_25::Token::LessThanOrEquals => Some(5),
+        lex_4_25::Token::BitwiseOr => Some(6),
+        lex_4_25::Token::BitwiseXor => Some(7),
+        lex_4_25::Token::BitwiseAnd => Some(8),
+        lex_4_25::Token::ShiftLeft | lex_4_25::Token::ShiftRight => Some(9),
+        lex_4_25::Token::Plus | lex_4_25::Token::Minus => Some(10),
         lex_4_25::Token::Divide   |
         lex_4_25::Token::Multiply |
-        lex_4_25::Token::Modulus => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::Divide              |
-                        lex_4_25::Token::Minus               |
-                        lex_4_25::Token::Modulus             |
-                        lex_4_25::Token::Multiply            |
-                        lex_4_25::Token::Plus                |
-                        lex_4_25::Token::ShiftLeft           |
-                        lex_4_25::Token::ShiftRight          |
-                        lex_4_25::Token::BitwiseAnd          |
-                        lex_4_25::Token::BitwiseXor          |
-                        lex_4_25::Token::BitwiseOr           |
-                        lex_4_25::Token::GreaterThan         |
-                        lex_4_25::Token::GreaterThanOrEquals |
-                        lex_4_25::Token::LessThan            |
-                        lex_4_25::Token::LessThanOrEquals    |
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
-                }
-                _ => tree.add_invalid(NonTerminal::Expression)
-            }
-        }
-        lex_4_25::Token::Plus | lex_4_25::Token::Minus => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::Minus               |
-                        lex_4_25::Token::Plus                |
-                        lex_4_25::Token::ShiftLeft           |
-                        lex_4_25::Token::ShiftRight          |
-                        lex_4_25::Token::BitwiseAnd          |
-                        lex_4_25::Token::BitwiseXor          |
-                        lex_4_25::Token::BitwiseOr           |
-                        lex_4_25::Token::GreaterThan         |
-                        lex_4_25::Token::GreaterThanOrEquals |
-                        lex_4_25::Token::LessThan            |
-                        lex_4_25::Token::LessThanOrEquals    |
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
-                }
-                _ => tree.add_invalid(NonTerminal::Expression)
-            }
-        }
-        lex_4_25::Token::ShiftLeft | lex_4_25::Token::ShiftRight => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::ShiftLeft           |
-                        lex_4_25::Token::ShiftRight          |
-                        lex_4_25::Token::BitwiseAnd          |
-                        lex_4_25::Token::BitwiseXor          |
-                        lex_4_25::Token::BitwiseOr           |
-                        lex_4_25::Token::GreaterThan         |
-                        lex_4_25::Token::GreaterThanOrEquals |
-                        lex_4_25::Token::LessThan            |
-                        lex_4_25::Token::LessThanOrEquals    |
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
-                }
-                _ => tree.add_invalid(NonTerminal::Expression)
-            }
-        }
-        lex_4_25::Token::BitwiseAnd => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::BitwiseAnd          |
-                        lex_4_25::Token::BitwiseXor          |
-                        lex_4_25::Token::BitwiseOr           |
-                        lex_4_25::Token::GreaterThan         |
-                        lex_4_25::Token::GreaterThanOrEquals |
-                        lex_4_25::Token::LessThan            |
-                        lex_4_25::Token::LessThanOrEquals    |
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
-                }
-                _ => tree.add_invalid(NonTerminal::Expression)
+        lex_4_25::Token::Modulus => Some(11),
+        lex_4_25::Token::Power => Some(12),
+        _ => None
+    }
+}
+
+/**
+ * @dev Parse the binary operations that follow an operand by precedence climbing. Operations with
+ *      a precedence below min_precedence are left for the caller. Chains of left associative
+ *      operations like `a + b + c` are folded in a loop, so a long chain uses no more stack than a
+ *      short one. Each operation nests the chain one level deeper in the tree, so the rest of a
+ *      chain that goes past the max_depth option is skipped and recorded as an error.
+ * @param input The characters from the input that should be read
+ * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @param left The operand to the left of the first operation.
+ * @param min_precedence The lowest precedence of the operations that should be parsed.
+ * @return A ParseTree that represents the operations.
+ */
+fn parse_operation(input: &Vec<char>, current_ptr: &mut usize, context: &Context, left: ParseTree, min_precedence: usize) -> ParseTree {
+    let mut tree = left;
+    // An operand that couldn't be parsed isn't combined with anything
+    if let NonTerminal::Expression = tree.root {
+        return tree;
+    }
+    let mut depth = vec![];
+    loop {
+        let operator = context.peek_token(input, current_ptr);
+        let precedence = match binary_precedence(&operator) {
+            Some(precedence) if precedence >= min_precedence => precedence,
+            _ => return tree
+        };
+        match DepthGuard::enter(context) {
+            Some(guard) => depth.push(guard),
+            None => {
+                tree.add_tree(parse_too_deep(input, current_ptr, context));
+                return tree;
             }
         }
-        lex_4_25::Token::BitwiseXor => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::BitwiseXor          |
-                        lex_4_25::Token::BitwiseOr           |
-                        lex_4_25::Token::GreaterThan         |
-                        lex_4_25::Token::GreaterThanOrEquals |
-                        lex_4_25::Token::LessThan            |
-                        lex_4_25::Token::LessThanOrEquals    |
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
+        context.next_token(input, current_ptr);
+        let is_conditional = operator == lex_4_25::Token::Question;
+        let mut operation = NonTerminal::Token(operator).to_leaf();
+        operation.add_tree(tree);
+        let right = if precedence == ASSIGNMENT_PRECEDENCE {
+            if is_conditional {
+                // The middle of a conditional is delimited by the `?` and the `:`, so it can be
+                // any expression.
+                operation.add_tree(parse_expression(input, current_ptr, context));
+                let start = *current_ptr;
+                match context.next_token(input, current_ptr) {
+                    lex_4_25::Token::Colon => (),
+                    _ => operation.add_expected_token(input, start, lex_4_25::Token::Colon, codes::EXPECTED_KEYWORD)
                 }
-                _ => tree.add_invalid(NonTerminal::Expression)
             }
+            parse_expression(input, current_ptr, context)
+        } else {
+            let operand = parse_operand(input, current_ptr, context);
+            parse_operation(input, current_ptr, context, operand, precedence + 1)
+        };
+        match right.root {
+            NonTerminal::Expression => operation.add_invalid(NonTerminal::Expression),
+            _ => operation.add_tree(right)
         }
-        lex_4_25::Token::BitwiseOr => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::BitwiseOr           |
-                        lex_4_25::Token::GreaterThan         |
-                        lex_4_25::Token::GreaterThanOrEquals |
-                        lex_4_25::Token::LessThan            |
-                        lex_4_25::Token::LessThanOrEquals    |
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
+        tree = operation;
+    }
+}
+
+/**
+ * @dev Parse the postfix operations that follow an operand. These are increments, decrements,
+ *      index accesses, member accesses and function calls, and they are applied from left to
+ *      right, so `a.b(c)[d]` is an IndexAccess of a FunctionCall of a MemberAccess. Like binary
+ *      operations, they count against the max_depth option.
+ * @param input The characters from the input that should be read
+ * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @param left The operand that the operations are applied to.
+ * @return A ParseTree that represents the operand and its postfix operations.
+ */
+fn parse_postfix_operation(input: &Vec<char>, current_ptr: &mut usize, context: &Context, left: ParseTree) -> ParseTree {
+    let mut tree = left;
+    let mut depth = vec![];
+    loop {
+        if let lex_4_25::Token::Decrement   |
+               lex_4_25::Token::Increment   |
+               lex_4_25::Token::OpenBracket |
+               lex_4_25::Token::Dot         |
+               lex_4_25::Token::OpenParenthesis = context.peek_token(input, current_ptr) {
+            match DepthGuard::enter(context) {
+                Some(guard) => depth.push(guard),
+                None => {
+                    tree.add_tree(parse_too_deep(input, current_ptr, context));
+                    return tree;
                 }
-                _ => tree.add_invalid(NonTerminal::Expression)
             }
         }
-        lex_4_25::Token::GreaterThan         |
-        lex_4_25::Token::LessThan            |
-        lex_4_25::Token::GreaterThanOrEquals |
-        lex_4_25::Token::LessThanOrEquals => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::GreaterThan         |
-                        lex_4_25::Token::GreaterThanOrEquals |
-                        lex_4_25::Token::LessThan            |
-                        lex_4_25::Token::LessThanOrEquals    |
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
-                }
-                _ => tree.add_invalid(NonTerminal::Expression)
+        let mut operation = match context.peek_token(input, current_ptr) {
+            lex_4_25::Token::Decrement | lex_4_25::Token::Increment => {
+                NonTerminal::next_token(input, current_ptr).to_leaf()
             }
-        }
-        lex_4_25::Token::Equals | lex_4_25::Token::NotEquals => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            println!("{:#?}", right);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::Equals              |
-                        lex_4_25::Token::NotEquals           |
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
-                }
-                id @ NonTerminal::Identifier(..) => tree.add_leaf(id),
-                _ => tree.add_invalid(NonTerminal::Expression)
+            lex_4_25::Token::OpenBracket => {
+                context.next_token(input, current_ptr);
+                NonTerminal::IndexAccess.to_leaf()
             }
-        }
-        lex_4_25::Token::LogicalAnd | lex_4_25::Token::LogicalOr => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::LogicalAnd          |
-                        lex_4_25::Token::LogicalOr           |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
-                }
-                _ => tree.add_invalid(NonTerminal::Expression)
+            lex_4_25::Token::Dot => {
+                context.next_token(input, current_ptr);
+                NonTerminal::MemberAccess.to_leaf()
             }
-        }
-        lex_4_25::Token::Question | lex_4_25::Token::Colon => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::Question            |
-                        lex_4_25::Token::Colon               |
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
-                    }
+            lex_4_25::Token::OpenParenthesis => NonTerminal::FunctionCall.to_leaf(),
+            _ => return tree
+        };
+        operation.add_tree(tree);
+        match operation.root {
+            NonTerminal::IndexAccess => {
+                // The index is delimited by the brackets, so it is never merged with the operation
+                // that is being parsed.
+                match context.peek_token(input, current_ptr) {
+                    lex_4_25::Token::CloseBracket => (),
+                    _ => operation.add_tree(parse_expression(input, current_ptr, context))
                 }
-                _ => tree.add_invalid(NonTerminal::Expression)
-            }
-        }
-        lex_4_25::Token::Assignment       |
-        lex_4_25::Token::OrEquals         |
-        lex_4_25::Token::XorEquals        |
-        lex_4_25::Token::AndEquals        |
-        lex_4_25::Token::ShiftLeftEquals  |
-        lex_4_25::Token::ShiftRightEquals |
-        lex_4_25::Token::PlusEquals       |
-        lex_4_25::Token::MinusEquals      |
-        lex_4_25::Token::ModEquals        |
-        lex_4_25::Token::MultiplyEquals   |
-        lex_4_25::Token::DivideEquals => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(left);
-            let right = parse_expression(input, current_ptr);
-            match right.root.clone() {
-                NonTerminal::Token(token) => {
-                    match token {
-                        lex_4_25::Token::Assignment          |
-                        lex_4_25::Token::OrEquals            |
-                        lex_4_25::Token::XorEquals           |
-                        lex_4_25::Token::AndEquals           |
-                        lex_4_25::Token::ShiftLeftEquals     |
-                        lex_4_25::Token::ShiftRightEquals    |
-                        lex_4_25::Token::PlusEquals          |
-                        lex_4_25::Token::MinusEquals         |
-                        lex_4_25::Token::MultiplyEquals      |
-                        lex_4_25::Token::DivideEquals        |
-                        lex_4_25::Token::ModEquals => tree = tree.merge_expressions(right),
-                        lex_4_25::Token::NoMatch => (),
-                        _ => tree.add_tree(right)
+                let start = *current_ptr;
+                match context.next_token(input, current_ptr) {
+                    lex_4_25::Token::CloseBracket => (),
+                    _ => {
+                        operation.add_expected_token(input, start, lex_4_25::Token::CloseBracket, codes::EXPECTED_CLOSING_DELIMITER);
+                        return operation;
                     }
                 }
-                _ => tree.add_invalid(NonTerminal::Expression)
             }
+            // TODO(jalextowle): Is there a way to flatten this if it is all MemberAccess?
+            NonTerminal::MemberAccess => operation.add_leaf(NonTerminal::next_to_identifier(input, current_ptr)),
+            NonTerminal::FunctionCall => operation.add_tree(parse_function_call_arguments(input, current_ptr, context)),
+            _ => ()
         }
-        _ => {
-            tree = left;
-        }
+        tree = operation;
     }
-    tree
 }
 
 /**
@@ -1451,30 +1864,34 @@ fn parse_operation(input: &Vec<char>, current_ptr: &mut usize, left: ParseTree)
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents function call arguments.
  */
-fn parse_function_call_arguments(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_function_call_arguments(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::FunctionCallArguments.to_leaf();
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::OpenParenthesis => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::OpenParenthesis)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::OpenParenthesis, codes::EXPECTED_OPENING_DELIMITER)
     }
-    match lex_4_25::peek_token(input, current_ptr) {
+    match context.peek_token(input, current_ptr) {
         lex_4_25::Token::OpenBrace => {
-            lex_4_25::next_token(input, current_ptr);
-            tree.add_tree(parse_name_value_list(input, current_ptr));
-            match lex_4_25::next_token(input, current_ptr) {
+            context.next_token(input, current_ptr);
+            tree.add_tree(parse_name_value_list(input, current_ptr, context));
+            let start = *current_ptr;
+            match context.next_token(input, current_ptr) {
                 lex_4_25::Token::CloseBrace => (),
-                _ => tree.add_invalid_token(lex_4_25::Token::CloseBrace)
+                _ => tree.add_expected_token(input, start, lex_4_25::Token::CloseBrace, codes::EXPECTED_CLOSING_DELIMITER)
             }
         }
         _ => {
-            tree.add_tree(parse_expression_list(input, current_ptr));
+            tree.add_tree(parse_expression_list(input, current_ptr, context));
         }
     }
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::CloseParenthesis => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::CloseParenthesis)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::CloseParenthesis, codes::EXPECTED_CLOSING_DELIMITER)
     }
     tree
 }
@@ -1484,26 +1901,28 @@ fn parse_function_call_arguments(input: &Vec<char>, current_ptr: &mut usize) ->
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a name value list.
  */
-fn parse_name_value_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_name_value_list(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::NameValueList.to_leaf();
     let mut stop = false;
     while !stop {
         let mut subtree = NonTerminal::Token(lex_4_25::Token::Colon).to_leaf();
-        match lex_4_25::peek_token(input, current_ptr) {
+        match context.peek_token(input, current_ptr) {
             lex_4_25::Token::Identifier(..) => {
                 subtree.add_leaf(NonTerminal::Identifier(lex_4_25::Token::next_identifier_name(input, current_ptr)));
             }
             _ => stop = true
         }
         if !stop {
-            match lex_4_25::next_token(input, current_ptr) {
+            let start = *current_ptr;
+            match context.next_token(input, current_ptr) {
                 lex_4_25::Token::Colon => (),
-                _ => subtree.add_invalid_token(lex_4_25::Token::Colon)
+                _ => subtree.add_expected_token(input, start, lex_4_25::Token::Colon, codes::EXPECTED_KEYWORD)
             }
-            subtree.add_tree(parse_expression(input, current_ptr));
-            match lex_4_25::peek_token(input, current_ptr) {
+            subtree.add_tree(parse_expression(input, current_ptr, context));
+            match context.peek_token(input, current_ptr) {
                 lex_4_25::Token::Comma => (),
                 _ => stop = true
             }
@@ -1515,71 +1934,183 @@ fn parse_name_value_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTre
 
 /**
  * @dev Parse an Expression nonterminal. If there are issues parsing the nonterminal, error
- *      information will be recorded in the ParseTree to aid with debugging.
+ *      information will be recorded in the ParseTree to aid with debugging. An expression that is
+ *      nested more deeply than the max_depth option allows is skipped and recorded as an error.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an expression.
  */
-pub fn parse_expression(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
-    let mut tree = NonTerminal::Expression.to_leaf();
-    match lex_4_25::peek_token(input, current_ptr) {
+pub fn parse_expression(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let _depth = match DepthGuard::enter(context) {
+        Some(depth) => depth,
+        None => return parse_too_deep(input, current_ptr, context)
+    };
+    let left = parse_operand(input, current_ptr, context);
+    parse_operation(input, current_ptr, context, left, ASSIGNMENT_PRECEDENCE)
+}
+
+/**
+ * @dev Parse an operand of a binary operation, which is a primary expression along with the
+ *      prefix and postfix operations that are applied to it. If there are issues parsing the
+ *      operand, error information will be recorded in the ParseTree to aid with debugging.
+ * @param input The characters from the input that should be read
+ * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @return A ParseTree that represents an operand.
+ */
+fn parse_operand(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    // Prefix operations are collected in a loop so that long chains like `!!!!a` don't use any
+    // more stack than short ones. Each of them still nests the operand a level deeper.
+    let mut prefixes = vec![];
+    let mut depth = vec![];
+    while let prefix @ (lex_4_25::Token::Exclamation |
+                        lex_4_25::Token::Tilda       |
+                        lex_4_25::Token::Delete      |
+                        lex_4_25::Token::Increment   |
+                        lex_4_25::Token::Decrement   |
+                        lex_4_25::Token::Plus        |
+                        lex_4_25::Token::Minus) = context.peek_token(input, current_ptr) {
+        match DepthGuard::enter(context) {
+            Some(guard) => depth.push(guard),
+            None => return parse_too_deep(input, current_ptr, context)
+        }
+        context.next_token(input, current_ptr);
+        prefixes.push(prefix);
+    }
+    let mut tree = match context.peek_token(input, current_ptr) {
         lex_4_25::Token::New => {
-            tree.add_leaf(NonTerminal::NewExpression);
-            tree.leaves[0].add_tree(parse_type_name(input, current_ptr));
+            context.next_token(input, current_ptr);
+            let mut left = NonTerminal::NewExpression.to_leaf();
+            left.add_tree(parse_type_name(input, current_ptr, context));
+            parse_postfix_operation(input, current_ptr, context, left)
         }
         lex_4_25::Token::DecimalNumber(..) | lex_4_25::Token::HexNumber(..) => {
             let mut left = NonTerminal::next_token(input, current_ptr).to_leaf();
-            let peek = lex_4_25::peek_token(input, current_ptr);
+            let peek = context.peek_token(input, current_ptr);
             if peek.is_number_unit() {
-                 left.add_token(lex_4_25::next_token(input, current_ptr));
+                 left.add_token(context.next_token(input, current_ptr));
             }
-            tree = parse_operation(input, current_ptr, left);
+            parse_postfix_operation(input, current_ptr, context, left)
         }
         lex_4_25::Token::Identifier(..) => {
             let left = NonTerminal::next_to_identifier(input, current_ptr).to_leaf();
-            println!("left: {:#?}", left);
-            tree = parse_operation(input, current_ptr, left.clone());
-            println!("tree: {:#?}", tree);
-            match tree.root {
-                NonTerminal::Invalid(..) => tree = left,
-                _ => ()
-            }
+            parse_postfix_operation(input, current_ptr, context, left)
+        }
+        ref reserved if reserved.is_reserved() => {
+            let left = NonTerminal::next_to_identifier(input, current_ptr).to_leaf();
+            parse_postfix_operation(input, current_ptr, context, left)
         }
         lex_4_25::Token::HexLiteral(..)    |
         lex_4_25::Token::StringLiteral(..) |
         lex_4_25::Token::True              |
         lex_4_25::Token::False => {
             let left = NonTerminal::next_token(input, current_ptr).to_leaf();
-            tree = parse_operation(input, current_ptr, left);
+            parse_postfix_operation(input, current_ptr, context, left)
         }
         lex_4_25::Token::OpenParenthesis => {
-            lex_4_25::next_token(input, current_ptr);
-            tree = parse_expression(input, current_ptr);
-            let mut stop = false;
-            while !stop {
-                match lex_4_25::next_token(input, current_ptr) {
-                    lex_4_25::Token::Comma => tree.add_tree(parse_expression(input, current_ptr)),
-                    lex_4_25::Token::CloseParenthesis => stop = true,
-                    // TODO(jalextowle): Use list of invalid tokens
-                    _ => tree.add_invalid_token(lex_4_25::Token::CloseParenthesis)
-                }
-                tree = parse_operation(input, current_ptr, tree);
-            }
+            let left = parse_tuple_expression(input, current_ptr, context);
+            parse_postfix_operation(input, current_ptr, context, left)
         }
-        lex_4_25::Token::Exclamation |
-        lex_4_25::Token::Tilda       |
-        lex_4_25::Token::Delete      |
-        lex_4_25::Token::Increment   |
-        lex_4_25::Token::Decrement   |
-        lex_4_25::Token::Plus        |
-        lex_4_25::Token::Minus => {
-            tree.root = NonTerminal::next_token(input, current_ptr);
-            tree.add_tree(parse_expression(input, current_ptr));
+        lex_4_25::Token::OpenBracket => {
+            let left = parse_inline_array_expression(input, current_ptr, context);
+            parse_postfix_operation(input, current_ptr, context, left)
         }
         elementary => {
+            // Elementary type names can be used as expressions in casts like `address(this)`.
             if elementary.is_elementary_type() {
-                let left = NonTerminal::next_token(input, current_ptr).to_leaf();
-                tree = parse_operation(input, current_ptr, left);
+                context.next_token(input, current_ptr);
+                let mut left = NonTerminal::ElementaryTypeNameExpression.to_leaf();
+                left.add_token(elementary);
+                parse_postfix_operation(input, current_ptr, context, left)
+            } else {
+                NonTerminal::Expression.to_leaf()
+            }
+        }
+    };
+    while let Some(prefix) = prefixes.pop() {
+        let mut operation = NonTerminal::Token(prefix).to_leaf();
+        operation.add_tree(tree);
+        tree = operation;
+    }
+    tree
+}
+
+/**
+ * @dev Parse a TupleExpression nonterminal. If there are issues parsing the nonterminal, error
+ *      information will be recorded in the ParseTree to aid with debugging. Components that are
+ *      left out, as in `(, b) = f()`, are recorded as Empty leaves. A single parenthesised
+ *      expression is not a tuple, so the expression itself is returned.
+ * @param input The characters from the input that should be read
+ * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @return A ParseTree that represents a tuple expression.
+ */
+fn parse_tuple_expression(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let mut tree = NonTerminal::TupleExpression.to_leaf();
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
+        lex_4_25::Token::OpenParenthesis => (),
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::OpenParenthesis, codes::EXPECTED_OPENING_DELIMITER)
+    }
+    let mut is_tuple = false;
+    let mut stop = false;
+    while !stop {
+        match context.peek_token(input, current_ptr) {
+            lex_4_25::Token::Comma |
+            lex_4_25::Token::CloseParenthesis => tree.add_leaf(NonTerminal::Empty),
+            _ => tree.add_tree(parse_expression(input, current_ptr, context))
+        }
+        let start = *current_ptr;
+        match context.next_token(input, current_ptr) {
+            lex_4_25::Token::Comma => is_tuple = true,
+            lex_4_25::Token::CloseParenthesis => stop = true,
+            _ => {
+                tree.add_expected_token(input, start, lex_4_25::Token::CloseParenthesis, codes::EXPECTED_CLOSING_DELIMITER);
+                stop = true;
+            }
+        }
+    }
+    if !is_tuple && tree.leaves.len() == 1 {
+        return match tree.leaves[0].root {
+            // `()` is an empty tuple
+            NonTerminal::Empty => NonTerminal::TupleExpression.to_leaf(),
+            _ => *tree.leaves.remove(0)
+        }
+    }
+    tree
+}
+
+/**
+ * @dev Parse an InlineArrayExpression nonterminal. If there are issues parsing the nonterminal, error
+ *      information will be recorded in the ParseTree to aid with debugging.
+ * @param input The characters from the input that should be read
+ * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @return A ParseTree that represents an inline array expression.
+ */
+fn parse_inline_array_expression(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let mut tree = NonTerminal::InlineArrayExpression.to_leaf();
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
+        lex_4_25::Token::OpenBracket => (),
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::OpenBracket, codes::EXPECTED_OPENING_DELIMITER)
+    }
+    let mut stop = false;
+    while !stop {
+        // Unlike tuples, inline arrays can't leave out components.
+        match context.peek_token(input, current_ptr) {
+            lex_4_25::Token::Comma |
+            lex_4_25::Token::CloseBracket => tree.add_invalid(NonTerminal::Expression),
+            _ => tree.add_tree(parse_expression(input, current_ptr, context))
+        }
+        let start = *current_ptr;
+        match context.next_token(input, current_ptr) {
+            lex_4_25::Token::Comma => (),
+            lex_4_25::Token::CloseBracket => stop = true,
+            _ => {
+                tree.add_expected_token(input, start, lex_4_25::Token::CloseBracket, codes::EXPECTED_CLOSING_DELIMITER);
+                stop = true;
             }
         }
     }
@@ -1591,20 +2122,21 @@ pub fn parse_expression(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an expression list.
  */
-fn parse_expression_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_expression_list(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::ExpressionList.to_leaf();
     let mut stop = false;
     while !stop {
-        let returned = parse_expression(&input, current_ptr);
+        let returned = parse_expression(input, current_ptr, context);
         match tree.root {
             NonTerminal::Invalid(..) => stop = true,
             _ => tree.add_tree(returned)
         }
         if !stop {
-            if let lex_4_25::Token::Comma = lex_4_25::peek_token(input, current_ptr) {
-                lex_4_25::next_token(input, current_ptr);
+            if let lex_4_25::Token::Comma = context.peek_token(input, current_ptr) {
+                context.next_token(input, current_ptr);
             } else {
                 stop = true;
             }
@@ -1620,24 +2152,47 @@ fn parse_expression_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTre
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a typename.
  */
-pub fn parse_type_name(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
-    return match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::Identifier(..) => parse_user_defined_type_name(input, current_ptr),
-        lex_4_25::Token::Function => parse_function_type_name(input, current_ptr),
-        lex_4_25::Token::Mapping => parse_mapping(input, current_ptr),
+pub fn parse_type_name(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let element = match context.peek_token(input, current_ptr) {
+        lex_4_25::Token::Identifier(..) => {
+            let (_, span) = context.peek_token_with_span(input, current_ptr);
+            let mut tree = parse_user_defined_type_name(input, current_ptr, context);
+            // A user defined type name that is a single identifier may be a misspelled elementary
+            // type or a misspelled name of a type that the input declares. This is only a warning,
+            // since the name may be declared in another file.
+            if tree.leaves.len() == 1 {
+                let mut candidates = lex_4_25::elementary_type_keywords();
+                candidates.extend(context.declared_types().map(String::from));
+                if let Some(warning) = misspelled_keyword(input, context, span, &candidates, Diagnostic::warning) {
+                    tree.add_leaf(warning);
+                }
+            }
+            tree
+        }
+        lex_4_25::Token::Function => parse_function_type_name(input, current_ptr, context),
+        lex_4_25::Token::Mapping => parse_mapping(input, current_ptr, context),
         elementary => {
-            if elementary.is_elementary_type() {
-                lex_4_25::next_token(input, current_ptr);
-                // Try to parse an array type and return elementary if the type isn't followed by
-                // array brackets.
-                parse_array_type_name(input, current_ptr, elementary.to_token().to_leaf())
+            if elementary.is_fixed() || elementary.is_ufixed() {
+                context.next_token(input, current_ptr);
+                // Fixed point types carry their dimensions, so they are wrapped in an
+                // ElementaryTypeName to make them easy to distinguish from identifiers.
+                let mut fixed = NonTerminal::ElementaryTypeName.to_leaf();
+                fixed.add_token(elementary);
+                fixed
+            } else if elementary.is_elementary_type() {
+                context.next_token(input, current_ptr);
+                elementary.to_token().to_leaf()
             } else {
-                NonTerminal::TypeName.to_invalid().to_leaf()
+                return NonTerminal::TypeName.to_invalid().to_leaf();
             }
         }
-    }
+    };
+    // Try to parse an array type and return the element type if it isn't followed by array
+    // brackets.
+    parse_array_type_name(input, current_ptr, context, element)
 }
 
 /**
@@ -1645,17 +2200,18 @@ pub fn parse_type_name(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a user defined type name.
  */
-fn parse_user_defined_type_name(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_user_defined_type_name(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::UserDefinedTypeName.to_leaf();
     let mut stop = false;
     while !stop {
         // Expect an identifier
         tree.add_leaf(NonTerminal::next_to_identifier(input, current_ptr));
         if !stop {
-            if let lex_4_25::Token::Dot = lex_4_25::peek_token(&input, current_ptr) {
-                lex_4_25::next_token(&input, current_ptr);
+            if let lex_4_25::Token::Dot = context.peek_token(input, current_ptr) {
+                context.next_token(input, current_ptr);
             } else {
                 stop = true;
             }
@@ -1669,64 +2225,90 @@ fn parse_user_defined_type_name(input: &Vec<char>, current_ptr: &mut usize) -> P
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a mapping.
  */
-fn parse_mapping(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_mapping(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let _depth = match DepthGuard::enter(context) {
+        Some(depth) => depth,
+        None => return parse_too_deep(input, current_ptr, context)
+    };
     let mut tree = NonTerminal::Mapping.to_leaf();
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Mapping => (),
         _ => tree.add_invalid(NonTerminal::Mapping)
     }
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::OpenParenthesis => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::OpenParenthesis)
-    }
-    let elementary = lex_4_25::next_token(input, current_ptr);
-    if elementary.is_elementary_type() {
-        tree.add_leaf(elementary.to_token());
-    } else {
-        tree.add_invalid(NonTerminal::ElementaryTypeName);
-    }
-    match lex_4_25::next_token(input, current_ptr) {
-        lex_4_25::Token::Arrow => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::Arrow)
-    }
-    tree.add_tree(parse_type_name(input, current_ptr));
-    match lex_4_25::next_token(input, current_ptr) {
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::OpenParenthesis, codes::EXPECTED_OPENING_DELIMITER)
+    }
+    // Parse the key as a full type name so that the mapping can still be read when the key type
+    // is not allowed.
+    let (_, key_span) = context.peek_token_with_span(input, current_ptr);
+    let key = parse_type_name(input, current_ptr, context);
+    let span = Span::new(key_span.start, *current_ptr);
+    let key_error = match key.root {
+        NonTerminal::ArrayTypeName => Some(String::from("arrays cannot be used as mapping keys")),
+        NonTerminal::Mapping => Some(String::from("mappings cannot be used as mapping keys")),
+        NonTerminal::FunctionTypeName => Some(String::from("function types cannot be used as mapping keys")),
+        NonTerminal::Token(lex_4_25::Token::Var) => Some(String::from("`var` cannot be used as a mapping key")),
+        // A name like `Library.Info` names a struct if its last part does.
+        NonTerminal::UserDefinedTypeName => match key.leaves.last().map(|leaf| &leaf.root) {
+            Some(NonTerminal::Identifier(name)) if context.declares_struct(name) => {
+                Some(format!("struct `{}` cannot be used as a mapping key", span.text(input)))
+            }
+            _ => None
+        },
+        _ => None
+    };
+    tree.add_tree(key);
+    if let Some(message) = key_error {
+        let diagnostic = Diagnostic::error(message, span).with_code(codes::INVALID_MAPPING_KEY);
+        tree.add_leaf(NonTerminal::Error(Box::new(diagnostic)));
+    }
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
+        lex_4_25::Token::Arrow => tree.add_token(lex_4_25::Token::Arrow),
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::Arrow, codes::EXPECTED_KEYWORD)
+    }
+    tree.add_tree(parse_type_name(input, current_ptr, context));
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::CloseParenthesis => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::CloseParenthesis)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::CloseParenthesis, codes::EXPECTED_CLOSING_DELIMITER)
     }
     tree
 }
 
 /**
  * @dev Parse an ArrayTypeName nonterminal. If there are issues parsing the nonterminal, error
- *      information will be recorded in the ParseTree to aid with debugging.
+ *      information will be recorded in the ParseTree to aid with debugging. Each pair of brackets
+ *      wraps the type to its left in another ArrayTypeName, so `T[][3]` is a fixed size array of
+ *      three dynamic arrays. A dynamic dimension has the element type as its only leaf, and a
+ *      fixed dimension has the element type followed by the length expression.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
- * @return A ParseTree that represents an array type name.
+ * @param context The state of the parse, which holds the options that it runs with.
+ * @param left The element type that was parsed before the brackets.
+ * @return A ParseTree that represents an array type name, or left if there are no brackets.
  */
-fn parse_array_type_name(input: &Vec<char>, current_ptr: &mut usize, left: ParseTree) -> ParseTree {
-    let mut tree = NonTerminal::ArrayTypeName.to_leaf();
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::OpenBracket => {
-            lex_4_25::next_token(input, current_ptr);
-        }
-        _ => return left
-    }
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::CloseBracket => {
-            lex_4_25::next_token(input, current_ptr);
-            return left;
+fn parse_array_type_name(input: &Vec<char>, current_ptr: &mut usize, context: &Context, left: ParseTree) -> ParseTree {
+    let mut tree = left;
+    while let lex_4_25::Token::OpenBracket = context.peek_token(input, current_ptr) {
+        context.next_token(input, current_ptr);
+        let mut array = NonTerminal::ArrayTypeName.to_leaf();
+        array.add_tree(tree);
+        match context.peek_token(input, current_ptr) {
+            lex_4_25::Token::CloseBracket => (),
+            _ => array.add_tree(parse_expression(input, current_ptr, context))
         }
-        _ => {
-            tree.add_tree(left);
-            tree.add_tree(parse_expression(input, current_ptr));
+        let start = *current_ptr;
+        match context.next_token(input, current_ptr) {
+            lex_4_25::Token::CloseBracket => (),
+            _ => array.add_expected_token(input, start, lex_4_25::Token::CloseBracket, codes::EXPECTED_CLOSING_DELIMITER)
         }
-    }
-    match lex_4_25::next_token(input, current_ptr) {
-        lex_4_25::Token::CloseBracket => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::CloseBracket)
+        tree = array;
     }
     tree
 }
@@ -1736,36 +2318,38 @@ fn parse_array_type_name(input: &Vec<char>, current_ptr: &mut usize, left: Parse
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a function type.
  */
-fn parse_function_type_name(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_function_type_name(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
+    let _depth = match DepthGuard::enter(context) {
+        Some(depth) => depth,
+        None => return parse_too_deep(input, current_ptr, context)
+    };
     let mut tree = NonTerminal::FunctionTypeName.to_leaf();
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::Function => (),
         _ => tree.root = tree.root.to_invalid()
     }
-    tree.add_tree(parse_function_type_parameter_list(input, current_ptr));
+    tree.add_tree(parse_function_type_parameter_list(input, current_ptr, context));
     let mut stop = false;
     while !stop {
-        match lex_4_25::peek_token(input, current_ptr) {
+        match context.peek_token(input, current_ptr) {
             lex_4_25::Token::External |
             lex_4_25::Token::Internal |
             lex_4_25::Token::Public   |
-            lex_4_25::Token::Private  => tree.add_token(lex_4_25::next_token(input, current_ptr)),
+            lex_4_25::Token::Private  => tree.add_token(context.next_token(input, current_ptr)),
             lex_4_25::Token::Pure     |
             lex_4_25::Token::Constant |
             lex_4_25::Token::View     |
-            lex_4_25::Token::Payable => tree.add_leaf(NonTerminal::StateMutability(lex_4_25::next_token(input, current_ptr))),
+            lex_4_25::Token::Payable => tree.add_leaf(NonTerminal::StateMutability(context.next_token(input, current_ptr))),
             _ => stop = true
         }
     }
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::Returns => {
-            tree.add_token(lex_4_25::next_token(input, current_ptr));
-            let last = tree.leaves.len() - 1;
-            tree.leaves[last].add_tree(parse_function_type_parameter_list(input, current_ptr));
-        }
-        _ => (),
+    if context.peek_token(input, current_ptr) == lex_4_25::Token::Returns {
+        tree.add_token(context.next_token(input, current_ptr));
+        let last = tree.leaves.len() - 1;
+        tree.leaves[last].add_tree(parse_function_type_parameter_list(input, current_ptr, context));
     }
     tree
 }
@@ -1775,32 +2359,34 @@ fn parse_function_type_name(input: &Vec<char>, current_ptr: &mut usize) -> Parse
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents an function type parameter list.
  */
-fn parse_function_type_parameter_list(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_function_type_parameter_list(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::FunctionTypeParameterList.to_leaf();
-    match lex_4_25::next_token(input, current_ptr) {
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::OpenParenthesis => (),
         _ => tree.root = tree.root.to_invalid()
     }
     let mut stop = false;
     while !stop {
-        match lex_4_25::peek_token(input, current_ptr) {
+        match context.peek_token(input, current_ptr) {
             lex_4_25::Token::CloseParenthesis => stop = true,
-            _ => tree.add_tree(parse_function_type_parameter(input, current_ptr))
+            _ => tree.add_tree(parse_function_type_parameter(input, current_ptr, context))
         }
         if !stop {
-            match lex_4_25::peek_token(input, current_ptr) {
+            match context.peek_token(input, current_ptr) {
                 lex_4_25::Token::Comma => {
-                    lex_4_25::next_token(input, current_ptr);
+                    context.next_token(input, current_ptr);
                 }
                 _ => stop = true
             }
         }
     }
-    match lex_4_25::next_token(input, current_ptr) {
+    let start = *current_ptr;
+    match context.next_token(input, current_ptr) {
         lex_4_25::Token::CloseParenthesis => (),
-        _ => tree.add_invalid_token(lex_4_25::Token::CloseParenthesis)
+        _ => tree.add_expected_token(input, start, lex_4_25::Token::CloseParenthesis, codes::EXPECTED_CLOSING_DELIMITER)
     }
     tree
 }
@@ -1810,15 +2396,17 @@ fn parse_function_type_parameter_list(input: &Vec<char>, current_ptr: &mut usize
  *      information will be recorded in the ParseTree to aid with debugging.
  * @param input The characters from the input that should be read
  * @param current_ptr The position in the input characters where the lexer should start reading.
+ * @param context The state of the parse, which holds the options that it runs with.
  * @return A ParseTree that represents a function type parameter.
  */
-fn parse_function_type_parameter(input: &Vec<char>, current_ptr: &mut usize) -> ParseTree {
+fn parse_function_type_parameter(input: &Vec<char>, current_ptr: &mut usize, context: &Context) -> ParseTree {
     let mut tree = NonTerminal::FunctionTypeParameter.to_leaf();
-    tree.add_tree(parse_type_name(input, current_ptr));
-    match lex_4_25::peek_token(input, current_ptr) {
-        lex_4_25::Token::Memory |
+    tree.add_tree(parse_type_name(input, current_ptr, context));
+    match context.peek_token(input, current_ptr) {
+        lex_4_25::Token::Calldata |
+        lex_4_25::Token::Memory   |
         lex_4_25::Token::Storage => {
-            tree.add_leaf(NonTerminal::StorageLocation(lex_4_25::next_token(input, current_ptr)));
+            tree.add_leaf(NonTerminal::StorageLocation(context.next_token(input, current_ptr)));
         }
         _ => ()
     }
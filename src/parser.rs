@@ -0,0 +1,286 @@
+use std::cell::{ Cell, RefCell };
+use super::cst::{ self, SyntaxNode, TokenRecord };
+use super::diagnostics::Span;
+use super::incremental::{ self, ParsedSource, Session, TextEdit };
+use super::lex_4_25;
+use super::parse_4_25::{ self, ParseTree };
+
+/**
+ * @dev The settings that a Parser uses for every input that it reads.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParserOptions {
+    // The version of Solidity that the input is written in, as (major, minor, patch). Syntax that
+    // was introduced after this version is reported as an error.
+    pub version: (usize, usize, usize),
+    // If false, the returned tree ends at the first error that was found.
+    pub error_recovery: bool,
+    // If true, comments and whitespace are kept in concrete syntax trees.
+    pub retain_trivia: bool,
+    // The deepest that expressions, blocks and type names may be nested. Each operation in a
+    // chain like `a + b + c` or `a.b.c` counts as a level, since it nests the chain in the tree.
+    pub max_depth: usize,
+}
+
+impl Default for ParserOptions {
+    fn default() -> ParserOptions {
+        ParserOptions {
+            version: (0, 4, 25),
+            error_recovery: true,
+            retain_trivia: false,
+            max_depth: 256,
+        }
+    }
+}
+
+/**
+ * @dev The state of a single parse, which the parse functions pass on to each other. It holds the
+ *      options that the parse runs with, the names that the input declares, how deeply the
+ *      nonterminal that is currently being read is nested, the tokens that have been read and,
+ *      when the input is parsed incrementally, the nodes that can be reused.
+ */
+#[derive(Debug)]
+pub struct Context<'a> {
+    options: &'a ParserOptions,
+    declared: Vec<(lex_4_25::Token, String)>,
+    depth: Cell<usize>,
+    session: Option<RefCell<Session>>,
+    tokens: RefCell<TokenRecord>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(options: &'a ParserOptions) -> Context<'a> {
+        Context { options, declared: vec![], depth: Cell::new(0), session: None, tokens: RefCell::default() }
+    }
+
+    // Returns this context along with the names that input declares. Names can be used before
+    // they are declared, so the whole input is scanned before it is parsed.
+    pub fn with_declarations(mut self, input: &Vec<char>) -> Context<'a> {
+        let (declared, _) = parse_4_25::declared_names(input, 0, |_| false);
+        self.declared = declared.into_iter().map(|(_, keyword, name)| (keyword, name)).collect();
+        self
+    }
+
+    // Returns this context along with names that were found in the input beforehand
+    pub(crate) fn with_declared(mut self, declared: Vec<(lex_4_25::Token, String)>) -> Context<'a> {
+        self.declared = declared;
+        self
+    }
+
+    // Returns this context along with an incremental session that reusable nodes are looked up in
+    // and recorded to
+    pub(crate) fn with_session(mut self, session: Session) -> Context<'a> {
+        self.session = Some(RefCell::new(session));
+        self
+    }
+
+    pub fn options(&self) -> &ParserOptions {
+        self.options
+    }
+
+    pub(crate) fn session(&self) -> Option<&RefCell<Session>> {
+        self.session.as_ref()
+    }
+
+    pub(crate) fn into_session(self) -> Option<Session> {
+        self.session.map(RefCell::into_inner)
+    }
+
+    // Takes the tokens that have been read so far out of this context
+    pub(crate) fn take_tokens(&self) -> TokenRecord {
+        self.tokens.take()
+    }
+
+    /*** Lexing ***/
+
+    // The parse functions read tokens through these rather than through lex_4_25, so that each
+    // token is recorded
+
+    pub(crate) fn next_token(&self, input: &Vec<char>, current_ptr: &mut usize) -> lex_4_25::Token {
+        self.next_token_with_span(input, current_ptr).0
+    }
+
+    pub(crate) fn next_token_with_span(&self, input: &Vec<char>, current_ptr: &mut usize) -> (lex_4_25::Token, Span) {
+        self.tokens.borrow_mut().next_token_with_span(input, current_ptr)
+    }
+
+    pub(crate) fn peek_token(&self, input: &Vec<char>, current_ptr: &mut usize) -> lex_4_25::Token {
+        self.next_token(input, &mut current_ptr.clone())
+    }
+
+    pub(crate) fn peek_token_with_span(&self, input: &Vec<char>, current_ptr: &mut usize) -> (lex_4_25::Token, Span) {
+        self.next_token_with_span(input, &mut current_ptr.clone())
+    }
+
+    /*** Declarations ***/
+
+    // Returns true if the input declares a contract, struct, enum, modifier, function or event
+    // called name
+    pub(crate) fn declares(&self, name: &str) -> bool {
+        self.declared.iter().any(|(_, declared)| declared == name)
+    }
+
+    // Returns true if the input declares a struct called name
+    pub(crate) fn declares_struct(&self, name: &str) -> bool {
+        self.declared.iter().any(|(keyword, declared)| *keyword == lex_4_25::Token::Struct && declared == name)
+    }
+
+    // Returns the names of the contracts, interfaces, libraries, structs and enums that the input
+    // declares
+    pub(crate) fn declared_types(&self) -> impl Iterator<Item = &str> {
+        self.declared.iter()
+            .filter(|(keyword, _)| matches!(
+                keyword,
+                lex_4_25::Token::Contract  |
+                lex_4_25::Token::Interface |
+                lex_4_25::Token::Library   |
+                lex_4_25::Token::Struct    |
+                lex_4_25::Token::Enum
+            ))
+            .map(|(_, name)| name.as_str())
+    }
+
+    // Returns how deeply the nonterminal that is currently being read is nested
+    pub(crate) fn depth(&self) -> usize {
+        self.depth.get()
+    }
+
+    // Returns true if the parse should stop after tree, because tree has an error and the
+    // error_recovery option is off
+    pub(crate) fn stops_after(&self, tree: &ParseTree) -> bool {
+        !self.options.error_recovery && tree.has_errors()
+    }
+}
+
+/**
+ * @dev A reusable parser. Each method reads a complete input string as a single nonterminal and
+ *      records anything left over after that nonterminal as an error.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Parser {
+    options: ParserOptions,
+}
+
+impl Parser {
+    pub fn new(options: ParserOptions) -> Parser {
+        Parser { options }
+    }
+
+    pub fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    // Parses a whole source file
+    pub fn parse_source_unit(&self, source: &str) -> ParseTree {
+        let input = lex_4_25::to_chars(source);
+        self.run(&input, |context| parse_4_25::parse_source_unit(&input, context))
+    }
+
+    // Parses a whole source file into a concrete syntax tree, which is built from the tokens that
+    // the parse read. If the retain_trivia option is set, the tree holds every character of the
+    // source.
+    pub fn parse_syntax_tree(&self, source: &str) -> SyntaxNode {
+        let input = lex_4_25::to_chars(source);
+        let context = Context::new(&self.options).with_declarations(&input);
+        let tree = self.finish(parse_4_25::parse_source_unit(&input, &context));
+        cst::build_with_tokens(&input, &tree, &context.take_tokens(), self.options.retain_trivia)
+    }
+
+    // Parses a whole source file and keeps what is needed to reparse it after an edit
+    pub fn parse_reusable(&self, source: &str) -> ParsedSource {
+        let input = source.chars().collect::<Vec<char>>();
+        incremental::parse_reusable(input, &self.options, None, |input, context| {
+            self.finish(parse_4_25::parse_source_unit(input, context))
+        })
+    }
+
+    // Applies edit to the source of previous and parses the result. The contract members and
+    // blocks of previous that the edit doesn't touch are reused rather than read again, and the
+    // tree is the same as the one that parse_source_unit returns for the edited source.
+    pub fn reparse(&self, previous: &ParsedSource, edit: &TextEdit) -> ParsedSource {
+        let input = edit.apply(previous.input());
+        incremental::parse_reusable(input, &self.options, Some((previous, edit)), |input, context| {
+            self.finish(parse_4_25::parse_source_unit(input, context))
+        })
+    }
+
+    // Parses a single contract part, such as a function or a state variable declaration
+    pub fn parse_contract_part(&self, source: &str) -> ParseTree {
+        self.run_fragment(source, parse_4_25::parse_contract_member)
+    }
+
+    // Parses a block of statements, including its braces
+    pub fn parse_block(&self, source: &str) -> ParseTree {
+        self.run_fragment(source, parse_4_25::parse_block)
+    }
+
+    pub fn parse_statement(&self, source: &str) -> ParseTree {
+        self.run_fragment(source, parse_4_25::parse_statement)
+    }
+
+    pub fn parse_expression(&self, source: &str) -> ParseTree {
+        self.run_fragment(source, parse_4_25::parse_expression)
+    }
+
+    pub fn parse_type_name(&self, source: &str) -> ParseTree {
+        self.run_fragment(source, parse_4_25::parse_type_name)
+    }
+
+    // Runs parse_fragment over the whole source and records an error if any input is left over
+    fn run_fragment(&self, source: &str, parse_fragment: fn(&Vec<char>, &mut usize, &Context) -> ParseTree) -> ParseTree {
+        let input = source.chars().collect::<Vec<char>>();
+        self.run(&input, |context| {
+            let current_ptr = &mut 0;
+            let mut tree = parse_fragment(&input, current_ptr, context);
+            if context.stops_after(&tree) {
+                return tree;
+            }
+            match lex_4_25::peek_token(&input, current_ptr) {
+                lex_4_25::Token::EOF => (),
+                actual => tree.leaves.push(Box::new(lex_4_25::Token::EOF.to_invalid_pair(actual).to_leaf()))
+            }
+            tree
+        })
+    }
+
+    // Runs parse with a new Context for this Parser's options and the names that input declares.
+    // Without error recovery, parsing stops after the first part of the input with an error, and
+    // that part is cut off at the error.
+    fn run<F: FnOnce(&Context) -> ParseTree>(&self, input: &Vec<char>, parse: F) -> ParseTree {
+        self.finish(parse(&Context::new(&self.options).with_declarations(input)))
+    }
+
+    // Cuts tree off at its first error if the error_recovery option is off
+    fn finish(&self, mut tree: ParseTree) -> ParseTree {
+        if !self.options.error_recovery {
+            tree.truncate_at_first_error();
+        }
+        tree
+    }
+}
+
+/**
+ * @dev Counts one level of nesting for as long as it is alive. The parse functions that can be
+ *      nested inside of themselves hold one of these while they run, which stops a deeply nested
+ *      input from overflowing the stack.
+ */
+pub(crate) struct DepthGuard<'a> {
+    depth: &'a Cell<usize>,
+}
+
+impl<'a> DepthGuard<'a> {
+    // Enters one more level of nesting, or returns None if that would go past the max_depth option
+    pub(crate) fn enter(context: &'a Context) -> Option<DepthGuard<'a>> {
+        if context.depth.get() >= context.options.max_depth {
+            return None;
+        }
+        context.depth.set(context.depth.get() + 1);
+        Some(DepthGuard { depth: &context.depth })
+    }
+}
+
+impl<'a> Drop for DepthGuard<'a> {
+    fn drop(&mut self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+}
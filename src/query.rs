@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+use super::cst::SyntaxNode;
+use super::diagnostics::{ Diagnostic, Span };
+use super::lex_4_25;
+use super::navigation::NodeId;
+use super::parse_4_25::{ NonTerminal, ParseTree };
+use super::parser::Parser;
+
+// The identifier that each ellipsis of a pattern is replaced with before the pattern is parsed
+const ELLIPSIS: &str = "$__";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PatternKind {
+    Expression,
+    Statements,
+    ContractMember,
+}
+
+/**
+ * @dev A snippet of Solidity that is matched against the structure of parse trees, rather than
+ *      against their text. Metavariables like `$X` or `$VALUE` match any single node, and a
+ *      metavariable that appears more than once has to match equal nodes each time, so `$A == $A`
+ *      only matches comparisons of a value with itself. An ellipsis `...` matches any number of
+ *      arguments, parameters or statements, including none, or any expression where a single
+ *      expression is expected.
+ *      A pattern is read as an expression if it is one, as a run of statements if it is a list of
+ *      statements, and otherwise as a contract member. Statement patterns match statements that
+ *      follow one another in a block, so `$X.transfer($V); ... $B = 0;` finds transfers that are
+ *      followed by an assignment of zero later on in the same block.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    kind: PatternKind,
+    tree: ParseTree,
+}
+
+// The node that each metavariable is bound to, keyed by the metavariable's name
+pub type Bindings<'a> = BTreeMap<String, (NodeId, &'a ParseTree)>;
+
+/**
+ * @dev A place in a parse tree where a pattern matched. Statement patterns match a run of
+ *      statements in a block and every other pattern matches a single node.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match<'a> {
+    pub nodes: Vec<(NodeId, &'a ParseTree)>,
+    pub bindings: Bindings<'a>,
+}
+
+impl<'a> Match<'a> {
+    // Returns the characters of the input that the matched nodes cover. The syntax tree has to be
+    // built from the tree that was searched, with its trivia, for the span to line up with the
+    // input.
+    pub fn span(&self, syntax: &SyntaxNode) -> Option<Span> {
+        let first = syntax.node(&self.nodes.first()?.0)?.span();
+        let last = syntax.node(&self.nodes.last()?.0)?.span();
+        Some(Span::new(first.start, last.end))
+    }
+}
+
+impl Pattern {
+    // Parses a pattern, or returns an error if it isn't an expression, statements or a contract
+    // member
+    pub fn new(pattern: &str) -> Result<Pattern, Diagnostic> {
+        let source = replace_ellipses(pattern);
+        let parser = Parser::default();
+        let expression = parser.parse_expression(&source);
+        // An operand that couldn't be parsed is an Expression nonterminal without any leaves
+        if !expression.has_errors() && expression.root != NonTerminal::Expression {
+            return Ok(Pattern { kind: PatternKind::Expression, tree: expression });
+        }
+        let block = parser.parse_block(&format!("{{{}}}", source));
+        if !block.has_errors() && !block.leaves.is_empty() {
+            return Ok(Pattern { kind: PatternKind::Statements, tree: block });
+        }
+        let member = parser.parse_contract_part(&source);
+        if !member.has_errors() {
+            return Ok(Pattern { kind: PatternKind::ContractMember, tree: member });
+        }
+        let message = String::from("the pattern isn't an expression, a list of statements or a contract member");
+        Err(Diagnostic::error(message, Span::new(0, pattern.chars().count())))
+    }
+
+    pub fn kind(&self) -> PatternKind {
+        self.kind
+    }
+
+    pub fn tree(&self) -> &ParseTree {
+        &self.tree
+    }
+
+    // Returns every match of this pattern in tree, in the order that the matched nodes are
+    // visited by a preorder traversal
+    pub fn find_matches<'a>(&self, tree: &'a ParseTree) -> Vec<Match<'a>> {
+        let mut matches = vec![];
+        for (id, node) in tree.preorder() {
+            if self.kind == PatternKind::Statements {
+                if node.root != NonTerminal::Block {
+                    continue;
+                }
+                let statements = leaves_with_ids(&id, node);
+                for start in 0..statements.len() {
+                    let mut bindings = Bindings::new();
+                    match match_sequence(&self.tree.leaves, &statements[start..], &mut bindings, false) {
+                        Some(len) if len > 0 => {
+                            matches.push(Match { nodes: statements[start..start + len].to_vec(), bindings });
+                        }
+                        _ => ()
+                    }
+                }
+            } else {
+                let mut bindings = Bindings::new();
+                if match_node(&self.tree, &id, node, &mut bindings) {
+                    matches.push(Match { nodes: vec![(id, node)], bindings });
+                }
+            }
+        }
+        matches
+    }
+}
+
+/*** Matching ***/
+
+// Returns whether pattern matches target, binding the metavariables of pattern along the way
+fn match_node<'a>(pattern: &ParseTree, id: &NodeId, target: &'a ParseTree, bindings: &mut Bindings<'a>) -> bool {
+    match pattern_name(pattern) {
+        Some(ELLIPSIS) => return true,
+        Some(name) if is_metavariable(name) => {
+            // The operand of a call without arguments isn't anything that can be bound
+            if target.root == NonTerminal::Expression && target.leaves.is_empty() {
+                return false;
+            }
+            return match bindings.get(name) {
+                Some((_, bound)) => *bound == target,
+                None => {
+                    bindings.insert(String::from(name), (id.clone(), target));
+                    true
+                }
+            };
+        }
+        _ => ()
+    }
+    pattern.root == target.root && match_sequence(&pattern.leaves, &leaves_with_ids(id, target), bindings, true).is_some()
+}
+
+/**
+ * @dev Matches a sequence of pattern nodes against a sequence of target nodes. An ellipsis first
+ *      tries to match as few targets as it can, and the bindings of every attempt that fails are
+ *      undone.
+ * @param patterns The pattern nodes.
+ * @param targets The target nodes along with their ids.
+ * @param bindings The metavariables that have been bound so far.
+ * @param whole Whether the patterns have to match every target, or only some of the first ones.
+ * @return The number of targets that were matched, or None if the patterns don't match.
+ */
+fn match_sequence<'a>(
+    patterns: &[Box<ParseTree>],
+    targets: &[(NodeId, &'a ParseTree)],
+    bindings: &mut Bindings<'a>,
+    whole: bool
+) -> Option<usize> {
+    let (pattern, rest) = match patterns.split_first() {
+        Some(split) => split,
+        None => return if whole && !targets.is_empty() { None } else { Some(0) }
+    };
+    if pattern_name(pattern) == Some(ELLIPSIS) {
+        for skipped in 0..=targets.len() {
+            let saved = bindings.clone();
+            if let Some(len) = match_sequence(rest, &targets[skipped..], bindings, whole) {
+                return Some(skipped + len);
+            }
+            *bindings = saved;
+        }
+        return None;
+    }
+    let (id, target) = targets.first()?;
+    let saved = bindings.clone();
+    if match_node(pattern, id, target, bindings) {
+        if let Some(len) = match_sequence(rest, &targets[1..], bindings, whole) {
+            return Some(len + 1);
+        }
+    }
+    *bindings = saved;
+    None
+}
+
+// Returns the leaves of tree along with their ids
+fn leaves_with_ids<'a>(id: &NodeId, tree: &'a ParseTree) -> Vec<(NodeId, &'a ParseTree)> {
+    tree.leaves.iter().enumerate().map(|(index, leaf)| (id.child(index), leaf.as_ref())).collect()
+}
+
+// Returns the identifier that a pattern node consists of. A name where a type is expected is
+// parsed as a user defined type name, and a name on its own in a parameter list as a parameter of
+// that type, so those are looked through.
+fn pattern_name(tree: &ParseTree) -> Option<&str> {
+    match (&tree.root, tree.leaves.as_slice()) {
+        (NonTerminal::Identifier(name), []) => Some(name),
+        (NonTerminal::UserDefinedTypeName, [leaf]) |
+        (NonTerminal::Parameter, [leaf]) => pattern_name(leaf),
+        _ => None
+    }
+}
+
+// Returns true if name is a metavariable, which is a `$` followed by an uppercase letter and then
+// any uppercase letters, digits and underscores
+fn is_metavariable(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next() == Some('$') &&
+        chars.next().is_some_and(|first| first.is_ascii_uppercase()) &&
+        chars.all(|rest| rest.is_ascii_uppercase() || rest.is_ascii_digit() || rest == '_')
+}
+
+/*** Ellipses ***/
+
+/**
+ * @dev Replaces each ellipsis in a pattern with an identifier so that the pattern can be parsed.
+ *      An ellipsis that stands for statements is followed by a semicolon, so that it is parsed as
+ *      a statement of its own.
+ * @param source The text of the pattern.
+ * @return The text of the pattern with its ellipses replaced.
+ */
+fn replace_ellipses(source: &str) -> String {
+    let input = source.chars().collect::<Vec<char>>();
+    let mut tokens = vec![];
+    let current_ptr = &mut 0;
+    loop {
+        match lex_4_25::next_token_or_trivia(&input, current_ptr) {
+            (lex_4_25::Token::EOF, _) => break,
+            (token, _) if token.is_trivia() => (),
+            (token, span) => tokens.push((token, span))
+        }
+    }
+    let mut replaced = String::new();
+    let mut copied = 0;
+    let mut index = 0;
+    while index < tokens.len() {
+        let is_ellipsis = tokens.len() - index >= 3 && (0..3).all(|offset| {
+            let (token, span) = &tokens[index + offset];
+            *token == lex_4_25::Token::Dot && span.start == tokens[index].1.start + offset
+        });
+        if !is_ellipsis {
+            index += 1;
+            continue;
+        }
+        replaced.push_str(&Span::new(copied, tokens[index].1.start).text(&input));
+        replaced.push_str(ELLIPSIS);
+        let previous = index.checked_sub(1).map(|previous| &tokens[previous].0);
+        let next = tokens.get(index + 3).map_or(&lex_4_25::Token::EOF, |(token, _)| token);
+        let after_statement = match previous {
+            Some(lex_4_25::Token::OpenBrace) |
+            Some(lex_4_25::Token::CloseBrace) |
+            Some(lex_4_25::Token::Semicolon) => true,
+            // An ellipsis on its own is an expression
+            None => *next != lex_4_25::Token::EOF,
+            _ => false
+        };
+        if after_statement && starts_statement(next) {
+            replaced.push(';');
+        }
+        copied = tokens[index + 2].1.end;
+        index += 3;
+    }
+    replaced.push_str(&Span::new(copied, input.len()).text(&input));
+    replaced
+}
+
+// Returns true if token can't continue an expression, so that an ellipsis in front of it stands
+// for statements
+fn starts_statement(token: &lex_4_25::Token) -> bool {
+    match token {
+        lex_4_25::Token::EOF |
+        lex_4_25::Token::OpenBrace |
+        lex_4_25::Token::CloseBrace |
+        lex_4_25::Token::Identifier(..) => true,
+        keyword => keyword.to_source().starts_with(|first: char| first.is_ascii_alphabetic())
+    }
+}
@@ -0,0 +1,307 @@
+use super::codes::{ self, Code };
+use super::cst;
+use super::diagnostics::{ Diagnostic, Severity, Span, Suggestion };
+use super::lex_4_25;
+use super::navigation::NodeId;
+use super::parse_4_25::{ NonTerminal, ParseTree };
+use super::source_map::{ Encoding, SourceFile };
+
+// The ANSI escape codes of the styles that coloured output uses
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[1;32m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+
+// The number of columns that a tab is drawn as, so that underlines line up with the text above
+const TAB_WIDTH: usize = 4;
+
+/**
+ * @dev The settings that control how diagnostics are rendered.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RenderOptions {
+    // Whether the output is styled with ANSI escape codes for a terminal
+    pub color: bool,
+}
+
+/*** Collecting ***/
+
+/**
+ * @dev Returns the diagnostics for every problem in a parse tree, in the order that their spans
+ *      start in the input. Along with the Error leaves that the parser records, each Invalid or InvalidPair
+ *      leaf becomes an error that says what was expected and what was found in its place. Those
+ *      leaves don't record where they are, so their spans are found in the concrete syntax tree.
+ *      Only the first of the invalid leaves at any one position is reported, since the others
+ *      follow from it, and so are none of the leaves that expected the very token found there.
+ *      The parser's own errors for missing tokens are left out when they come right after an
+ *      invalid leaf in the same node, for the same reason.
+ *      A leaf whose position holds a token that the lexer couldn't read becomes an invalid token
+ *      error instead. Every diagnostic that this returns for an invalid leaf has a code.
+ * @param file The source file that tree was parsed from.
+ * @param tree The parse tree of the whole file.
+ * @return The diagnostics of the tree.
+ */
+pub fn diagnostics(file: &SourceFile, tree: &ParseTree) -> Vec<Diagnostic> {
+    let input = file.chars();
+    let syntax = cst::build(input, tree, true);
+    let tokens = syntax.tokens().into_iter().filter(|token| !token.is_trivia()).collect::<Vec<_>>();
+    let mut diagnostics = vec![];
+    let mut reported = None;
+    for (id, node) in tree.preorder() {
+        let expected = match &node.root {
+            // Like an invalid leaf, a missing token right after another invalid leaf follows from it
+            NonTerminal::Error(diagnostic) if is_expected_token(diagnostic) && follows_invalid(tree, &id) => continue,
+            NonTerminal::Error(diagnostic) => {
+                diagnostics.push(*diagnostic.clone());
+                continue;
+            }
+            NonTerminal::Invalid(expected) | NonTerminal::InvalidPair(expected, _) => expected,
+            _ => continue
+        };
+        let span = match syntax.node(&id) {
+            Some(syntax) => syntax.span(),
+            None => continue
+        };
+        // A node that holds leaves was only partly parsed, so its error is put where the token
+        // that stopped it was found, or otherwise at its end
+        let span = match (&node.root, node.leaves.is_empty()) {
+            (_, true) => span,
+            (NonTerminal::InvalidPair(_, actual), false) => tokens.iter()
+                .rev()
+                .find(|token| token.span().end <= span.end && NonTerminal::Token(token.kind().clone()) == **actual)
+                .map_or(Span::new(span.end, span.end), |token| token.span()),
+            _ => Span::new(span.end, span.end)
+        };
+        let found = match (&node.root, span.start < span.end) {
+            (NonTerminal::InvalidPair(..), true) => tokens.iter().find(|token| token.span() == span),
+            _ => tokens.iter().find(|token| token.span().start >= span.start)
+        };
+        // A token that the lexer couldn't read is reported on its own, since whatever the parser
+        // expected, the token itself is the problem
+        if let Some(token) = found.filter(|token| *token.kind() == lex_4_25::Token::Illegal) {
+            if reported != Some(token.span().start) {
+                reported = Some(token.span().start);
+                let message = format!("invalid token `{}`", token.text());
+                diagnostics.push(Diagnostic::error(message, token.span()).with_code(codes::INVALID_TOKEN));
+            }
+            continue;
+        }
+        let found = match found {
+            Some(token) => format!("`{}`", token.text()),
+            None => String::from("end of file")
+        };
+        // Finding what was expected only happens after an earlier error threw the parser off
+        // and the token was used up by some other nonterminal
+        let code = codes::expected_code(expected);
+        let expected = describe(expected);
+        if reported == Some(span.start) || found == expected {
+            continue;
+        }
+        reported = Some(span.start);
+        let message = format!("expected {}, found {}", expected, found);
+        diagnostics.push(Diagnostic::error(message, span).with_code(code));
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    diagnostics
+}
+
+// Whether a diagnostic is the parser's error for a token that it expected and didn't find
+fn is_expected_token(diagnostic: &Diagnostic) -> bool {
+    matches!(
+        diagnostic.code,
+        Some(codes::EXPECTED_SEMICOLON)         |
+        Some(codes::EXPECTED_CLOSING_DELIMITER) |
+        Some(codes::EXPECTED_OPENING_DELIMITER) |
+        Some(codes::EXPECTED_KEYWORD)           |
+        Some(codes::EXPECTED_VERSION)
+    )
+}
+
+// Whether the leaf just before the node with an id in its parent is an invalid leaf or a missing token
+fn follows_invalid(tree: &ParseTree, id: &NodeId) -> bool {
+    let index = match id.path().last() {
+        Some(index) if *index > 0 => *index - 1,
+        _ => return false
+    };
+    let previous = id.parent().and_then(|parent| tree.node(&parent.child(index)));
+    match previous.map(|previous| &previous.root) {
+        Some(NonTerminal::Invalid(_)) | Some(NonTerminal::InvalidPair(..)) => true,
+        Some(NonTerminal::Error(diagnostic)) => is_expected_token(diagnostic),
+        _ => false
+    }
+}
+
+// Describes a nonterminal the way that an error message names it, which is by its source text if
+// it has any, like `;`, and otherwise by its kind in lowercase words, like type name
+pub(crate) fn describe(nonterminal: &NonTerminal) -> String {
+    let text = match nonterminal {
+        NonTerminal::ContractDefinition(token) |
+        NonTerminal::StorageLocation(token)    |
+        NonTerminal::StateMutability(token)    |
+        NonTerminal::Token(token) => token.to_source(),
+        NonTerminal::EnumValue(text)      |
+        NonTerminal::BooleanLiteral(text) |
+        NonTerminal::NumberLiteral(text)  |
+        NonTerminal::HexLiteral(text)     |
+        NonTerminal::StringLiteral(text)  |
+        NonTerminal::Identifier(text) => text.clone(),
+        _ => String::new()
+    };
+    if !text.is_empty() {
+        return format!("`{}`", text);
+    }
+    let kind = match nonterminal {
+        NonTerminal::Token(lex_4_25::Token::EOF) => return String::from("end of file"),
+        NonTerminal::SourceUnit => return String::from("pragma, import or contract"),
+        NonTerminal::Token(token) => format!("{:?}", token),
+        nonterminal => format!("{:?}", nonterminal)
+    };
+    let mut words = String::new();
+    for c in kind.split('(').next().unwrap_or("").chars() {
+        if c.is_uppercase() && !words.is_empty() {
+            words.push(' ');
+        }
+        words.extend(c.to_lowercase());
+    }
+    words
+}
+
+/*** Rendering ***/
+
+/**
+ * @dev Renders a diagnostic the way that rustc does. The message is followed by the file name,
+ *      line and column of the span, the lines of the input that the span covers with the span
+ *      underlined, and then any notes, help and suggestions. A suggestion is shown as the line
+ *      that it fixes with the replacement applied.
+ * @param diagnostic The diagnostic to render.
+ * @param file The source file that the diagnostic's span refers to.
+ * @param options How the diagnostic is rendered.
+ * @return The rendered diagnostic, which ends with a newline.
+ */
+pub fn render(diagnostic: &Diagnostic, file: &SourceFile, options: &RenderOptions) -> String {
+    let style = Style { color: options.color };
+    let input = file.chars();
+    let position = file.line_column(diagnostic.span.start, Encoding::Utf32);
+    let (line, column) = (position.line + 1, position.column + 1);
+    let last_line = file.line(diagnostic.span.end.max(diagnostic.span.start + 1) - 1) + 1;
+    let suggestions = diagnostic.suggestions.iter()
+        .filter_map(|suggestion| fixed_line(file, suggestion).map(|fixed| (suggestion, fixed)))
+        .collect::<Vec<_>>();
+    let widest = suggestions.iter().map(|(_, (number, _, _))| *number).chain(Some(last_line)).max().unwrap_or(last_line);
+    let gutter = " ".repeat(widest.to_string().len());
+    let color = severity_color(&diagnostic.severity);
+    let mut text = format!("{}\n", render_message(&diagnostic.severity, diagnostic.code, &diagnostic.message, options));
+    text.push_str(&format!("{}{} {}:{}:{}\n", gutter, style.paint("-->", BLUE), file.path(), line, column));
+    text.push_str(&format!("{} {}\n", gutter, style.paint("|", BLUE)));
+    for number in line..=last_line {
+        let Span { start, end } = file.line_span(number - 1);
+        let source = expand_tabs(&input[start..end]);
+        text.push_str(&format!("{} {}", style.paint(&format!("{:>width$}", number, width = gutter.len()), BLUE), style.paint("|", BLUE)));
+        text.push_str(&format!("{}\n", if source.is_empty() { String::new() } else { format!(" {}", source) }));
+        // The part of the span on this line, which is drawn as a single caret if it is empty
+        let from = diagnostic.span.start.max(start).min(end);
+        let to = diagnostic.span.end.min(end).max(from + 1);
+        let indent = expand_tabs(&input[start..from]).chars().count();
+        let width = expand_tabs(&input[from..to.min(end)]).chars().count().max(1);
+        text.push_str(&format!("{} {} {}{}\n", gutter, style.paint("|", BLUE), " ".repeat(indent), style.paint(&"^".repeat(width), color)));
+    }
+    if !diagnostic.notes.is_empty() || diagnostic.help.is_some() {
+        text.push_str(&format!("{} {}\n", gutter, style.paint("|", BLUE)));
+    }
+    for note in &diagnostic.notes {
+        text.push_str(&format!("{} {} {} {}\n", gutter, style.paint("=", BLUE), style.paint("note:", GREEN), note));
+    }
+    if let Some(help) = &diagnostic.help {
+        text.push_str(&format!("{} {} {} {}\n", gutter, style.paint("=", BLUE), style.paint("help:", CYAN), help));
+    }
+    for (suggestion, (number, fixed, (from, to))) in &suggestions {
+        text.push_str(&format!("{}: {}\n", style.paint("help", CYAN), suggestion.message));
+        text.push_str(&format!("{} {}\n", gutter, style.paint("|", BLUE)));
+        text.push_str(&format!("{} {} {}\n", style.paint(&format!("{:>width$}", number, width = gutter.len()), BLUE), style.paint("|", BLUE), fixed));
+        let marker = "~".repeat((to - from).max(1));
+        text.push_str(&format!("{} {} {}{}\n", gutter, style.paint("|", BLUE), " ".repeat(*from), style.paint(&marker, CYAN)));
+    }
+    // Suggestions that span several lines can't be drawn as a single fixed line
+    for suggestion in diagnostic.suggestions.iter().filter(|suggestion| fixed_line(file, suggestion).is_none()) {
+        text.push_str(&format!("{} {} {} {}\n", gutter, style.paint("=", BLUE), style.paint("help:", CYAN), suggestion.message));
+    }
+    text
+}
+
+/**
+ * @dev Renders the first line of a diagnostic, which is its severity and code followed by its
+ *      message, like `error[SF0001]: expected `;``. This is also used on its own for messages
+ *      that aren't about a span, like a summary of how many errors were found.
+ * @param severity The severity of the message.
+ * @param code The code of the diagnostic, if it has one.
+ * @param message The message to render.
+ * @param options How the message is rendered.
+ * @return The rendered message, without a newline.
+ */
+pub fn render_message(severity: &Severity, code: Option<Code>, message: &str, options: &RenderOptions) -> String {
+    let style = Style { color: options.color };
+    let label = match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning"
+    };
+    let label = match code {
+        Some(code) => format!("{}[{}]:", label, code),
+        None => format!("{}:", label)
+    };
+    format!("{}{}", style.paint(&label, severity_color(severity)), style.paint(&format!(" {}", message), BOLD))
+}
+
+fn severity_color(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Error => RED,
+        Severity::Warning => YELLOW
+    }
+}
+
+// Applies the ANSI styles of coloured output, or leaves text as it is
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    fn paint(&self, text: &str, style: &str) -> String {
+        match self.color {
+            true => format!("{}{}{}", style, text, RESET),
+            false => String::from(text)
+        }
+    }
+}
+
+// Returns the number of the line that a suggestion changes, counted from one, the text of that
+// line once the suggestion is applied, and the columns of the replacement in it. Suggestions that
+// change more than one line have none.
+fn fixed_line(file: &SourceFile, suggestion: &Suggestion) -> Option<(usize, String, (usize, usize))> {
+    let input = file.chars();
+    let line = file.line(suggestion.span.start);
+    let Span { start, end } = file.line_span(line);
+    if suggestion.span.end > end || suggestion.replacement.contains('\n') {
+        return None;
+    }
+    let before = expand_tabs(&input[start..suggestion.span.start.min(end)]);
+    let replacement = expand_tabs(&suggestion.replacement.chars().collect::<Vec<char>>());
+    let after = expand_tabs(&input[suggestion.span.end.min(end)..end]);
+    let from = before.chars().count();
+    let to = from + replacement.chars().count();
+    Some((line + 1, format!("{}{}{}", before, replacement, after), (from, to)))
+}
+
+// Returns the text of chars with each tab replaced by spaces
+fn expand_tabs(chars: &[char]) -> String {
+    let mut text = String::new();
+    for c in chars {
+        match c {
+            '\t' => text.push_str(&" ".repeat(TAB_WIDTH)),
+            '\r' => (),
+            c => text.push(*c)
+        }
+    }
+    text
+}
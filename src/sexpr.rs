@@ -0,0 +1,435 @@
+use super::codes::Code;
+use super::diagnostics::{ Diagnostic, Severity, Span, Suggestion };
+use super::lex_4_25::{ self, Token };
+use super::parse_4_25::{ NonTerminal, ParseTree };
+
+// The widest that a line of printed text gets before a node's leaves are put on lines of their own
+const WIDTH: usize = 100;
+
+/*** Printing ***/
+
+impl ParseTree {
+    // Prints this tree as an S-expression. Each node is a list of its nonterminal's kind, the
+    // values that the nonterminal holds and then its leaves, like
+    // `(ContractDefinition Contract (Identifier Ownable) (ContractPart))`. Tokens are written as
+    // `(Token BitwiseXor)` or `(Token Version 0.4.25)`, and an invalid nonterminal holds the
+    // nonterminal that was expected, like `(Invalid (Token Semicolon))`. Values are quoted
+    // unless they consist of letters, digits, `_`, `$` and `.` alone. A node that fits on its
+    // line is printed on it, and the leaves of any other node are printed on lines of their
+    // own, indented by two spaces, so the text only depends on the tree and can be kept in
+    // golden files.
+    pub fn to_sexpr(&self) -> String {
+        let mut text = String::new();
+        print_tree(self, 0, &mut text);
+        text
+    }
+
+    // Reads a tree that was printed by `to_sexpr`, or returns an error that points at the part
+    // of the text that couldn't be read. Any whitespace may separate the parts of a node.
+    pub fn from_sexpr(text: &str) -> Result<ParseTree, Diagnostic> {
+        let mut reader = Reader::new(text)?;
+        let tree = reader.tree()?;
+        match reader.peek() {
+            None => Ok(tree),
+            Some((_, span)) => Err(Diagnostic::error(String::from("expected the end of the text after the tree"), span))
+        }
+    }
+}
+
+fn print_tree(tree: &ParseTree, indent: usize, text: &mut String) {
+    let line = single_line(tree);
+    if indent + line.chars().count() <= WIDTH || tree.leaves.is_empty() {
+        text.push_str(&line);
+        return;
+    }
+    text.push('(');
+    text.push_str(&head(&tree.root));
+    for leaf in &tree.leaves {
+        text.push('\n');
+        text.push_str(&" ".repeat(indent + 2));
+        print_tree(leaf, indent + 2, text);
+    }
+    text.push(')');
+}
+
+fn single_line(tree: &ParseTree) -> String {
+    let mut line = format!("({}", head(&tree.root));
+    for leaf in &tree.leaves {
+        line.push(' ');
+        line.push_str(&single_line(leaf));
+    }
+    line.push(')');
+    line
+}
+
+// Returns the kind of a nonterminal followed by the values that it holds
+fn head(nonterminal: &NonTerminal) -> String {
+    let mut parts = vec![nonterminal.kind_name().to_string()];
+    match nonterminal {
+        NonTerminal::ContractDefinition(token) |
+        NonTerminal::StorageLocation(token)    |
+        NonTerminal::StateMutability(token)    |
+        NonTerminal::Token(token) => parts.extend(token_atoms(token)),
+        NonTerminal::EnumValue(value)      |
+        NonTerminal::BooleanLiteral(value) |
+        NonTerminal::NumberLiteral(value)  |
+        NonTerminal::HexLiteral(value)     |
+        NonTerminal::StringLiteral(value)  |
+        NonTerminal::Identifier(value) => parts.push(atom(value)),
+        NonTerminal::Invalid(expected) => parts.push(format!("({})", head(expected))),
+        NonTerminal::InvalidPair(expected, actual) => {
+            parts.push(format!("({})", head(expected)));
+            parts.push(format!("({})", head(actual)));
+        }
+        NonTerminal::Error(diagnostic) => {
+            parts.push(format!("{:?}", diagnostic.severity));
+            parts.push(atom(&diagnostic.message));
+            parts.push(diagnostic.span.start.to_string());
+            parts.push(diagnostic.span.end.to_string());
+            if let Some(code) = diagnostic.code {
+                parts.push(format!("(code {})", code));
+            }
+            for note in &diagnostic.notes {
+                parts.push(format!("(note {})", atom(note)));
+            }
+            if let Some(help) = &diagnostic.help {
+                parts.push(format!("(help {})", atom(help)));
+            }
+            for suggestion in &diagnostic.suggestions {
+                let span = suggestion.span;
+                parts.push(format!("(suggestion {} {} {} {})", atom(&suggestion.message), span.start, span.end, atom(&suggestion.replacement)));
+            }
+        }
+        _ => ()
+    }
+    parts.join(" ")
+}
+
+// Returns the name of a token's variant from its debug text, without the values that it holds
+fn variant_name(debug: &str) -> String {
+    debug.split('(').next().unwrap_or("").to_string()
+}
+
+fn token_atoms(token: &Token) -> Vec<String> {
+    let mut atoms = vec![variant_name(&format!("{:?}", token))];
+    match token {
+        Token::DecimalNumber(value) |
+        Token::HexLiteral(value)    |
+        Token::HexNumber(value)     |
+        Token::Identifier(value)    |
+        Token::StringLiteral(value) |
+        Token::Version(value) => atoms.push(atom(value)),
+        Token::FixedMxN(m, n) | Token::UfixedMxN(m, n) => {
+            atoms.push(m.to_string());
+            atoms.push(n.to_string());
+        }
+        _ => ()
+    }
+    atoms
+}
+
+// Returns a value as it is printed, which is quoted unless it is a plain word or number
+fn atom(value: &str) -> String {
+    let plain = !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.');
+    if plain {
+        return String::from(value);
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c)
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/*** Reading ***/
+
+#[derive(Clone, Debug, PartialEq)]
+enum Item {
+    Open,
+    Close,
+    Atom(String),
+}
+
+// Reads the items of an S-expression in order
+struct Reader {
+    items: Vec<(Item, Span)>,
+    next: usize,
+    // The position of the end of the text, where errors about missing items point
+    end: usize,
+}
+
+impl Reader {
+    fn new(text: &str) -> Result<Reader, Diagnostic> {
+        let chars = text.chars().collect::<Vec<char>>();
+        let mut items = vec![];
+        let mut current = 0;
+        while current < chars.len() {
+            let start = current;
+            match chars[current] {
+                c if c.is_whitespace() => current += 1,
+                '(' => {
+                    current += 1;
+                    items.push((Item::Open, Span::new(start, current)));
+                }
+                ')' => {
+                    current += 1;
+                    items.push((Item::Close, Span::new(start, current)));
+                }
+                '"' => {
+                    current += 1;
+                    let mut value = String::new();
+                    loop {
+                        match chars.get(current) {
+                            None => return Err(Diagnostic::error(String::from("unterminated string"), Span::new(start, current))),
+                            Some('"') => break,
+                            Some('\\') => {
+                                let escaped = match chars.get(current + 1) {
+                                    Some('n') => '\n',
+                                    Some('t') => '\t',
+                                    Some(c @ '"') | Some(c @ '\\') => *c,
+                                    _ => {
+                                        let span = Span::new(current, (current + 2).min(chars.len()));
+                                        return Err(Diagnostic::error(String::from("unknown escape sequence"), span));
+                                    }
+                                };
+                                value.push(escaped);
+                                current += 2;
+                            }
+                            Some(c) => {
+                                value.push(*c);
+                                current += 1;
+                            }
+                        }
+                    }
+                    current += 1;
+                    items.push((Item::Atom(value), Span::new(start, current)));
+                }
+                _ => {
+                    while current < chars.len() && !chars[current].is_whitespace() && !"()\"".contains(chars[current]) {
+                        current += 1;
+                    }
+                    items.push((Item::Atom(chars[start..current].iter().collect()), Span::new(start, current)));
+                }
+            }
+        }
+        Ok(Reader { items, next: 0, end: chars.len() })
+    }
+
+    fn peek(&self) -> Option<(Item, Span)> {
+        self.items.get(self.next).cloned()
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, Diagnostic> {
+        let span = self.peek().map_or(Span::new(self.end, self.end), |(_, span)| span);
+        Err(Diagnostic::error(String::from(message), span))
+    }
+
+    fn expect(&mut self, item: Item, message: &str) -> Result<(), Diagnostic> {
+        match self.peek() {
+            Some((next, _)) if next == item => {
+                self.next += 1;
+                Ok(())
+            }
+            _ => self.error(message)
+        }
+    }
+
+    fn atom(&mut self) -> Result<String, Diagnostic> {
+        match self.peek() {
+            Some((Item::Atom(value), _)) => {
+                self.next += 1;
+                Ok(value)
+            }
+            _ => self.error("expected a value")
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, Diagnostic> {
+        match self.peek() {
+            Some((Item::Atom(value), _)) if value.parse::<usize>().is_ok() => {
+                self.next += 1;
+                Ok(value.parse::<usize>().unwrap_or(0))
+            }
+            _ => self.error("expected a number")
+        }
+    }
+
+    // Returns true if the next items open a list that begins with name
+    fn at_list(&self, name: &str) -> bool {
+        self.peek().map(|(item, _)| item) == Some(Item::Open) &&
+            self.items.get(self.next + 1).map(|(item, _)| item) == Some(&Item::Atom(String::from(name)))
+    }
+
+    // Reads a tree, which is a nonterminal followed by its leaves, all in parentheses
+    fn tree(&mut self) -> Result<ParseTree, Diagnostic> {
+        self.expect(Item::Open, "expected `(`")?;
+        let root = self.nonterminal()?;
+        let mut leaves = vec![];
+        while self.peek().map(|(item, _)| item) == Some(Item::Open) {
+            leaves.push(Box::new(self.tree()?));
+        }
+        self.expect(Item::Close, "expected `(` or `)`")?;
+        Ok(ParseTree { root, leaves })
+    }
+
+    // Reads a nonterminal that is held by another one, which is written in parentheses without
+    // any leaves
+    fn nested(&mut self) -> Result<Box<NonTerminal>, Diagnostic> {
+        self.expect(Item::Open, "expected `(`")?;
+        let nonterminal = self.nonterminal()?;
+        self.expect(Item::Close, "expected `)`")?;
+        Ok(Box::new(nonterminal))
+    }
+
+    fn nonterminal(&mut self) -> Result<NonTerminal, Diagnostic> {
+        let span = self.peek().map_or(Span::new(self.end, self.end), |(_, span)| span);
+        let kind = self.atom()?;
+        match kind.as_str() {
+            "ContractDefinition" => Ok(NonTerminal::ContractDefinition(self.token()?)),
+            "StorageLocation" => Ok(NonTerminal::StorageLocation(self.token()?)),
+            "StateMutability" => Ok(NonTerminal::StateMutability(self.token()?)),
+            "Token" => Ok(NonTerminal::Token(self.token()?)),
+            "EnumValue" => Ok(NonTerminal::EnumValue(self.atom()?)),
+            "BooleanLiteral" => Ok(NonTerminal::BooleanLiteral(self.atom()?)),
+            "NumberLiteral" => Ok(NonTerminal::NumberLiteral(self.atom()?)),
+            "HexLiteral" => Ok(NonTerminal::HexLiteral(self.atom()?)),
+            "StringLiteral" => Ok(NonTerminal::StringLiteral(self.atom()?)),
+            "Identifier" => Ok(NonTerminal::Identifier(self.atom()?)),
+            "Invalid" => Ok(NonTerminal::Invalid(self.nested()?)),
+            "InvalidPair" => Ok(NonTerminal::InvalidPair(self.nested()?, self.nested()?)),
+            "Error" => Ok(NonTerminal::Error(Box::new(self.diagnostic()?))),
+            name => match unit_nonterminal(name) {
+                Some(nonterminal) => Ok(nonterminal),
+                None => Err(Diagnostic::error(format!("`{}` isn't a kind of nonterminal", name), span))
+            }
+        }
+    }
+
+    fn token(&mut self) -> Result<Token, Diagnostic> {
+        let span = self.peek().map_or(Span::new(self.end, self.end), |(_, span)| span);
+        let name = self.atom()?;
+        match name.as_str() {
+            "DecimalNumber" => Ok(lex_4_25::to_decimal_number(&self.atom()?)),
+            "HexLiteral" => Ok(lex_4_25::to_hex_literal(&self.atom()?)),
+            "HexNumber" => Ok(lex_4_25::to_hex_number(&self.atom()?)),
+            "Identifier" => Ok(lex_4_25::to_identifier(&self.atom()?)),
+            "StringLiteral" => Ok(lex_4_25::to_string_literal(&self.atom()?)),
+            "Version" => Ok(Token::Version(self.atom()?)),
+            "FixedMxN" => Ok(Token::FixedMxN(self.number()?, self.number()?)),
+            "UfixedMxN" => Ok(Token::UfixedMxN(self.number()?, self.number()?)),
+            name => match unit_token(name) {
+                Some(token) => Ok(token),
+                None => Err(Diagnostic::error(format!("`{}` isn't a kind of token", name), span))
+            }
+        }
+    }
+
+    fn diagnostic(&mut self) -> Result<Diagnostic, Diagnostic> {
+        let severity = match self.atom()?.as_str() {
+            "Error" => Severity::Error,
+            "Warning" => Severity::Warning,
+            _ => {
+                self.next -= 1;
+                return self.error("expected `Error` or `Warning`");
+            }
+        };
+        let message = self.atom()?;
+        let span = Span::new(self.number()?, self.number()?);
+        let mut diagnostic = Diagnostic { severity, ..Diagnostic::error(message, span) };
+        if self.at_list("code") {
+            self.next += 2;
+            match Code::parse(&self.atom()?) {
+                Some(code) => diagnostic.code = Some(code),
+                None => {
+                    self.next -= 1;
+                    return self.error("expected an error code like `SF0001`");
+                }
+            }
+            self.expect(Item::Close, "expected `)`")?;
+        }
+        while self.at_list("note") || self.at_list("help") || self.at_list("suggestion") {
+            self.next += 1;
+            match self.atom()?.as_str() {
+                "note" => diagnostic.notes.push(self.atom()?),
+                "help" => diagnostic.help = Some(self.atom()?),
+                _ => {
+                    let message = self.atom()?;
+                    let span = Span::new(self.number()?, self.number()?);
+                    diagnostic.suggestions.push(Suggestion { message, span, replacement: self.atom()? });
+                }
+            }
+            self.expect(Item::Close, "expected `)`")?;
+        }
+        Ok(diagnostic)
+    }
+}
+
+/*** Names ***/
+
+// Expands to a match that returns the variant of an enum whose name is the given string, for
+// variants that don't hold any values
+macro_rules! variant_by_name {
+    ($enum:ident, $name:expr, $($variant:ident),* $(,)?) => {
+        match $name {
+            $(stringify!($variant) => Some($enum::$variant),)*
+            _ => None
+        }
+    }
+}
+
+fn unit_nonterminal(name: &str) -> Option<NonTerminal> {
+    variant_by_name!(NonTerminal, name,
+        SourceUnit, PragmaDirective, ImportDirective, ContractPart, InheritanceList,
+        InheritanceSpecifier, StateVariableDeclaration, UsingForDeclaration, StructDefinition,
+        ModifierDefinition, ModifierInvocation, FunctionDefinition, ConstructorDefinition,
+        FallbackFunctionDefinition, EventDefinition, EnumValueList, EnumDefinition,
+        ParameterList, Parameter, EventParameterList, EventParameter, FunctionTypeParameterList,
+        FunctionTypeParameter, VariableDeclaration, TypeName, UserDefinedTypeName, Mapping,
+        ArrayTypeName, FunctionTypeName, Block, Statement, ExpressionStatement, IfStatement,
+        WhileStatement, PlaceholderStatement, SimpleStatement, ForStatement,
+        InlineAssemblyStatement, DoWhileStatement, Continue, Break, Return, Throw,
+        EmitStatement, VariableDefinition, IdentifierList, Expression, PrimaryExpression,
+        ExpressionList, NameValueList, FunctionCall, FunctionCallArguments, NewExpression,
+        MemberAccess, IndexAccess, NumberUnit, HexNumber, DecimalNumber, TupleExpression,
+        InlineArrayExpression, ElementaryTypeNameExpression, ElementaryTypeName, Int, Uint,
+        Byte, Fixed, Ufixed, InlineAssemblyBlock, AssemblyItem, AssemblyLocalBinding,
+        AssemblyAssignment, AssemblyLabel, FunctionalAssemblyExpression, Empty
+    )
+}
+
+fn unit_token(name: &str) -> Option<Token> {
+    variant_by_name!(Token, name,
+        Abstract, Address, After, AndEquals, Anonymous, Arrow, As, Assembly, Assignment,
+        ASMAssign, BitwiseAnd, BitwiseOr, BitwiseXor, Bool, Break, Byte, Bytes, Bytes1, Bytes2,
+        Bytes3, Bytes4, Bytes5, Bytes6, Bytes7, Bytes8, Bytes9, Bytes10, Bytes11, Bytes12,
+        Bytes13, Bytes14, Bytes15, Bytes16, Bytes17, Bytes18, Bytes19, Bytes20, Bytes21,
+        Bytes22, Bytes23, Bytes24, Bytes25, Bytes26, Bytes27, Bytes28, Bytes29, Bytes30,
+        Bytes31, Bytes32, Calldata, Case, Catch, CloseBrace, CloseBracket, CloseParenthesis,
+        Colon, Comma, CommentMulti, CommentSingle, Constant, Constructor, Continue, Contract,
+        Days, Decrement, Default, Delete, Divide, DivideEquals, Do, Dot, Else, Emit, Enum, EOF,
+        Equals, Ether, Event, EventParameter, Exclamation, External, False, Final, Finney,
+        Fixed, For, From, Function, GreaterThan, GreaterThanOrEquals, Hex, Hours, If, Illegal,
+        Import, In, Increment, Indexed, Inline, Int, Int8, Int16, Int24, Int32, Int40, Int48,
+        Int56, Int64, Int72, Int80, Int88, Int96, Int104, Int112, Int120, Int128, Int136,
+        Int144, Int152, Int160, Int168, Int176, Int184, Int192, Int200, Int208, Int216, Int224,
+        Int232, Int240, Int248, Int256, Interface, Internal, Is, LessThan, LessThanOrEquals,
+        Let, Library, LogicalAnd, LogicalOr, Mapping, Match, Memory, Minus, MinusEquals,
+        Minutes, ModEquals, Modifier, Modulus, Multiply, MultiplyEquals, New, NoMatch,
+        NotEquals, Null, Of, OpenBrace, OpenBracket, OpenParenthesis, OrEquals, Parameter,
+        Payable, Plus, PlusEquals, Power, Pragma, Private, Public, Pure, Question, Relocatable,
+        Return, Returns, Seconds, Semicolon, ShiftLeft, ShiftLeftEquals, ShiftRight,
+        ShiftRightEquals, StateVariable, Static, Storage, String, Struct, Switch, Szabo, Throw,
+        Tilda, True, Try, Type, Typeof, Ufixed, Uint, Uint8, Uint16, Uint24, Uint32, Uint40,
+        Uint48, Uint56, Uint64, Uint72, Uint80, Uint88, Uint96, Uint104, Uint112, Uint120,
+        Uint128, Uint136, Uint144, Uint152, Uint160, Uint168, Uint176, Uint184, Uint192,
+        Uint200, Uint208, Uint216, Uint224, Uint232, Uint240, Uint248, Uint256,
+        UserDefinedTypeName, Using, Var, View, Weeks, Wei, While, Whitespace, XorEquals, Years
+    )
+}
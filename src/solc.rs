@@ -0,0 +1,1650 @@
+use std::collections::HashMap;
+use serde_json::{ json, Map, Value };
+use super::cst::{ self, SyntaxNode };
+use super::diagnostics::{ Diagnostic, Span };
+use super::lex_4_25;
+use super::parse_4_25::{ NonTerminal, ParseTree };
+use super::source_map::{ SourceFile, SourceMap };
+
+/*** Export ***/
+
+/**
+ * @dev Converts the parse tree of a source file into the compact AST JSON that solc 0.4.25 prints
+ *      with `--ast-compact-json`. Node ids are assigned in the order that solc assigns them, so a
+ *      node's leaves get lower ids than the node itself. The `src` of each node holds byte
+ *      offsets into the source, like solc's.
+ *      solfix doesn't type check its input, so every `typeDescriptions` holds nulls, and names
+ *      are only resolved to the contracts, members and parameters that are declared in the same
+ *      source. The other `referencedDeclaration`s, including those of global names like `msg`,
+ *      are null. Nonterminals that couldn't be parsed are left out of the AST, so callers that
+ *      need a faithful AST should check the tree for errors first.
+ * @param tree The parse tree of the whole source.
+ * @param file The source file that tree was parsed from. Its path is recorded as the source's
+ *        `absolutePath` and its id is the last part of each `src`.
+ * @return The SourceUnit node of the AST.
+ */
+pub fn export_compact_ast(tree: &ParseTree, file: &SourceFile) -> Value {
+    let syntax = cst::build(file.chars(), tree, true);
+    let mut exporter = Exporter {
+        file,
+        next_id: 1,
+        contract_name: String::new(),
+        in_modifier: false,
+        return_parameters: None,
+    };
+    let mut unit = exporter.source_unit(tree, &syntax, file.path());
+    resolve(&mut unit);
+    unit
+}
+
+struct Exporter<'f> {
+    // The source file, which converts spans to the byte offsets of a src
+    file: &'f SourceFile,
+    next_id: u64,
+    // The name of the contract that is being exported
+    contract_name: String,
+    in_modifier: bool,
+    // The id of the return parameters of the function that is being exported
+    return_parameters: Option<u64>,
+}
+
+// Returns the leaves of tree that aren't errors, along with their syntax nodes
+fn leaves<'t>(tree: &'t ParseTree, syntax: &SyntaxNode) -> Vec<(&'t ParseTree, SyntaxNode)> {
+    tree.leaves.iter()
+        .zip(syntax.children())
+        .filter(|(leaf, _)| !leaf.is_error())
+        .map(|(leaf, node)| (leaf.as_ref(), node))
+        .collect()
+}
+
+// The typeDescriptions of every expression and type name, since solfix doesn't know any types
+fn no_type() -> Value {
+    json!({ "typeIdentifier": null, "typeString": null })
+}
+
+// Returns the text of a token that holds a keyword, such as a visibility or a storage location
+fn keyword(token: &lex_4_25::Token) -> String {
+    match token {
+        // `constant` functions are `view` functions
+        lex_4_25::Token::Constant => String::from("view"),
+        other => other.to_source()
+    }
+}
+
+impl Exporter<'_> {
+    // Returns the src of the characters covered by span
+    fn src(&self, span: Span) -> String {
+        let start = self.file.byte_offset(span.start);
+        let end = self.file.byte_offset(span.end);
+        format!("{}:{}:{}", start, end - start, self.file.id())
+    }
+
+    // Returns the span of a node without the semicolon or comma that closes it, as solc leaves
+    // those out
+    fn span(&self, syntax: &SyntaxNode) -> Span {
+        let tokens = syntax.tokens().into_iter().filter(|token| !token.is_trivia()).collect::<Vec<_>>();
+        let mut end = tokens.len();
+        while end > 1 {
+            match tokens[end - 1].kind() {
+                lex_4_25::Token::Semicolon | lex_4_25::Token::Comma => end -= 1,
+                _ => break
+            }
+        }
+        match (tokens.first(), tokens.get(end.max(1) - 1)) {
+            (Some(first), Some(last)) => Span::new(first.span().start, last.span().end),
+            _ => Span::new(syntax.span().start, syntax.span().start)
+        }
+    }
+
+    // Completes a node with its id, type and src. Ids are handed out once a node's leaves have
+    // been exported.
+    fn node(&mut self, node_type: &str, span: Span, fields: Value) -> Value {
+        let mut node = match fields {
+            Value::Object(fields) => fields,
+            _ => Map::new()
+        };
+        node.insert(String::from("id"), json!(self.next_id));
+        node.insert(String::from("nodeType"), json!(node_type));
+        node.insert(String::from("src"), json!(self.src(span)));
+        self.next_id += 1;
+        Value::Object(node)
+    }
+
+    /*** Source Units ***/
+
+    fn source_unit(&mut self, tree: &ParseTree, syntax: &SyntaxNode, path: &str) -> Value {
+        let mut nodes = vec![];
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::PragmaDirective => nodes.push(self.pragma_directive(leaf, &node)),
+                NonTerminal::ContractDefinition(..) => nodes.push(self.contract_definition(leaf, &node)),
+                _ => ()
+            }
+        }
+        let fields = json!({ "absolutePath": path, "exportedSymbols": {}, "nodes": nodes });
+        self.node("SourceUnit", syntax.span(), fields)
+    }
+
+    fn pragma_directive(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut literals = vec![];
+        for leaf in &tree.leaves {
+            match &leaf.root {
+                NonTerminal::Identifier(name) => literals.push(name.clone()),
+                // solc reads a version like `0.4.25` as the numbers `0.4` and `.25`
+                NonTerminal::Token(lex_4_25::Token::Version(version)) => {
+                    let parts = version.split('.').collect::<Vec<&str>>();
+                    match parts.len() {
+                        3 => {
+                            literals.push(format!("{}.{}", parts[0], parts[1]));
+                            literals.push(format!(".{}", parts[2]));
+                        }
+                        _ => literals.push(version.clone())
+                    }
+                }
+                NonTerminal::Token(token) => literals.push(token.to_source()),
+                _ => ()
+            }
+        }
+        // Unlike other nodes, a pragma's src covers its semicolon
+        let end = syntax.tokens().into_iter().filter(|token| !token.is_trivia()).last().map_or(0, |token| token.span().end);
+        let span = Span::new(self.span(syntax).start, end);
+        self.node("PragmaDirective", span, json!({ "literals": literals }))
+    }
+
+    /*** Contracts ***/
+
+    fn contract_definition(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let kind = match &tree.root {
+            NonTerminal::ContractDefinition(token) => token.to_source(),
+            _ => String::from("contract")
+        };
+        self.contract_name = tree.contract_name().unwrap_or("").to_string();
+        let mut base_contracts = vec![];
+        let mut nodes = vec![];
+        for (leaf, node) in leaves(tree, syntax) {
+            match leaf.root {
+                NonTerminal::InheritanceList => {
+                    for (specifier, node) in leaves(leaf, &node) {
+                        base_contracts.push(self.inheritance_specifier(specifier, &node));
+                    }
+                }
+                NonTerminal::ContractPart => {
+                    for (member, node) in leaves(leaf, &node) {
+                        if let Some(member) = self.contract_member(member, &node) {
+                            nodes.push(member);
+                        }
+                    }
+                }
+                _ => ()
+            }
+        }
+        let fields = json!({
+            "baseContracts": base_contracts,
+            "contractDependencies": [],
+            "contractKind": kind,
+            "documentation": null,
+            "fullyImplemented": !tree.is_abstract(),
+            "linearizedBaseContracts": [],
+            "name": self.contract_name,
+            "nodes": nodes,
+            "scope": null,
+        });
+        let span = self.span(syntax);
+        self.node("ContractDefinition", span, fields)
+    }
+
+    fn inheritance_specifier(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut base_name = Value::Null;
+        let mut arguments = Value::Null;
+        for (leaf, node) in leaves(tree, syntax) {
+            match leaf.root {
+                NonTerminal::UserDefinedTypeName => base_name = self.type_name(leaf, &node),
+                NonTerminal::ExpressionList => arguments = json!(self.expression_list(leaf, &node)),
+                _ => ()
+            }
+        }
+        let span = self.span(syntax);
+        self.node("InheritanceSpecifier", span, json!({ "arguments": arguments, "baseName": base_name }))
+    }
+
+    fn contract_member(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Option<Value> {
+        match tree.root {
+            NonTerminal::StateVariableDeclaration => Some(self.state_variable_declaration(tree, syntax)),
+            NonTerminal::StructDefinition => Some(self.struct_definition(tree, syntax)),
+            NonTerminal::EnumDefinition => Some(self.enum_definition(tree, syntax)),
+            NonTerminal::UsingForDeclaration => Some(self.using_for_directive(tree, syntax)),
+            NonTerminal::EventDefinition => Some(self.event_definition(tree, syntax)),
+            NonTerminal::ModifierDefinition => Some(self.modifier_definition(tree, syntax)),
+            NonTerminal::FunctionDefinition |
+            NonTerminal::ConstructorDefinition |
+            NonTerminal::FallbackFunctionDefinition => Some(self.function_definition(tree, syntax)),
+            _ => None
+        }
+    }
+
+    fn state_variable_declaration(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut type_name = Value::Null;
+        let mut name = String::new();
+        let mut visibility = String::from("internal");
+        let mut constant = false;
+        let mut value = Value::Null;
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::Identifier(identifier) => name = identifier.clone(),
+                NonTerminal::Token(lex_4_25::Token::Constant) => constant = true,
+                NonTerminal::Token(lex_4_25::Token::Assignment) => {
+                    if let Some((expression, node)) = leaves(leaf, &node).first() {
+                        value = self.expression(expression, node).unwrap_or(Value::Null);
+                    }
+                }
+                NonTerminal::Token(token @ lex_4_25::Token::Public)   |
+                NonTerminal::Token(token @ lex_4_25::Token::Private)  |
+                NonTerminal::Token(token @ lex_4_25::Token::Internal) => visibility = keyword(token),
+                _ if type_name.is_null() => type_name = self.type_name(leaf, &node),
+                _ => ()
+            }
+        }
+        let fields = json!({
+            "constant": constant,
+            "name": name,
+            "scope": null,
+            "stateVariable": true,
+            "storageLocation": "default",
+            "typeDescriptions": no_type(),
+            "typeName": type_name,
+            "value": value,
+            "visibility": visibility,
+        });
+        let span = self.span(syntax);
+        self.node("VariableDeclaration", span, fields)
+    }
+
+    fn struct_definition(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut name = String::new();
+        let mut members = vec![];
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::Identifier(identifier) => name = identifier.clone(),
+                NonTerminal::VariableDeclaration => members.push(self.variable_declaration(leaf, &node)),
+                _ => ()
+            }
+        }
+        let fields = json!({
+            "canonicalName": format!("{}.{}", self.contract_name, name),
+            "members": members,
+            "name": name,
+            "scope": null,
+            "visibility": "public",
+        });
+        let span = self.span(syntax);
+        self.node("StructDefinition", span, fields)
+    }
+
+    fn enum_definition(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut name = String::new();
+        let mut members = vec![];
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::Identifier(identifier) => name = identifier.clone(),
+                NonTerminal::EnumValueList => {
+                    for (value, node) in leaves(leaf, &node) {
+                        if let NonTerminal::EnumValue(value) = &value.root {
+                            let span = self.span(&node);
+                            members.push(self.node("EnumValue", span, json!({ "name": value })));
+                        }
+                    }
+                }
+                _ => ()
+            }
+        }
+        let fields = json!({
+            "canonicalName": format!("{}.{}", self.contract_name, name),
+            "members": members,
+            "name": name,
+        });
+        let span = self.span(syntax);
+        self.node("EnumDefinition", span, fields)
+    }
+
+    fn using_for_directive(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut library_name = Value::Null;
+        let mut type_name = Value::Null;
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::Identifier(name) if library_name.is_null() => {
+                    let span = self.span(&node);
+                    let fields = json!({
+                        "contractScope": null,
+                        "name": name,
+                        "referencedDeclaration": null,
+                        "typeDescriptions": no_type(),
+                    });
+                    library_name = self.node("UserDefinedTypeName", span, fields);
+                }
+                // `using L for *` applies to every type
+                NonTerminal::Token(lex_4_25::Token::Multiply) => (),
+                _ => type_name = self.type_name(leaf, &node)
+            }
+        }
+        let span = self.span(syntax);
+        self.node("UsingForDirective", span, json!({ "libraryName": library_name, "typeName": type_name }))
+    }
+
+    fn event_definition(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut name = String::new();
+        let mut anonymous = false;
+        let mut parameters = Value::Null;
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::Identifier(identifier) => name = identifier.clone(),
+                NonTerminal::Token(lex_4_25::Token::Anonymous) => anonymous = true,
+                NonTerminal::EventParameterList => parameters = self.parameter_list(leaf, &node),
+                _ => ()
+            }
+        }
+        if parameters.is_null() {
+            let end = self.span(syntax).end;
+            parameters = self.empty_parameter_list(end);
+        }
+        let fields = json!({ "anonymous": anonymous, "documentation": null, "name": name, "parameters": parameters });
+        let span = self.span(syntax);
+        self.node("EventDefinition", span, fields)
+    }
+
+    fn modifier_definition(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut name = String::new();
+        let mut parameters = Value::Null;
+        let mut body = Value::Null;
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::Identifier(identifier) => name = identifier.clone(),
+                NonTerminal::ParameterList => parameters = self.parameter_list(leaf, &node),
+                NonTerminal::Block => {
+                    if parameters.is_null() {
+                        parameters = self.empty_parameter_list(self.span(&node).start);
+                    }
+                    self.in_modifier = true;
+                    body = self.block(leaf, &node);
+                    self.in_modifier = false;
+                }
+                _ => ()
+            }
+        }
+        let fields = json!({
+            "body": body,
+            "documentation": null,
+            "name": name,
+            "parameters": parameters,
+            "visibility": "internal",
+        });
+        let span = self.span(syntax);
+        self.node("ModifierDefinition", span, fields)
+    }
+
+    fn function_definition(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let name = tree.function_name().unwrap_or("").to_string();
+        let mut parameters = Value::Null;
+        let mut return_parameters = Value::Null;
+        let mut modifiers = vec![];
+        let mut visibility = String::from("public");
+        let mut state_mutability = String::from("nonpayable");
+        let mut is_declared_const = false;
+        let mut body = Value::Null;
+        let mut returns = false;
+        let mut end_of_header = self.span(syntax).start;
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::ParameterList if returns => return_parameters = self.parameter_list(leaf, &node),
+                NonTerminal::ParameterList => parameters = self.parameter_list(leaf, &node),
+                NonTerminal::ModifierInvocation => modifiers.push(self.modifier_invocation(leaf, &node)),
+                NonTerminal::StateMutability(token) => {
+                    is_declared_const = *token == lex_4_25::Token::Constant;
+                    state_mutability = keyword(token);
+                }
+                NonTerminal::Token(lex_4_25::Token::Returns) => returns = true,
+                NonTerminal::Token(token @ lex_4_25::Token::Public)   |
+                NonTerminal::Token(token @ lex_4_25::Token::Private)  |
+                NonTerminal::Token(token @ lex_4_25::Token::Internal) |
+                NonTerminal::Token(token @ lex_4_25::Token::External) => visibility = keyword(token),
+                NonTerminal::Block => {
+                    if return_parameters.is_null() {
+                        return_parameters = self.empty_parameter_list(end_of_header);
+                    }
+                    self.return_parameters = return_parameters["id"].as_u64();
+                    body = self.block(leaf, &node);
+                    self.return_parameters = None;
+                }
+                _ => ()
+            }
+            if leaf.root != NonTerminal::Block {
+                end_of_header = self.span(&node).end;
+            }
+        }
+        if return_parameters.is_null() {
+            return_parameters = self.empty_parameter_list(end_of_header);
+        }
+        // Before 0.4.22, constructors were functions with the same name as their contract
+        let is_constructor = tree.root == NonTerminal::ConstructorDefinition || name == self.contract_name;
+        let fields = json!({
+            "body": body,
+            "documentation": null,
+            "implemented": tree.is_implemented(),
+            "isConstructor": is_constructor,
+            "isDeclaredConst": is_declared_const,
+            "modifiers": modifiers,
+            "name": name,
+            "parameters": parameters,
+            "payable": state_mutability == "payable",
+            "returnParameters": return_parameters,
+            "scope": null,
+            "stateMutability": state_mutability,
+            "superFunction": null,
+            "visibility": visibility,
+        });
+        let span = self.span(syntax);
+        self.node("FunctionDefinition", span, fields)
+    }
+
+    fn modifier_invocation(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut modifier_name = Value::Null;
+        let mut arguments = Value::Null;
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::Identifier(..) => modifier_name = self.expression(leaf, &node).unwrap_or(Value::Null),
+                NonTerminal::ExpressionList => arguments = json!(self.expression_list(leaf, &node)),
+                _ => ()
+            }
+        }
+        let span = self.span(syntax);
+        self.node("ModifierInvocation", span, json!({ "arguments": arguments, "modifierName": modifier_name }))
+    }
+
+    /*** Parameters ***/
+
+    fn parameter_list(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut parameters = vec![];
+        for (leaf, node) in leaves(tree, syntax) {
+            parameters.push(self.variable_declaration(leaf, &node));
+        }
+        let span = self.span(syntax);
+        self.node("ParameterList", span, json!({ "parameters": parameters }))
+    }
+
+    // solc records missing parameter lists as empty ones where the list would have been
+    fn empty_parameter_list(&mut self, position: usize) -> Value {
+        self.node("ParameterList", Span::new(position, position), json!({ "parameters": [] }))
+    }
+
+    // Exports a parameter, an event parameter or a struct member
+    fn variable_declaration(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut type_name = Value::Null;
+        let mut name = String::new();
+        let mut storage_location = String::from("default");
+        let mut indexed = false;
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::Identifier(identifier) => name = identifier.clone(),
+                NonTerminal::StorageLocation(token) => storage_location = keyword(token),
+                NonTerminal::Token(lex_4_25::Token::Indexed) => indexed = true,
+                _ if type_name.is_null() => type_name = self.type_name(leaf, &node),
+                _ => ()
+            }
+        }
+        let mut fields = json!({
+            "constant": false,
+            "name": name,
+            "scope": null,
+            "stateVariable": false,
+            "storageLocation": storage_location,
+            "typeDescriptions": no_type(),
+            "typeName": type_name,
+            "value": null,
+            "visibility": "internal",
+        });
+        if tree.root == NonTerminal::EventParameter {
+            fields["indexed"] = json!(indexed);
+        }
+        let span = self.span(syntax);
+        self.node("VariableDeclaration", span, fields)
+    }
+
+    /*** Types ***/
+
+    fn type_name(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let span = self.span(syntax);
+        match &tree.root {
+            NonTerminal::Token(token) if token.is_elementary_type() => {
+                let fields = json!({ "name": token.to_source(), "typeDescriptions": no_type() });
+                self.node("ElementaryTypeName", span, fields)
+            }
+            NonTerminal::UserDefinedTypeName => {
+                let name = tree.leaves.iter().filter_map(|leaf| match &leaf.root {
+                    NonTerminal::Identifier(name) => Some(name.clone()),
+                    _ => None
+                }).collect::<Vec<String>>().join(".");
+                let fields = json!({
+                    "contractScope": null,
+                    "name": name,
+                    "referencedDeclaration": null,
+                    "typeDescriptions": no_type(),
+                });
+                self.node("UserDefinedTypeName", span, fields)
+            }
+            NonTerminal::Mapping => {
+                let types = leaves(tree, syntax).into_iter()
+                    .filter(|(leaf, _)| leaf.root != NonTerminal::Token(lex_4_25::Token::Arrow))
+                    .map(|(leaf, node)| self.type_name(leaf, &node))
+                    .collect::<Vec<Value>>();
+                let fields = json!({
+                    "keyType": types.first(),
+                    "typeDescriptions": no_type(),
+                    "valueType": types.get(1),
+                });
+                self.node("Mapping", span, fields)
+            }
+            NonTerminal::ArrayTypeName => {
+                let parts = leaves(tree, syntax);
+                let base_type = match parts.first() {
+                    Some((leaf, node)) => self.type_name(leaf, node),
+                    None => Value::Null
+                };
+                let length = match parts.get(1) {
+                    Some((leaf, node)) => self.expression(leaf, node).unwrap_or(Value::Null),
+                    None => Value::Null
+                };
+                let fields = json!({ "baseType": base_type, "length": length, "typeDescriptions": no_type() });
+                self.node("ArrayTypeName", span, fields)
+            }
+            NonTerminal::FunctionTypeName => self.function_type_name(tree, syntax),
+            _ => Value::Null
+        }
+    }
+
+    fn function_type_name(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut parameter_types = Value::Null;
+        let mut return_parameter_types = Value::Null;
+        let mut visibility = String::from("internal");
+        let mut state_mutability = String::from("nonpayable");
+        for (leaf, node) in leaves(tree, syntax) {
+            match &leaf.root {
+                NonTerminal::FunctionTypeParameterList => parameter_types = self.parameter_list(leaf, &node),
+                NonTerminal::StateMutability(token) => state_mutability = keyword(token),
+                // The return types of a function type are a leaf of its `returns`
+                NonTerminal::Token(lex_4_25::Token::Returns) => {
+                    if let Some((list, node)) = leaves(leaf, &node).first() {
+                        return_parameter_types = self.parameter_list(list, node);
+                    }
+                }
+                NonTerminal::Token(token @ lex_4_25::Token::Internal) |
+                NonTerminal::Token(token @ lex_4_25::Token::External) => visibility = keyword(token),
+                _ => ()
+            }
+        }
+        let end = self.span(syntax).end;
+        if parameter_types.is_null() {
+            parameter_types = self.empty_parameter_list(end);
+        }
+        if return_parameter_types.is_null() {
+            return_parameter_types = self.empty_parameter_list(end);
+        }
+        let fields = json!({
+            "parameterTypes": parameter_types,
+            "returnParameterTypes": return_parameter_types,
+            "stateMutability": state_mutability,
+            "typeDescriptions": no_type(),
+            "visibility": visibility,
+        });
+        let span = self.span(syntax);
+        self.node("FunctionTypeName", span, fields)
+    }
+
+    /*** Statements ***/
+
+    fn block(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Value {
+        let mut statements = vec![];
+        for (leaf, node) in leaves(tree, syntax) {
+            if let Some(statement) = self.statement(leaf, &node) {
+                statements.push(statement);
+            }
+        }
+        let span = self.span(syntax);
+        self.node("Block", span, json!({ "statements": statements }))
+    }
+
+    fn statement(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Option<Value> {
+        let span = self.span(syntax);
+        match &tree.root {
+            NonTerminal::Block => Some(self.block(tree, syntax)),
+            NonTerminal::EmitStatement => {
+                let (call, node) = leaves(tree, syntax).into_iter().next()?;
+                let event_call = self.expression(call, &node)?;
+                Some(self.node("EmitStatement", span, json!({ "eventCall": event_call })))
+            }
+            NonTerminal::Return => {
+                let expression = match leaves(tree, syntax).first() {
+                    Some((leaf, node)) => self.expression(leaf, node),
+                    None => None
+                };
+                let fields = json!({ "expression": expression, "functionReturnParameters": self.return_parameters });
+                Some(self.node("Return", span, fields))
+            }
+            NonTerminal::Identifier(name) if name == "_" && self.in_modifier => {
+                Some(self.node("PlaceholderStatement", span, json!({})))
+            }
+            _ => {
+                let expression = self.expression(tree, syntax)?;
+                Some(self.node("ExpressionStatement", span, json!({ "expression": expression })))
+            }
+        }
+    }
+
+    /*** Expressions ***/
+
+    // Returns the arguments in an expression list, leaving out the placeholder of an empty list
+    fn expression_list(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Vec<Value> {
+        leaves(tree, syntax).into_iter().filter_map(|(leaf, node)| self.expression(leaf, &node)).collect()
+    }
+
+    // Exports an expression, or returns None if it couldn't be parsed
+    fn expression(&mut self, tree: &ParseTree, syntax: &SyntaxNode) -> Option<Value> {
+        let span = self.span(syntax);
+        let parts = leaves(tree, syntax);
+        let mut operands = vec![];
+        for (leaf, node) in &parts {
+            match leaf.root {
+                NonTerminal::FunctionCallArguments | NonTerminal::Empty => (),
+                _ => operands.push(self.expression(leaf, node))
+            }
+        }
+        let fields = match &tree.root {
+            NonTerminal::Identifier(name) => {
+                let fields = json!({
+                    "argumentTypes": null,
+                    "name": name,
+                    "overloadedDeclarations": [],
+                    "referencedDeclaration": null,
+                    "typeDescriptions": no_type(),
+                });
+                return Some(self.node("Identifier", span, fields));
+            }
+            NonTerminal::MemberAccess => {
+                let member_name = match parts.get(1).map(|(leaf, _)| &leaf.root) {
+                    Some(NonTerminal::Identifier(name)) => name.clone(),
+                    _ => return None
+                };
+                // The member name is part of this node rather than an identifier of its own
+                self.next_id -= 1;
+                json!({
+                    "argumentTypes": null,
+                    "expression": operands.first()?.clone()?,
+                    "memberName": member_name,
+                    "referencedDeclaration": null,
+                    "typeDescriptions": no_type(),
+                })
+            }
+            NonTerminal::IndexAccess => json!({
+                "argumentTypes": null,
+                "baseExpression": operands.first()?.clone()?,
+                "indexExpression": operands.get(1).cloned().flatten(),
+                "typeDescriptions": no_type(),
+            }),
+            NonTerminal::FunctionCall => return self.function_call(tree, syntax, operands.first()?.clone()?),
+            NonTerminal::NewExpression => {
+                let (type_name, node) = parts.first()?;
+                // The type name was exported as an expression above, which is thrown away
+                let type_name = self.type_name(type_name, node);
+                json!({ "argumentTypes": null, "typeName": type_name, "typeDescriptions": no_type() })
+            }
+            NonTerminal::ElementaryTypeNameExpression => {
+                let type_name = match parts.first().map(|(leaf, _)| &leaf.root) {
+                    Some(NonTerminal::Token(token)) => token.to_source(),
+                    _ => return None
+                };
+                json!({ "argumentTypes": null, "typeName": type_name, "typeDescriptions": no_type() })
+            }
+            NonTerminal::TupleExpression | NonTerminal::InlineArrayExpression => {
+                let mut components = vec![];
+                let mut exported = operands.into_iter();
+                for (leaf, _) in &parts {
+                    components.push(match leaf.root {
+                        NonTerminal::Empty => None,
+                        _ => exported.next().flatten()
+                    });
+                }
+                json!({
+                    "argumentTypes": null,
+                    "components": components,
+                    "isInlineArray": tree.root == NonTerminal::InlineArrayExpression,
+                    "typeDescriptions": no_type(),
+                })
+            }
+            NonTerminal::Token(token) => return self.operation(token, tree, syntax, span, operands),
+            _ => return None
+        };
+        let node_type = match tree.root {
+            NonTerminal::MemberAccess => "MemberAccess",
+            NonTerminal::IndexAccess => "IndexAccess",
+            NonTerminal::NewExpression => "NewExpression",
+            NonTerminal::ElementaryTypeNameExpression => "ElementaryTypeNameExpression",
+            _ => "TupleExpression"
+        };
+        Some(self.node(node_type, span, fields))
+    }
+
+    fn function_call(&mut self, tree: &ParseTree, syntax: &SyntaxNode, expression: Value) -> Option<Value> {
+        let mut arguments = vec![];
+        let mut names = vec![];
+        for (leaf, node) in leaves(tree, syntax) {
+            if leaf.root != NonTerminal::FunctionCallArguments {
+                continue;
+            }
+            for (list, node) in leaves(leaf, &node) {
+                match list.root {
+                    NonTerminal::ExpressionList => arguments = self.expression_list(list, &node),
+                    NonTerminal::NameValueList => {
+                        for (pair, node) in leaves(list, &node) {
+                            let pair = leaves(pair, &node);
+                            if let (Some((name, _)), Some((value, node))) = (pair.first(), pair.get(1)) {
+                                if let NonTerminal::Identifier(name) = &name.root {
+                                    names.push(name.clone());
+                                    arguments.extend(self.expression(value, node));
+                                }
+                            }
+                        }
+                    }
+                    _ => ()
+                }
+            }
+        }
+        let kind = match expression["nodeType"].as_str() {
+            Some("ElementaryTypeNameExpression") => "typeConversion",
+            _ => "functionCall"
+        };
+        let fields = json!({
+            "argumentTypes": null,
+            "arguments": arguments,
+            "expression": expression,
+            "kind": kind,
+            "names": names,
+            "typeDescriptions": no_type(),
+        });
+        let span = self.span(syntax);
+        Some(self.node("FunctionCall", span, fields))
+    }
+
+    // Exports a literal or an operation, which the parse tree records as a token with operands
+    fn operation(
+        &mut self,
+        token: &lex_4_25::Token,
+        tree: &ParseTree,
+        syntax: &SyntaxNode,
+        span: Span,
+        operands: Vec<Option<Value>>
+    ) -> Option<Value> {
+        let literal = |kind: &str, value: String, subdenomination: Option<String>| json!({
+            "argumentTypes": null,
+            "hexValue": value.bytes().map(|byte| format!("{:02x}", byte)).collect::<String>(),
+            "kind": kind,
+            "subdenomination": subdenomination,
+            "typeDescriptions": no_type(),
+            "value": value,
+        });
+        let (node_type, fields) = match (token, operands.len()) {
+            (lex_4_25::Token::DecimalNumber(value), _) | (lex_4_25::Token::HexNumber(value), _) => {
+                let unit = tree.leaves.iter().find_map(|leaf| match &leaf.root {
+                    NonTerminal::Token(unit) if unit.is_number_unit() => Some(unit.to_source()),
+                    _ => None
+                });
+                ("Literal", literal("number", value.clone(), unit))
+            }
+            (lex_4_25::Token::StringLiteral(value), _) => {
+                let contents = value.chars().skip(1).take(value.chars().count().saturating_sub(2)).collect();
+                ("Literal", literal("string", contents, None))
+            }
+            (lex_4_25::Token::True, _) => ("Literal", literal("bool", String::from("true"), None)),
+            (lex_4_25::Token::False, _) => ("Literal", literal("bool", String::from("false"), None)),
+            (lex_4_25::Token::Question, 3) => ("Conditional", json!({
+                "argumentTypes": null,
+                "condition": operands[0].clone()?,
+                "falseExpression": operands[2].clone()?,
+                "trueExpression": operands[1].clone()?,
+                "typeDescriptions": no_type(),
+            })),
+            (operator, 2) if is_assignment(operator) => ("Assignment", json!({
+                "argumentTypes": null,
+                "leftHandSide": operands[0].clone()?,
+                "operator": operator.to_source(),
+                "rightHandSide": operands[1].clone()?,
+                "typeDescriptions": no_type(),
+            })),
+            (operator, 2) => ("BinaryOperation", json!({
+                "argumentTypes": null,
+                "commonType": no_type(),
+                "leftExpression": operands[0].clone()?,
+                "operator": operator.to_source(),
+                "rightExpression": operands[1].clone()?,
+                "typeDescriptions": no_type(),
+            })),
+            (operator, 1) => {
+                // Increments and decrements come after their operand unless they are prefixes
+                let prefix = syntax.tokens().into_iter()
+                    .find(|child| !child.is_trivia())
+                    .is_some_and(|first| first.kind() == operator);
+                ("UnaryOperation", json!({
+                    "argumentTypes": null,
+                    "operator": operator.to_source(),
+                    "prefix": prefix,
+                    "subExpression": operands[0].clone()?,
+                    "typeDescriptions": no_type(),
+                }))
+            }
+            _ => return None
+        };
+        Some(self.node(node_type, span, fields))
+    }
+}
+
+fn is_assignment(token: &lex_4_25::Token) -> bool {
+    matches!(
+        token,
+        lex_4_25::Token::Assignment |
+        lex_4_25::Token::OrEquals |
+        lex_4_25::Token::XorEquals |
+        lex_4_25::Token::AndEquals |
+        lex_4_25::Token::ShiftLeftEquals |
+        lex_4_25::Token::ShiftRightEquals |
+        lex_4_25::Token::PlusEquals |
+        lex_4_25::Token::MinusEquals |
+        lex_4_25::Token::ModEquals |
+        lex_4_25::Token::MultiplyEquals |
+        lex_4_25::Token::DivideEquals
+    )
+}
+
+/*** Name Resolution ***/
+
+// The names that are declared in a scope, along with the ids of their declarations
+type Scope = HashMap<String, Value>;
+
+// The contracts of a source unit, by id
+struct Contract {
+    name: String,
+    bases: Vec<String>,
+    members: Scope,
+}
+
+/**
+ * @dev Fills in the fields of an exported source unit that refer to other nodes: the scope of each
+ *      declaration, the declarations that names refer to, the linearized base contracts and the
+ *      exported symbols.
+ * @param unit The SourceUnit node.
+ */
+fn resolve(unit: &mut Value) {
+    let mut contracts = vec![];
+    for node in unit["nodes"].as_array().into_iter().flatten() {
+        if node["nodeType"] != "ContractDefinition" {
+            continue;
+        }
+        let bases = node["baseContracts"].as_array().into_iter().flatten()
+            .filter_map(|base| base["baseName"]["name"].as_str().map(String::from))
+            .collect();
+        let mut members = Scope::new();
+        for member in node["nodes"].as_array().into_iter().flatten() {
+            if let Some(name) = member["name"].as_str().filter(|name| !name.is_empty()) {
+                members.insert(String::from(name), member["id"].clone());
+            }
+        }
+        contracts.push((node["id"].clone(), Contract { name: node["name"].as_str().unwrap_or("").to_string(), bases, members }));
+    }
+    let mut symbols = Map::new();
+    let mut file_scope = Scope::new();
+    for (id, contract) in &contracts {
+        symbols.insert(contract.name.clone(), json!([id]));
+        file_scope.insert(contract.name.clone(), id.clone());
+    }
+    unit["exportedSymbols"] = Value::Object(symbols);
+    let id = unit["id"].clone();
+    if let Some(nodes) = unit["nodes"].as_array_mut() {
+        for node in nodes {
+            if node["nodeType"] != "ContractDefinition" {
+                continue;
+            }
+            let linearized = linearize(&node["name"], &contracts, &mut vec![]).unwrap_or_else(|| vec![node["id"].clone()]);
+            // Members of the most derived contracts hide those of their bases
+            let mut contract_scope = Scope::new();
+            for base in linearized.iter().rev() {
+                if let Some((_, contract)) = contracts.iter().find(|(id, _)| id == base) {
+                    contract_scope.extend(contract.members.clone());
+                }
+            }
+            let mut dependencies = linearized[1..].to_vec();
+            dependencies.sort_by_key(|id| id.as_u64());
+            node["linearizedBaseContracts"] = json!(linearized);
+            node["contractDependencies"] = json!(dependencies);
+            node["scope"] = id.clone();
+            // Base contracts are looked up in the source unit, where members can't hide them
+            let contract_id = node["id"].clone();
+            resolve_node(&mut node["baseContracts"], &contract_id, &mut vec![file_scope.clone()], false);
+            let mut scopes = vec![file_scope.clone(), contract_scope];
+            resolve_node(node, &contract_id, &mut scopes, true);
+        }
+    }
+}
+
+/**
+ * @dev Returns the C3 linearization of a contract, most derived first, as solc orders
+ *      `linearizedBaseContracts`. Bases that aren't declared in the source are left out.
+ * @param name The name of the contract.
+ * @param contracts The contracts of the source unit.
+ * @param visiting The contracts whose linearization is being computed, which stops cycles.
+ * @return The ids of the contract and its bases, or None if there is no consistent order.
+ */
+fn linearize(name: &Value, contracts: &[(Value, Contract)], visiting: &mut Vec<String>) -> Option<Vec<Value>> {
+    let name = name.as_str()?;
+    let (id, contract) = contracts.iter().find(|(_, contract)| contract.name == name)?;
+    if visiting.iter().any(|visited| visited == name) {
+        return None;
+    }
+    visiting.push(String::from(name));
+    // The last base is the most derived one, so it comes first
+    let mut sequences = vec![];
+    for base in contract.bases.iter().rev() {
+        if let Some(linearized) = linearize(&json!(base), contracts, visiting) {
+            sequences.push(linearized);
+        }
+    }
+    sequences.push(contract.bases.iter().rev().filter_map(|base| {
+        contracts.iter().find(|(_, contract)| contract.name == *base).map(|(id, _)| id.clone())
+    }).collect());
+    visiting.pop();
+    let mut linearized = vec![id.clone()];
+    loop {
+        sequences.retain(|sequence| !sequence.is_empty());
+        if sequences.is_empty() {
+            return Some(linearized);
+        }
+        // The next contract is the first head that isn't in the tail of any sequence
+        let head = sequences.iter().map(|sequence| sequence[0].clone()).find(|head| {
+            sequences.iter().all(|sequence| !sequence[1..].contains(head))
+        })?;
+        for sequence in sequences.iter_mut() {
+            sequence.retain(|id| *id != head);
+        }
+        linearized.push(head);
+    }
+}
+
+// Sets the scope of the declarations under node and the declarations that its names refer to
+fn resolve_node(node: &mut Value, scope: &Value, scopes: &mut Vec<Scope>, is_root: bool) {
+    let node_type = node["nodeType"].as_str().unwrap_or("").to_string();
+    if !is_root && node.get("scope").is_some() {
+        node["scope"] = scope.clone();
+    }
+    match node_type.as_str() {
+        "Identifier" | "UserDefinedTypeName" => {
+            if let Some(name) = node["name"].as_str() {
+                let declaration = scopes.iter().rev().find_map(|scope| scope.get(name)).cloned();
+                node["referencedDeclaration"] = declaration.unwrap_or(Value::Null);
+            }
+        }
+        _ => ()
+    }
+    let opens_scope = matches!(
+        node_type.as_str(),
+        "ContractDefinition" |
+        "FunctionDefinition" |
+        "ModifierDefinition" |
+        "StructDefinition" |
+        "EventDefinition" |
+        "FunctionTypeName"
+    );
+    let inner = if opens_scope { node["id"].clone() } else { scope.clone() };
+    if node_type == "FunctionDefinition" || node_type == "ModifierDefinition" {
+        let mut parameters = Scope::new();
+        for list in ["parameters", "returnParameters"] {
+            for parameter in node[list]["parameters"].as_array().into_iter().flatten() {
+                if let Some(name) = parameter["name"].as_str().filter(|name| !name.is_empty()) {
+                    parameters.insert(String::from(name), parameter["id"].clone());
+                }
+            }
+        }
+        scopes.push(parameters);
+    }
+    match node {
+        Value::Object(fields) => {
+            for (key, value) in fields.iter_mut() {
+                if key != "scope" && !(is_root && key == "baseContracts") {
+                    resolve_node(value, &inner, scopes, false);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values.iter_mut() {
+                resolve_node(value, scope, scopes, false);
+            }
+        }
+        _ => ()
+    }
+    if node_type == "FunctionDefinition" || node_type == "ModifierDefinition" {
+        scopes.pop();
+    }
+}
+
+/*** Import ***/
+
+/**
+ * @dev Converts a solc AST into a parse tree with the shape that solfix's parser gives the same
+ *      source. Both the compact AST of `--ast-compact-json` and the legacy AST of `--ast-json` and
+ *      `--combined-json ast` are accepted. Nodes that solc's AST records but the parser doesn't
+ *      support yet, like `if` statements, are imported as the nonterminals of the same name.
+ *      The AST doesn't record which keywords were written out, so a function's visibility is
+ *      always added and a state variable's only if it isn't `internal`, the way most code is
+ *      written. Nodes that can't be imported become Error nonterminals at the node's `src`, which
+ *      is converted to characters if the source is given. Without it, the error is put at the
+ *      start of the input and a note gives the byte offsets of the `src`.
+ * @param ast The SourceUnit node of the AST.
+ * @param file The source that the AST was built from, if it is available.
+ * @return The parse tree of the source unit.
+ */
+pub fn import_ast(ast: &Value, file: Option<&SourceFile>) -> ParseTree {
+    // Legacy nodes are objects with a `name`, `attributes` and `children` in place of fields
+    let mut tree = match ast.get("nodeType").is_none() && ast.get("name").is_some() {
+        true => import_node(&from_legacy(ast)),
+        false => import_node(ast)
+    };
+    locate_errors(&mut tree, file);
+    tree
+}
+
+/**
+ * @dev Converts the ASTs in the output of `solc --combined-json ast` or `solc --standard-json`
+ *      into parse trees.
+ * @param output The JSON that solc printed.
+ * @param files The sources that the ASTs were built from, found by their paths. Sources that
+ *        aren't in the map are imported without them.
+ * @return The path and parse tree of each source, in the order of solc's `sourceList` if it has
+ *         one.
+ */
+pub fn import_sources(output: &Value, files: &SourceMap) -> Vec<(String, ParseTree)> {
+    let sources = match output["sources"].as_object() {
+        Some(sources) => sources,
+        None => return vec![]
+    };
+    let mut paths = output["sourceList"].as_array().into_iter().flatten()
+        .filter_map(|path| path.as_str().map(String::from))
+        .filter(|path| sources.contains_key(path))
+        .collect::<Vec<String>>();
+    if paths.is_empty() {
+        paths = sources.keys().cloned().collect();
+    }
+    paths.into_iter().filter_map(|path| {
+        let source = &sources[&path];
+        let ast = ["AST", "ast", "legacyAST"].iter().map(|key| &source[*key]).find(|ast| !ast.is_null())?;
+        let file = files.find(&path).map(|id| files.file(id));
+        Some((path, import_ast(ast, file)))
+    }).collect()
+}
+
+fn tree(root: NonTerminal, leaves: Vec<ParseTree>) -> ParseTree {
+    ParseTree { root, leaves: leaves.into_iter().map(Box::new).collect() }
+}
+
+fn text<'a>(node: &'a Value, field: &str) -> &'a str {
+    node[field].as_str().unwrap_or("")
+}
+
+// Returns an Error nonterminal for a node that can't be imported. Its span is the byte offsets of
+// the node's src until locate_errors converts it.
+fn import_error(node: &Value, message: String) -> ParseTree {
+    let mut parts = text(node, "src").split(':').map(|part| part.parse::<usize>().unwrap_or(0));
+    let start = parts.next().unwrap_or(0);
+    let span = Span::new(start, start + parts.next().unwrap_or(0));
+    NonTerminal::Error(Box::new(Diagnostic::error(message, span))).to_leaf()
+}
+
+// Converts the byte offsets of the import errors in tree to characters of file. Without the file,
+// or if they don't fall on its characters, the error is put at the start of the input and a note
+// gives its byte offsets instead.
+fn locate_errors(tree: &mut ParseTree, file: Option<&SourceFile>) {
+    let mut stack = vec![tree];
+    while let Some(tree) = stack.pop() {
+        if let NonTerminal::Error(diagnostic) = &mut tree.root {
+            let bytes = diagnostic.span;
+            let span = file.and_then(|file| Some(Span::new(file.char_offset(bytes.start)?, file.char_offset(bytes.end)?)));
+            diagnostic.span = span.unwrap_or_default();
+            if span.is_none() {
+                diagnostic.notes.push(format!("the node is at bytes {} to {} of its source", bytes.start, bytes.end));
+            }
+        }
+        stack.extend(tree.leaves.iter_mut().map(|leaf| &mut **leaf));
+    }
+}
+
+// Returns the token that a keyword or operator from the AST is lexed as
+fn lex(source: &str) -> Option<lex_4_25::Token> {
+    let input = lex_4_25::to_chars(source);
+    let current_ptr = &mut 0;
+    let token = lex_4_25::next_token(&input, current_ptr);
+    if *current_ptr == input.len() && token != lex_4_25::Token::EOF {
+        Some(token)
+    } else {
+        None
+    }
+}
+
+fn lex_leaf(node: &Value, source: &str) -> ParseTree {
+    match lex(source) {
+        Some(token) => NonTerminal::Token(token).to_leaf(),
+        None => import_error(node, format!("`{}` isn't a Solidity keyword or operator", source))
+    }
+}
+
+fn identifier(name: &str) -> ParseTree {
+    NonTerminal::Identifier(String::from(name)).to_leaf()
+}
+
+// Imports a field that has to hold a node
+fn required(node: &Value, field: &str) -> ParseTree {
+    if node[field].is_null() {
+        return import_error(node, format!("the `{}` of a {} node is missing", field, text(node, "nodeType")));
+    }
+    import_node(&node[field])
+}
+
+fn import_all(node: &Value, field: &str) -> Vec<ParseTree> {
+    node[field].as_array().into_iter().flatten().map(import_node).collect()
+}
+
+fn import_node(node: &Value) -> ParseTree {
+    let node_type = text(node, "nodeType");
+    match node_type {
+        "SourceUnit" => tree(NonTerminal::SourceUnit, import_all(node, "nodes")),
+        "PragmaDirective" => import_pragma_directive(node),
+        "ImportDirective" => {
+            let mut leaves = vec![NonTerminal::Token(lex_4_25::to_string_literal(&quote(text(node, "file")))).to_leaf()];
+            if !text(node, "unitAlias").is_empty() {
+                leaves.push(identifier(text(node, "unitAlias")));
+            }
+            tree(NonTerminal::ImportDirective, leaves)
+        }
+        "ContractDefinition" => import_contract_definition(node),
+        "InheritanceSpecifier" => {
+            let mut leaves = vec![required(node, "baseName")];
+            leaves.extend(import_arguments(node));
+            tree(NonTerminal::InheritanceSpecifier, leaves)
+        }
+        "UsingForDirective" => {
+            let type_name = match node["typeName"] {
+                // `using L for *` has no type name
+                Value::Null => NonTerminal::Token(lex_4_25::Token::Multiply).to_leaf(),
+                _ => required(node, "typeName")
+            };
+            tree(NonTerminal::UsingForDeclaration, vec![identifier(text(&node["libraryName"], "name")), type_name])
+        }
+        "StructDefinition" => {
+            let mut leaves = vec![identifier(text(node, "name"))];
+            for member in node["members"].as_array().into_iter().flatten() {
+                leaves.push(import_parameter(member, NonTerminal::VariableDeclaration));
+            }
+            tree(NonTerminal::StructDefinition, leaves)
+        }
+        "EnumDefinition" => {
+            let values = node["members"].as_array().into_iter().flatten()
+                .map(|value| NonTerminal::EnumValue(String::from(text(value, "name"))).to_leaf())
+                .collect();
+            tree(NonTerminal::EnumDefinition, vec![identifier(text(node, "name")), tree(NonTerminal::EnumValueList, values)])
+        }
+        "EventDefinition" => {
+            let parameters = import_parameter_list(&node["parameters"], NonTerminal::EventParameterList, NonTerminal::EventParameter);
+            let mut leaves = vec![identifier(text(node, "name")), parameters];
+            if node["anonymous"] == true {
+                leaves.push(NonTerminal::Token(lex_4_25::Token::Anonymous).to_leaf());
+            }
+            tree(NonTerminal::EventDefinition, leaves)
+        }
+        "ModifierDefinition" => {
+            let mut leaves = vec![identifier(text(node, "name"))];
+            // A modifier without parameters can leave out its parentheses
+            if node["parameters"]["parameters"].as_array().is_some_and(|parameters| !parameters.is_empty()) {
+                leaves.push(import_parameter_list(&node["parameters"], NonTerminal::ParameterList, NonTerminal::Parameter));
+            }
+            leaves.push(required(node, "body"));
+            tree(NonTerminal::ModifierDefinition, leaves)
+        }
+        "ModifierInvocation" => {
+            let mut leaves = vec![identifier(text(&node["modifierName"], "name"))];
+            leaves.extend(import_arguments(node));
+            tree(NonTerminal::ModifierInvocation, leaves)
+        }
+        "FunctionDefinition" => import_function_definition(node),
+        "VariableDeclaration" => match node["stateVariable"] == true {
+            true => import_state_variable_declaration(node),
+            false => import_parameter(node, NonTerminal::Parameter)
+        }
+
+        /*** Types ***/
+
+        "ElementaryTypeName" => lex_leaf(node, text(node, "name")),
+        "UserDefinedTypeName" => {
+            tree(NonTerminal::UserDefinedTypeName, text(node, "name").split('.').map(identifier).collect())
+        }
+        "Mapping" => {
+            let arrow = NonTerminal::Token(lex_4_25::Token::Arrow).to_leaf();
+            tree(NonTerminal::Mapping, vec![required(node, "keyType"), arrow, required(node, "valueType")])
+        }
+        "ArrayTypeName" => {
+            let mut leaves = vec![required(node, "baseType")];
+            if !node["length"].is_null() {
+                leaves.push(import_node(&node["length"]));
+            }
+            tree(NonTerminal::ArrayTypeName, leaves)
+        }
+        "FunctionTypeName" => {
+            let list = NonTerminal::FunctionTypeParameterList;
+            let mut leaves = vec![import_parameter_list(&node["parameterTypes"], list.clone(), NonTerminal::FunctionTypeParameter)];
+            if text(node, "visibility") == "external" {
+                leaves.push(NonTerminal::Token(lex_4_25::Token::External).to_leaf());
+            }
+            leaves.extend(import_state_mutability(node));
+            if node["returnParameterTypes"]["parameters"].as_array().is_some_and(|types| !types.is_empty()) {
+                let types = import_parameter_list(&node["returnParameterTypes"], list, NonTerminal::FunctionTypeParameter);
+                leaves.push(tree(NonTerminal::Token(lex_4_25::Token::Returns), vec![types]));
+            }
+            tree(NonTerminal::FunctionTypeName, leaves)
+        }
+
+        /*** Statements ***/
+
+        "Block" => tree(NonTerminal::Block, import_all(node, "statements")),
+        "PlaceholderStatement" => identifier("_"),
+        "ExpressionStatement" => required(node, "expression"),
+        "EmitStatement" => tree(NonTerminal::EmitStatement, vec![required(node, "eventCall")]),
+        "Return" => tree(NonTerminal::Return, import_optional(node, &["expression"])),
+        "Throw" => NonTerminal::Throw.to_leaf(),
+        "Break" => NonTerminal::Break.to_leaf(),
+        "Continue" => NonTerminal::Continue.to_leaf(),
+        "InlineAssembly" => NonTerminal::InlineAssemblyStatement.to_leaf(),
+        "IfStatement" => {
+            let mut leaves = vec![required(node, "condition"), required(node, "trueBody")];
+            leaves.extend(import_optional(node, &["falseBody"]));
+            tree(NonTerminal::IfStatement, leaves)
+        }
+        "WhileStatement" => tree(NonTerminal::WhileStatement, vec![required(node, "condition"), required(node, "body")]),
+        "DoWhileStatement" => tree(NonTerminal::DoWhileStatement, vec![required(node, "condition"), required(node, "body")]),
+        "ForStatement" => {
+            // The parts of the loop header that are left out are kept as Empty nonterminals
+            let mut leaves = ["initializationExpression", "condition", "loopExpression"].iter().map(|field| {
+                match node[*field] {
+                    Value::Null => NonTerminal::Empty.to_leaf(),
+                    _ => import_node(&node[*field])
+                }
+            }).collect::<Vec<ParseTree>>();
+            leaves.push(required(node, "body"));
+            tree(NonTerminal::ForStatement, leaves)
+        }
+        "VariableDeclarationStatement" => {
+            // Each declaration of a tuple is a leaf of an identifier list, with Empty nonterminals
+            // for the components that are left out
+            let declarations = node["declarations"].as_array().into_iter().flatten().map(|declaration| {
+                match declaration {
+                    Value::Null => NonTerminal::Empty.to_leaf(),
+                    _ => import_parameter(declaration, NonTerminal::VariableDeclaration)
+                }
+            }).collect::<Vec<ParseTree>>();
+            let mut leaves = match declarations.len() {
+                1 => declarations,
+                _ => vec![tree(NonTerminal::IdentifierList, declarations)]
+            };
+            leaves.extend(import_optional(node, &["initialValue"]));
+            tree(NonTerminal::VariableDefinition, leaves)
+        }
+
+        /*** Expressions ***/
+
+        "Identifier" => identifier(text(node, "name")),
+        "Literal" => import_literal(node),
+        "Assignment" | "BinaryOperation" => {
+            let (left, right) = match node_type {
+                "Assignment" => ("leftHandSide", "rightHandSide"),
+                _ => ("leftExpression", "rightExpression")
+            };
+            let mut operation = lex_leaf(node, text(node, "operator"));
+            operation.leaves = vec![Box::new(required(node, left)), Box::new(required(node, right))];
+            operation
+        }
+        "UnaryOperation" => {
+            let mut operation = lex_leaf(node, text(node, "operator"));
+            operation.leaves = vec![Box::new(required(node, "subExpression"))];
+            operation
+        }
+        "Conditional" => {
+            let leaves = vec![required(node, "condition"), required(node, "trueExpression"), required(node, "falseExpression")];
+            tree(NonTerminal::Token(lex_4_25::Token::Question), leaves)
+        }
+        "MemberAccess" => {
+            tree(NonTerminal::MemberAccess, vec![required(node, "expression"), identifier(text(node, "memberName"))])
+        }
+        "IndexAccess" => {
+            let mut leaves = vec![required(node, "baseExpression")];
+            leaves.extend(import_optional(node, &["indexExpression"]));
+            tree(NonTerminal::IndexAccess, leaves)
+        }
+        "FunctionCall" => import_function_call(node),
+        "NewExpression" => tree(NonTerminal::NewExpression, vec![required(node, "typeName")]),
+        "ElementaryTypeNameExpression" => {
+            // Newer versions of solc hold an ElementaryTypeName node rather than the type's name
+            let name = match &node["typeName"] {
+                Value::String(name) => name.as_str(),
+                type_name => text(type_name, "name")
+            };
+            tree(NonTerminal::ElementaryTypeNameExpression, vec![lex_leaf(node, name)])
+        }
+        "TupleExpression" => {
+            let root = match node["isInlineArray"] == true {
+                true => NonTerminal::InlineArrayExpression,
+                false => NonTerminal::TupleExpression
+            };
+            let components = node["components"].as_array().into_iter().flatten().map(|component| {
+                match component {
+                    Value::Null => NonTerminal::Empty.to_leaf(),
+                    _ => import_node(component)
+                }
+            }).collect();
+            tree(root, components)
+        }
+        "" => import_error(node, String::from("the AST has a value that isn't a node where a node is expected")),
+        _ => import_error(node, format!("solc AST nodes of type `{}` can't be imported", node_type))
+    }
+}
+
+// Imports the fields of node that hold a node, leaving out those that are null
+fn import_optional(node: &Value, fields: &[&str]) -> Vec<ParseTree> {
+    fields.iter().filter(|field| !node[**field].is_null()).map(|field| import_node(&node[*field])).collect()
+}
+
+// Returns the string literal token text for the contents of a string
+fn quote(contents: &str) -> String {
+    format!("\"{}\"", contents.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn import_pragma_directive(node: &Value) -> ParseTree {
+    let literals = node["literals"].as_array().into_iter().flatten().filter_map(|literal| literal.as_str()).collect::<Vec<&str>>();
+    let mut leaves = literals.first().map(|name| identifier(name)).into_iter().collect::<Vec<ParseTree>>();
+    // solc splits versions like `0.4.25` into the numbers `0.4` and `.25`, which are joined back
+    // into a single version token
+    let mut version = String::new();
+    for literal in literals.iter().skip(1) {
+        if literal.starts_with(|first: char| first.is_ascii_digit() || first == '.') {
+            version.push_str(literal);
+            continue;
+        }
+        if !version.is_empty() {
+            leaves.push(NonTerminal::Token(lex_4_25::Token::Version(version.clone())).to_leaf());
+            version.clear();
+        }
+        leaves.push(lex_leaf(node, literal));
+    }
+    if !version.is_empty() {
+        leaves.push(NonTerminal::Token(lex_4_25::Token::Version(version)).to_leaf());
+    }
+    tree(NonTerminal::PragmaDirective, leaves)
+}
+
+fn import_contract_definition(node: &Value) -> ParseTree {
+    let kind = match lex(text(node, "contractKind")) {
+        Some(kind) => kind,
+        None => return import_error(node, format!("`{}` isn't a kind of contract", text(node, "contractKind")))
+    };
+    let mut leaves = vec![identifier(text(node, "name"))];
+    let bases = import_all(node, "baseContracts");
+    if !bases.is_empty() {
+        leaves.push(tree(NonTerminal::InheritanceList, bases));
+    }
+    leaves.push(tree(NonTerminal::ContractPart, import_all(node, "nodes")));
+    tree(NonTerminal::ContractDefinition(kind), leaves)
+}
+
+// Imports the arguments of an inheritance specifier or modifier invocation, which are null if
+// there are no parentheses
+fn import_arguments(node: &Value) -> Option<ParseTree> {
+    let arguments = node["arguments"].as_array()?;
+    Some(import_expression_list(arguments))
+}
+
+// Imports a list of arguments. Empty lists hold an Expression nonterminal without any leaves, as
+// they do in the parser's trees.
+fn import_expression_list(arguments: &[Value]) -> ParseTree {
+    match arguments.is_empty() {
+        true => tree(NonTerminal::ExpressionList, vec![NonTerminal::Expression.to_leaf()]),
+        false => tree(NonTerminal::ExpressionList, arguments.iter().map(import_node).collect())
+    }
+}
+
+fn import_state_variable_declaration(node: &Value) -> ParseTree {
+    let mut leaves = vec![required(node, "typeName")];
+    if text(node, "visibility") != "internal" {
+        leaves.push(lex_leaf(node, text(node, "visibility")));
+    }
+    if node["constant"] == true {
+        leaves.push(NonTerminal::Token(lex_4_25::Token::Constant).to_leaf());
+    }
+    leaves.push(identifier(text(node, "name")));
+    if !node["value"].is_null() {
+        leaves.push(tree(NonTerminal::Token(lex_4_25::Token::Assignment), vec![import_node(&node["value"])]));
+    }
+    tree(NonTerminal::StateVariableDeclaration, leaves)
+}
+
+fn import_parameter_list(node: &Value, list: NonTerminal, parameter: NonTerminal) -> ParseTree {
+    let parameters = node["parameters"].as_array().into_iter().flatten()
+        .map(|declaration| import_parameter(declaration, parameter.clone()))
+        .collect();
+    tree(list, parameters)
+}
+
+// Imports a variable declaration that isn't a state variable, such as a parameter or a struct
+// member, with the given root
+fn import_parameter(node: &Value, root: NonTerminal) -> ParseTree {
+    let mut leaves = import_optional(node, &["typeName"]);
+    match text(node, "storageLocation") {
+        "" | "default" => (),
+        location => match lex(location) {
+            Some(location) => leaves.push(NonTerminal::StorageLocation(location).to_leaf()),
+            None => leaves.push(import_error(node, format!("`{}` isn't a storage location", location)))
+        }
+    }
+    if node["indexed"] == true {
+        leaves.push(NonTerminal::Token(lex_4_25::Token::Indexed).to_leaf());
+    }
+    if !text(node, "name").is_empty() {
+        leaves.push(identifier(text(node, "name")));
+    }
+    tree(root, leaves)
+}
+
+// Returns the state mutability of a function or function type, unless it is the default
+fn import_state_mutability(node: &Value) -> Option<ParseTree> {
+    if node["isDeclaredConst"] == true {
+        return Some(NonTerminal::StateMutability(lex_4_25::Token::Constant).to_leaf());
+    }
+    match text(node, "stateMutability") {
+        "" | "nonpayable" => None,
+        mutability => match lex(mutability) {
+            Some(mutability) => Some(NonTerminal::StateMutability(mutability).to_leaf()),
+            None => Some(import_error(node, format!("`{}` isn't a state mutability", mutability)))
+        }
+    }
+}
+
+fn import_function_definition(node: &Value) -> ParseTree {
+    let name = text(node, "name");
+    let root = match (name, node["isConstructor"] == true) {
+        ("", true) => NonTerminal::ConstructorDefinition,
+        ("", false) => NonTerminal::FallbackFunctionDefinition,
+        _ => NonTerminal::FunctionDefinition
+    };
+    let mut leaves = vec![];
+    if !name.is_empty() {
+        leaves.push(identifier(name));
+    }
+    leaves.push(import_parameter_list(&node["parameters"], NonTerminal::ParameterList, NonTerminal::Parameter));
+    leaves.push(lex_leaf(node, text(node, "visibility")));
+    leaves.extend(import_state_mutability(node));
+    leaves.extend(import_all(node, "modifiers"));
+    if node["returnParameters"]["parameters"].as_array().is_some_and(|parameters| !parameters.is_empty()) {
+        leaves.push(NonTerminal::Token(lex_4_25::Token::Returns).to_leaf());
+        leaves.push(import_parameter_list(&node["returnParameters"], NonTerminal::ParameterList, NonTerminal::Parameter));
+    }
+    leaves.extend(import_optional(node, &["body"]));
+    tree(root, leaves)
+}
+
+fn import_function_call(node: &Value) -> ParseTree {
+    let arguments = node["arguments"].as_array().map_or(&[][..], |arguments| arguments.as_slice());
+    // Legacy ASTs write empty lists as `[null]`
+    let names = node["names"].as_array().into_iter().flatten().filter_map(|name| name.as_str()).collect::<Vec<&str>>();
+    let list = match names.is_empty() {
+        true => import_expression_list(arguments),
+        false => {
+            // Named arguments are pairs of a name and a value under a colon
+            let pairs = names.iter().zip(arguments).map(|(name, value)| {
+                tree(NonTerminal::Token(lex_4_25::Token::Colon), vec![identifier(name), import_node(value)])
+            }).collect();
+            tree(NonTerminal::NameValueList, pairs)
+        }
+    };
+    tree(NonTerminal::FunctionCall, vec![required(node, "expression"), tree(NonTerminal::FunctionCallArguments, vec![list])])
+}
+
+fn import_literal(node: &Value) -> ParseTree {
+    let value = text(node, "value");
+    let token = match text(node, "kind") {
+        "number" if value.starts_with("0x") || value.starts_with("0X") => lex_4_25::to_hex_number(value),
+        "number" => lex_4_25::to_decimal_number(value),
+        "bool" if value == "true" => lex_4_25::Token::True,
+        "bool" => lex_4_25::Token::False,
+        // solc leaves out the value of hex literals that aren't valid UTF-8
+        "string" if node["value"].is_null() => lex_4_25::to_hex_literal(&format!("hex\"{}\"", text(node, "hexValue"))),
+        "string" => lex_4_25::to_string_literal(&quote(value)),
+        kind => return import_error(node, format!("`{}` isn't a kind of literal", kind))
+    };
+    let mut literal = NonTerminal::Token(token).to_leaf();
+    if !node["subdenomination"].is_null() {
+        literal.leaves.push(Box::new(lex_leaf(node, text(node, "subdenomination"))));
+    }
+    literal
+}
+
+/*** Legacy ASTs ***/
+
+// How the children of a legacy node are spread over the fields of the compact node
+enum Children {
+    // The field holds the next child
+    One,
+    // The field holds the children that follow, as long as they have one of these types
+    Many(&'static [&'static str]),
+    // The field holds the rest of the children
+    Rest,
+}
+
+// Returns the fields of a node type that hold nodes, in the order that the legacy AST lists the
+// nodes in as children
+fn legacy_fields(node_type: &str) -> Vec<(&'static str, Children)> {
+    use self::Children::*;
+    match node_type {
+        "SourceUnit" => vec![("nodes", Rest)],
+        "ContractDefinition" => vec![("baseContracts", Many(&["InheritanceSpecifier"])), ("nodes", Rest)],
+        "InheritanceSpecifier" => vec![("baseName", One), ("arguments", Rest)],
+        "UsingForDirective" => vec![("libraryName", One), ("typeName", One)],
+        "StructDefinition" | "EnumDefinition" => vec![("members", Rest)],
+        "ParameterList" => vec![("parameters", Rest)],
+        "EventDefinition" => vec![("parameters", One)],
+        "VariableDeclaration" => vec![("typeName", One), ("value", One)],
+        "ModifierDefinition" => vec![("parameters", One), ("body", One)],
+        "ModifierInvocation" => vec![("modifierName", One), ("arguments", Rest)],
+        "FunctionDefinition" => {
+            vec![("parameters", One), ("returnParameters", One), ("modifiers", Many(&["ModifierInvocation"])), ("body", One)]
+        }
+        "FunctionTypeName" => vec![("parameterTypes", One), ("returnParameterTypes", One)],
+        "Mapping" => vec![("keyType", One), ("valueType", One)],
+        "ArrayTypeName" => vec![("baseType", One), ("length", One)],
+        "Block" => vec![("statements", Rest)],
+        "IfStatement" => vec![("condition", One), ("trueBody", One), ("falseBody", One)],
+        "WhileStatement" | "DoWhileStatement" => vec![("condition", One), ("body", One)],
+        "ForStatement" => {
+            vec![("initializationExpression", One), ("condition", One), ("loopExpression", One), ("body", One)]
+        }
+        "ExpressionStatement" => vec![("expression", One)],
+        "EmitStatement" => vec![("eventCall", One)],
+        "Return" => vec![("expression", One)],
+        "VariableDeclarationStatement" => vec![("declarations", Many(&["VariableDeclaration"])), ("initialValue", One)],
+        "Assignment" => vec![("leftHandSide", One), ("rightHandSide", One)],
+        "BinaryOperation" => vec![("leftExpression", One), ("rightExpression", One)],
+        "UnaryOperation" => vec![("subExpression", One)],
+        "Conditional" => vec![("condition", One), ("trueExpression", One), ("falseExpression", One)],
+        "TupleExpression" => vec![("components", Rest)],
+        "FunctionCall" => vec![("expression", One), ("arguments", Rest)],
+        "NewExpression" => vec![("typeName", One)],
+        "MemberAccess" => vec![("expression", One)],
+        "IndexAccess" => vec![("baseExpression", One), ("indexExpression", One)],
+        _ => vec![]
+    }
+}
+
+/**
+ * @dev Converts a node of a legacy AST into a node of a compact AST. Legacy nodes keep their
+ *      scalar fields in `attributes` and list the nodes that their other fields hold as
+ *      `children`. A field whose node is missing is recorded as a null attribute.
+ * @param legacy The legacy node.
+ * @return The compact node.
+ */
+fn from_legacy(legacy: &Value) -> Value {
+    let node_type = text(legacy, "name");
+    let mut node = match &legacy["attributes"] {
+        Value::Object(attributes) => attributes.clone(),
+        _ => Map::new()
+    };
+    // A few attributes were renamed in the compact AST
+    let renames = match node_type {
+        "Identifier" => vec![("value", "name")],
+        "ElementaryTypeNameExpression" => vec![("value", "typeName")],
+        "MemberAccess" => vec![("member_name", "memberName")],
+        "Literal" => vec![("token", "kind")],
+        _ => vec![]
+    };
+    for (legacy_name, name) in renames {
+        if let Some(value) = node.remove(legacy_name) {
+            node.insert(String::from(name), value);
+        }
+    }
+    if node_type == "FunctionCall" {
+        let kind = match node.remove("type_conversion") {
+            Some(Value::Bool(true)) => "typeConversion",
+            _ => "functionCall"
+        };
+        node.insert(String::from("kind"), json!(kind));
+    }
+    let children = legacy["children"].as_array().into_iter().flatten().map(from_legacy).collect::<Vec<Value>>();
+    let mut children = children.into_iter().peekable();
+    for (field, kind) in legacy_fields(node_type) {
+        if node.get(field).is_some_and(|value| value.is_null()) {
+            continue;
+        }
+        let value = match kind {
+            Children::One => children.next().unwrap_or(Value::Null),
+            Children::Many(types) => {
+                let mut values = vec![];
+                while let Some(child) = children.next_if(|child| types.contains(&text(child, "nodeType"))) {
+                    values.push(child);
+                }
+                Value::Array(values)
+            }
+            Children::Rest => Value::Array(children.by_ref().collect())
+        };
+        node.insert(String::from(field), value);
+    }
+    node.insert(String::from("id"), legacy["id"].clone());
+    node.insert(String::from("nodeType"), json!(node_type));
+    node.insert(String::from("src"), legacy["src"].clone());
+    Value::Object(node)
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+use super::diagnostics::Span;
+
+/**
+ * @dev Identifies a file in a SourceMap. Ids are handed out in the order that files are added,
+ *      starting from zero, which is also how solc numbers the sources of a compilation.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileId(pub usize);
+
+impl fmt::Display for FileId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/**
+ * @dev The units that a column counts. Editors that speak the Language Server Protocol count
+ *      UTF-16 code units by default, while UTF-8 columns count bytes and UTF-32 columns count
+ *      characters, like the offsets of a Span.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    Utf16,
+    Utf32,
+}
+
+impl Encoding {
+    // Returns the number of units that c takes up in this encoding
+    fn len(&self, c: char) -> usize {
+        match self {
+            Encoding::Utf8 => c.len_utf8(),
+            Encoding::Utf16 => c.len_utf16(),
+            Encoding::Utf32 => 1
+        }
+    }
+}
+
+/**
+ * @dev A position in a source file. Both the line and the column are counted from zero, as in
+ *      the Language Server Protocol, and the column counts the units of an Encoding from the start
+ *      of the line.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    pub fn new(line: usize, column: usize) -> LineColumn {
+        LineColumn { line, column }
+    }
+}
+
+// Where a line starts, as a character offset and as the byte and UTF-16 offsets of that character
+#[derive(Clone, Debug, PartialEq)]
+struct LineStart {
+    offset: usize,
+    byte: usize,
+    utf16: usize,
+}
+
+/**
+ * @dev A file that has been added to a SourceMap, along with an index of where its lines start.
+ *      Offsets into a file count characters, like the offsets of a Span, and can be converted to
+ *      byte offsets and to the line and column in any Encoding.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceFile {
+    id: FileId,
+    path: String,
+    text: String,
+    // The characters of the text, which is what the lexer reads
+    chars: Vec<char>,
+    lines: Vec<LineStart>,
+}
+
+impl SourceFile {
+    pub fn new(id: FileId, path: &str, text: String) -> SourceFile {
+        let chars = text.chars().collect::<Vec<char>>();
+        let mut lines = vec![LineStart { offset: 0, byte: 0, utf16: 0 }];
+        let (mut byte, mut utf16) = (0, 0);
+        for (offset, c) in chars.iter().enumerate() {
+            byte += c.len_utf8();
+            utf16 += c.len_utf16();
+            if *c == '\n' {
+                lines.push(LineStart { offset: offset + 1, byte, utf16 });
+            }
+        }
+        SourceFile { id, path: String::from(path), text, chars, lines }
+    }
+
+    pub fn id(&self) -> FileId {
+        self.id
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn chars(&self) -> &Vec<char> {
+        &self.chars
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    // Returns the span of a line, counted from zero, without its line break
+    pub fn line_span(&self, line: usize) -> Span {
+        let start = self.lines[line].offset;
+        let end = self.lines.get(line + 1).map_or(self.chars.len(), |next| next.offset - 1);
+        Span::new(start, end)
+    }
+
+    // Returns the line, counted from zero, that the character at offset is on. Offsets past the
+    // end of the file are on the last line.
+    pub fn line(&self, offset: usize) -> usize {
+        let offset = offset.min(self.chars.len());
+        self.lines.partition_point(|start| start.offset <= offset) - 1
+    }
+
+    /**
+     * @dev Returns the line and column of the character at offset, with the column counted in the
+     *      units of encoding. Offsets past the end of the file are treated as the end of the file.
+     * @param offset The character offset to convert.
+     * @param encoding The units that the column counts.
+     */
+    pub fn line_column(&self, offset: usize, encoding: Encoding) -> LineColumn {
+        let offset = offset.min(self.chars.len());
+        let line = self.line(offset);
+        let start = &self.lines[line];
+        let column = match encoding {
+            Encoding::Utf32 => offset - start.offset,
+            encoding => self.chars[start.offset..offset].iter().map(|c| encoding.len(*c)).sum()
+        };
+        LineColumn::new(line, column)
+    }
+
+    /**
+     * @dev Returns the character offset of a line and column. A column past the end of its line
+     *      is the end of the line, as in the Language Server Protocol.
+     * @param position The line and column to convert.
+     * @param encoding The units that the column counts.
+     * @return The offset, or None if the line doesn't exist or the column falls inside of a
+     *         character.
+     */
+    pub fn offset(&self, position: LineColumn, encoding: Encoding) -> Option<usize> {
+        if position.line >= self.lines.len() {
+            return None;
+        }
+        let span = self.line_span(position.line);
+        let mut column = 0;
+        for offset in span.start..span.end {
+            if column >= position.column {
+                return if column == position.column { Some(offset) } else { None };
+            }
+            column += encoding.len(self.chars[offset]);
+        }
+        // The column is either inside of the last character or at or past the end of the line
+        match column > position.column {
+            true => None,
+            false => Some(span.end)
+        }
+    }
+
+    // Returns the byte offset in the text of the character at offset
+    pub fn byte_offset(&self, offset: usize) -> usize {
+        let offset = offset.min(self.chars.len());
+        let start = &self.lines[self.line(offset)];
+        start.byte + self.chars[start.offset..offset].iter().map(|c| c.len_utf8()).sum::<usize>()
+    }
+
+    // Returns the character offset of a byte offset in the text, or None if it isn't the start
+    // of a character or the end of the text
+    pub fn char_offset(&self, byte: usize) -> Option<usize> {
+        if byte > self.text.len() || !self.text.is_char_boundary(byte) {
+            return None;
+        }
+        let line = self.lines.partition_point(|start| start.byte <= byte) - 1;
+        let start = &self.lines[line];
+        Some(start.offset + self.text[start.byte..byte].chars().count())
+    }
+}
+
+/**
+ * @dev The files that a run of solfix reads. Each file is given a FileId when it is added, which
+ *      is how diagnostics and ASTs from several files record which file they belong to.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap { files: vec![] }
+    }
+
+    // Adds a file and returns its id. Adding the same path twice adds a second file.
+    pub fn add(&mut self, path: &str, text: String) -> FileId {
+        let id = FileId(self.files.len());
+        self.files.push(SourceFile::new(id, path, text));
+        id
+    }
+
+    // Returns the file with id, which must have been added to this map
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    // Returns the id of the last file that was added with path
+    pub fn find(&self, path: &str) -> Option<FileId> {
+        self.files.iter().rev().find(|file| file.path == path).map(|file| file.id)
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+}
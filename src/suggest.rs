@@ -0,0 +1,99 @@
+/**
+ * @dev Returns the edit distance between two words. Insertions, deletions, substitutions and
+ *      transpositions of adjacent characters each count as a single edit, so `fucntion` is one
+ *      edit away from `function`.
+ */
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/**
+ * @dev Returns the candidate that is closest to word, as long as it is close enough to be a
+ *      plausible misspelling. Words are allowed one edit for every three characters (and at least
+ *      one edit). Ties go to the candidate that shares the most leading and trailing characters with
+ *      word, so `uin256` suggests `uint256` rather than `uint56`, and then to the earlier candidate.
+ * @param word The word that was not recognized.
+ * @param candidates The words that could have been intended, such as keywords or declared names.
+ */
+pub fn best_match<'a, S: AsRef<str>>(word: &str, candidates: &'a [S]) -> Option<&'a str> {
+    let max_distance = (word.chars().count() / 3).max(1);
+    let mut best: Option<(&str, usize, usize)> = None;
+    for candidate in candidates {
+        let candidate = candidate.as_ref();
+        if candidate == word {
+            return None;
+        }
+        let distance = edit_distance(word, candidate);
+        let shared = shared_affix_len(word, candidate);
+        // A candidate that doesn't start or end like word, such as `A` for `C`, is too different
+        // to suggest even if it is only one edit away.
+        if distance > max_distance || shared == 0 {
+            continue;
+        }
+        match best {
+            Some((_, best_distance, best_shared))
+                if best_distance < distance || (best_distance == distance && best_shared >= shared) => (),
+            _ => best = Some((candidate, distance, shared))
+        }
+    }
+    best.map(|(candidate, _, _)| candidate)
+}
+
+// Returns the length of the common prefix plus the common suffix of two words
+fn shared_affix_len(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let prefix = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    prefix + suffix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("function", "function"), 0);
+        assert_eq!(edit_distance("fucntion", "function"), 1);
+        assert_eq!(edit_distance("uin256", "uint256"), 1);
+        assert_eq!(edit_distance("retruns", "returns"), 1);
+        assert_eq!(edit_distance("", "for"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_best_match() {
+        let candidates = ["function", "modifier", "returns", "return"];
+        assert_eq!(best_match("fucntion", &candidates), Some("function"));
+        assert_eq!(best_match("retruns", &candidates), Some("returns"));
+        assert_eq!(best_match("retrun", &candidates), Some("return"));
+        assert_eq!(best_match("balance", &candidates), None);
+        assert_eq!(best_match("function", &candidates), None);
+        assert_eq!(best_match("uin256", &["uint56", "uint256"]), Some("uint256"));
+    }
+}
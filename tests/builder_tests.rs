@@ -0,0 +1,70 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod builder_tests {
+    use solfix::diagnostics::{ Diagnostic, Span };
+    use solfix::lex_4_25::Token;
+    use solfix::parse_4_25::{ NonTerminal, ParseTree };
+    use solfix::tree;
+
+    /*** Builder Tests ***/
+
+    #[test]
+    fn tree_test1() {
+        let expected = ParseTree {
+            root: NonTerminal::ContractDefinition(Token::Contract),
+            leaves: vec![
+                Box::new(NonTerminal::Identifier(String::from("Ownable")).to_leaf()),
+                Box::new(ParseTree {
+                    root: NonTerminal::ContractPart,
+                    leaves: vec![Box::new(NonTerminal::StorageLocation(Token::Memory).to_leaf())]
+                })
+            ]
+        };
+        let actual = tree!(ContractDefinition(Contract) [
+            Identifier("Ownable"),
+            ContractPart [StorageLocation(Memory)],
+        ]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn tree_test2() {
+        let name = String::from("owner");
+        assert_eq!(tree!(Identifier(name.clone())).root, NonTerminal::Identifier(name));
+        assert_eq!(tree!(Token(UfixedMxN(64, 8))).root, NonTerminal::Token(Token::UfixedMxN(64, 8)));
+        assert_eq!(tree!(Token(Version("0.4.25"))).root, NonTerminal::Token(Token::Version(String::from("0.4.25"))));
+    }
+
+    #[test]
+    fn tree_test3() {
+        let diagnostic = Diagnostic::error(String::from("expected `;`"), Span::new(3, 4));
+        let expected = ParseTree {
+            root: NonTerminal::Statement,
+            leaves: vec![
+                Box::new(NonTerminal::Token(Token::Semicolon).to_invalid().to_leaf()),
+                Box::new(NonTerminal::InvalidPair(
+                    Box::new(NonTerminal::Token(Token::Semicolon)),
+                    Box::new(NonTerminal::Identifier(String::from("x")))
+                ).to_leaf()),
+                Box::new(NonTerminal::Error(Box::new(diagnostic.clone())).to_leaf())
+            ]
+        };
+        let actual = tree!(Statement [
+            Invalid(Token(Semicolon)),
+            InvalidPair(Token(Semicolon), Identifier("x")),
+            Error(diagnostic)
+        ]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn tree_test4() {
+        let leaf = tree!(Identifier("a"));
+        let leaves = [leaf.clone(), tree!(Identifier("b"))];
+        let actual = tree!(IdentifierList [{ leaf }, { leaves[1].clone() }, Token(Comma)]);
+        assert_eq!(actual.leaves.len(), 3);
+        assert_eq!(*actual.leaves[0], leaves[0]);
+        assert_eq!(*actual.leaves[1], leaves[1]);
+    }
+}
@@ -0,0 +1,105 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod codes_tests {
+    use regex::Regex;
+    use solfix::codes::{ self, Code };
+    use solfix::diagnostics::Diagnostic;
+    use solfix::parser::{ Parser, ParserOptions };
+    use solfix::report;
+    use solfix::source_map::{ FileId, SourceFile };
+
+    /*** Helpers ***/
+
+    // Returns the Solidity examples in an explanation, along with whether each one is an
+    // erroneous example and the options that it is parsed with
+    fn examples(explanation: &str) -> Vec<(String, bool, ParserOptions)> {
+        let block = Regex::new(r"(?s)```(solidity[^\n]*)\n(.*?)```").unwrap();
+        block.captures_iter(explanation).map(|captures| {
+            let mut erroneous = false;
+            let mut options = ParserOptions::default();
+            for attribute in captures[1].split(',').skip(1) {
+                match attribute.split_once('=') {
+                    Some(("version", version)) => {
+                        let parts = version.split('.').map(|part| part.parse::<usize>().unwrap()).collect::<Vec<usize>>();
+                        options.version = (parts[0], parts[1], parts[2]);
+                    }
+                    Some(("max_depth", depth)) => options.max_depth = depth.parse().unwrap(),
+                    _ if attribute == "error" => erroneous = true,
+                    _ => panic!("unknown attribute `{}`", attribute)
+                }
+            }
+            (captures[2].to_string(), erroneous, options)
+        }).collect()
+    }
+
+    fn diagnostics(source: &str, options: ParserOptions) -> Vec<Diagnostic> {
+        let tree = Parser::new(options).parse_source_unit(source);
+        report::diagnostics(&SourceFile::new(FileId(0), "A.sol", String::from(source)), &tree)
+    }
+
+    /*** Catalog Tests ***/
+
+    #[test]
+    fn catalog_test1() {
+        for (index, entry) in codes::catalog().iter().enumerate() {
+            assert_eq!(entry.code.to_string(), format!("SF{:04}", index + 1));
+            assert!(!entry.title.is_empty());
+            let examples = examples(entry.text);
+            assert!(examples.iter().any(|(_, erroneous, _)| *erroneous), "{} has no erroneous example", entry.code);
+            assert!(examples.iter().any(|(_, erroneous, _)| !*erroneous), "{} has no fixed example", entry.code);
+        }
+    }
+
+    #[test]
+    fn catalog_test2() {
+        for entry in codes::catalog() {
+            for (source, erroneous, options) in examples(entry.text) {
+                let diagnostics = diagnostics(&source, options);
+                if erroneous {
+                    assert!(
+                        diagnostics.iter().any(|diagnostic| diagnostic.code == Some(entry.code)),
+                        "the example of {} has the diagnostics {:#?}", entry.code, diagnostics
+                    );
+                } else {
+                    assert!(diagnostics.is_empty(), "the fixed example of {} has the diagnostics {:#?}", entry.code, diagnostics);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn catalog_test3() {
+        // Every diagnostic of the erroneous examples, including the ones that follow from the
+        // first, has a code
+        for entry in codes::catalog() {
+            for (source, _, options) in examples(entry.text) {
+                for diagnostic in diagnostics(&source, options) {
+                    assert!(diagnostic.code.is_some(), "{:?} has no code", diagnostic);
+                }
+            }
+        }
+    }
+
+    /*** Explain Tests ***/
+
+    #[test]
+    fn explain_test1() {
+        assert_eq!(codes::explain("SF0001").unwrap().code, codes::EXPECTED_SEMICOLON);
+        assert_eq!(codes::explain("sf0012").unwrap().code, codes::INVALID_MAPPING_KEY);
+        assert_eq!(codes::explain(" 0004 ").unwrap().code, codes::EXPECTED_IDENTIFIER);
+        assert_eq!(codes::explain("SF9999"), None);
+        assert_eq!(codes::explain("E0308"), None);
+        assert_eq!(codes::explain(""), None);
+    }
+
+    #[test]
+    fn code_test1() {
+        assert_eq!(Code::parse("SF0017"), Some(codes::NESTED_TOO_DEEPLY));
+        assert_eq!(Code::parse("sf0001"), Some(Code(1)));
+        assert_eq!(Code::parse("SF1"), None);
+        assert_eq!(Code::parse("SF00001"), None);
+        assert_eq!(Code::parse("SF+001"), None);
+        assert_eq!(Code(42).to_string(), "SF0042");
+    }
+}
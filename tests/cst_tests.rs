@@ -0,0 +1,209 @@
+extern crate regex;
+extern crate solfix;
+
+#[cfg(test)]
+mod cst_tests {
+    use regex::Regex;
+    use std::fs;
+    use std::time::{ Duration, Instant };
+    use solfix::cst::{ self, SyntaxElement, SyntaxNode };
+    use solfix::diagnostics::Span;
+    use solfix::lex_4_25;
+    use solfix::parse_4_25::NonTerminal;
+    use solfix::parser::{ Parser, ParserOptions };
+
+    /*** Helpers ***/
+
+    fn lossless_parser() -> Parser {
+        Parser::new(ParserOptions { retain_trivia: true, ..ParserOptions::default() })
+    }
+
+    fn assert_round_trip(source: &str) {
+        let actual = lossless_parser().parse_syntax_tree(source).to_source();
+        assert_eq!(source, actual, "Expected\n========\n{}\nActual\n======\n{}", source, actual);
+    }
+
+    fn find_node(node: &SyntaxNode, kind: &NonTerminal) -> Option<SyntaxNode> {
+        if node.kind() == kind {
+            return Some(node.clone());
+        }
+        node.children().iter().filter_map(|child| find_node(child, kind)).next()
+    }
+
+    fn significant_text(node: &SyntaxNode) -> Vec<String> {
+        node.tokens().iter()
+            .filter(|token| !token.is_trivia())
+            .map(|token| token.text().to_string())
+            .collect()
+    }
+
+    // Returns the contents of the string literals in the test files
+    fn test_inputs() -> Vec<String> {
+        let literal = Regex::new(r#""((?:[^"\\]|\\.)*)""#).unwrap();
+        let mut inputs = vec![];
+        for entry in fs::read_dir("tests").unwrap() {
+            let path = entry.unwrap().path();
+            if !path.is_file() {
+                continue;
+            }
+            let contents = fs::read_to_string(path).unwrap();
+            for captures in literal.captures_iter(&contents) {
+                inputs.push(captures[1].replace("\\n", "\n")
+                                       .replace("\\t", "\t")
+                                       .replace("\\\"", "\"")
+                                       .replace("\\\\", "\\"));
+            }
+        }
+        inputs
+    }
+
+    // Returns the tokens that weren't placed in any nonterminal and were left on the root
+    fn root_tokens(source: &str) -> Vec<String> {
+        lossless_parser().parse_syntax_tree(source).children_with_tokens().iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Token(token) if !token.is_trivia() => Some(token.text().to_string()),
+                _ => None
+            })
+            .collect()
+    }
+
+    /*** Round Trip ***/
+
+    #[test]
+    fn round_trip_test1() {
+        for entry in fs::read_dir("contracts").unwrap() {
+            assert_round_trip(&fs::read_to_string(entry.unwrap().path()).unwrap());
+        }
+    }
+
+    #[test]
+    fn round_trip_test2() {
+        for input in test_inputs() {
+            assert_round_trip(&input);
+        }
+    }
+
+    #[test]
+    fn round_trip_test3() {
+        let source = fs::read_to_string("contracts/Ownable.sol").unwrap();
+        let chars: Vec<char> = source.chars().collect();
+        for end in (0..chars.len()).step_by(37) {
+            assert_round_trip(&chars[..end].iter().collect::<String>());
+        }
+    }
+
+    #[test]
+    fn round_trip_test4() {
+        assert_round_trip("contract A { function f() { a = (b + c) * ((d, e)); /* unclosed");
+    }
+
+    /*** Structure ***/
+
+    #[test]
+    fn structure_test1() {
+        let source = "contract A { function f(uint a) returns (uint) { return (a + b)++; } }";
+        let root = lossless_parser().parse_syntax_tree(source);
+        assert_eq!(Span::new(0, source.len()), root.span());
+        let block = find_node(&root, &NonTerminal::Block).unwrap();
+        assert_eq!("{ return (a + b)++; }", &source[block.span().start..block.span().end]);
+        let plus = find_node(&root, &NonTerminal::Token(lex_4_25::Token::Plus)).unwrap();
+        assert_eq!(vec!["(", "a", "+", "b", ")"], significant_text(&plus));
+        let parameters = find_node(&root, &NonTerminal::ParameterList).unwrap();
+        assert_eq!(vec!["(", "uint", "a", ")"], significant_text(&parameters));
+        assert_eq!(&NonTerminal::FunctionDefinition, parameters.parent().unwrap().kind());
+    }
+
+    #[test]
+    fn structure_test2() {
+        let source = "contract A { mapping(address => uint[2][]) m; }";
+        let root = lossless_parser().parse_syntax_tree(source);
+        let mapping = find_node(&root, &NonTerminal::Mapping).unwrap();
+        assert_eq!("mapping(address => uint[2][])", &source[mapping.span().start..mapping.span().end]);
+        let declaration = find_node(&root, &NonTerminal::StateVariableDeclaration).unwrap();
+        assert_eq!(Some(";"), significant_text(&declaration).last().map(|text| text.as_str()));
+    }
+
+    #[test]
+    fn structure_test3() {
+        // Every token of an input that parses cleanly belongs to one of the nonterminals
+        let mut inputs = test_inputs();
+        for entry in fs::read_dir("contracts").unwrap() {
+            inputs.push(fs::read_to_string(entry.unwrap().path()).unwrap());
+        }
+        let mut checked = 0;
+        for input in inputs {
+            if !Parser::default().parse_source_unit(&input).has_errors() {
+                assert_eq!(root_tokens(&input), Vec::<String>::new(), "{}", input);
+                checked += 1;
+            }
+        }
+        assert!(checked > 50);
+    }
+
+    #[test]
+    fn structure_test4() {
+        let source = "contract A { struct S { uint a; bool b; } }";
+        let root = lossless_parser().parse_syntax_tree(source);
+        let definition = find_node(&root, &NonTerminal::StructDefinition).unwrap();
+        assert_eq!("struct S { uint a; bool b; }", &source[definition.span().start..definition.span().end]);
+        let declaration = find_node(&root, &NonTerminal::VariableDeclaration).unwrap();
+        assert_eq!(vec!["uint", "a", ";"], significant_text(&declaration));
+    }
+
+    /*** Trivia ***/
+
+    #[test]
+    fn trivia_test1() {
+        let plain = Parser::default().parse_source_unit("contract A { uint a; }");
+        let commented = Parser::default().parse_source_unit("contract /* name */ A { // state\n uint a; }");
+        assert_eq!(plain, commented);
+    }
+
+    #[test]
+    fn trivia_test2() {
+        let source = "contract A { // state\n uint a; }";
+        let with_trivia = lossless_parser().parse_syntax_tree(source);
+        let comments = with_trivia.tokens().into_iter()
+            .filter(|token| token.kind() == &lex_4_25::Token::CommentSingle)
+            .collect::<Vec<_>>();
+        assert_eq!(1, comments.len());
+        assert_eq!("// state", comments[0].text());
+        assert_eq!(Span::new(13, 21), comments[0].span());
+        let without_trivia = Parser::default().parse_syntax_tree(source);
+        assert!(without_trivia.tokens().iter().all(|token| !token.is_trivia()));
+        assert_eq!("contractA{uinta;}", without_trivia.to_source());
+    }
+
+    #[test]
+    fn trivia_test3() {
+        let root = lossless_parser().parse_syntax_tree("  ");
+        let tokens = root.children_with_tokens();
+        assert_eq!(1, tokens.len());
+        match &tokens[0] {
+            SyntaxElement::Token(token) => assert_eq!(&lex_4_25::Token::Whitespace, token.kind()),
+            SyntaxElement::Node(node) => panic!("Expected a token, found {:?}", node)
+        }
+    }
+
+    /*** Build Time ***/
+
+    #[test]
+    fn build_time_test1() {
+        // Each of the long expressions nests 200 operations, which the tree is built around in
+        // linear time
+        let statement = format!("x = {}1;\n", "1 + ".repeat(200));
+        let source = format!("contract A {{ function f() {{ {} }} }}", statement.repeat(20));
+        let input = source.chars().collect::<Vec<char>>();
+        let tree = Parser::default().parse_source_unit(&source);
+        assert!(!tree.has_errors());
+        let start = Instant::now();
+        let root = cst::build(&input, &tree, true);
+        assert!(start.elapsed() < Duration::from_secs(5), "building took {:?}", start.elapsed());
+        assert_eq!(source, root.to_source());
+        let plus = find_node(&root, &NonTerminal::Token(lex_4_25::Token::Plus)).unwrap();
+        assert_eq!(statement.trim_end().trim_start_matches("x = ").trim_end_matches(';'), plus.to_source());
+        // The tree that is built from the tokens that the parse recorded is the same
+        let recorded = lossless_parser().parse_syntax_tree(&source);
+        assert_eq!(format!("{:?}", root.tokens()), format!("{:?}", recorded.tokens()));
+    }
+}
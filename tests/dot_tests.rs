@@ -0,0 +1,87 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod dot_tests {
+    use solfix::dot::{ render, DotOptions };
+    use solfix::navigation::NodeId;
+    use solfix::parse_4_25::parse;
+    use solfix::parser::Parser;
+    use solfix::source_map::SourceMap;
+
+    /*** Helpers ***/
+
+    // Returns the lines of a graph between its header and its closing brace
+    fn body(dot: &str) -> Vec<&str> {
+        let lines = dot.lines().collect::<Vec<&str>>();
+        assert_eq!(&lines[..3], &["digraph ParseTree {", "    ordering=out;", "    node [shape=box, fontname=\"monospace\"];"]);
+        assert_eq!(lines.last(), Some(&"}"));
+        lines[3..lines.len() - 1].iter().map(|line| line.trim()).collect()
+    }
+
+    /*** Rendering ***/
+
+    #[test]
+    fn render_test1() {
+        let tree = Parser::default().parse_expression("a + 1");
+        assert_eq!(body(&render(&tree, &DotOptions::default())), vec![
+            "n [label=\"Token\\n+\"];",
+            "n_0 [label=\"Identifier\\na\"];",
+            "n -> n_0;",
+            "n_1 [label=\"Token\\n1\"];",
+            "n -> n_1;",
+        ]);
+    }
+
+    #[test]
+    fn render_test2() {
+        // Quotes in string literals are escaped
+        let tree = Parser::default().parse_expression("f(\"x\")");
+        let dot = render(&tree, &DotOptions::default());
+        assert!(dot.contains("n_1_0_0 [label=\"Token\\n\\\"x\\\"\"];"));
+        assert!(dot.contains("n_1 [label=\"FunctionCallArguments\"];"));
+    }
+
+    #[test]
+    fn render_test3() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract A { uint x }"));
+        let dot = render(&tree, &DotOptions::default());
+        let error = dot.lines().find(|line| line.contains("Error")).unwrap();
+        assert_eq!(error.trim(), "n_0_1_0_2 [label=\"Error\\nexpected `;`, found `}`\", color=red, fontcolor=red];");
+        assert!(!dot.lines().any(|line| line.contains("Identifier") && line.contains("red")));
+    }
+
+    /*** Options ***/
+
+    #[test]
+    fn max_depth_test1() {
+        let tree = Parser::default().parse_expression("a + b * c");
+        let options = DotOptions { max_depth: Some(1), ..DotOptions::default() };
+        assert_eq!(body(&render(&tree, &options)), vec![
+            "n [label=\"Token\\n+\"];",
+            "n_0 [label=\"Identifier\\na\"];",
+            "n -> n_0;",
+            "n_1 [label=\"Token\\n*\"];",
+            "n_1_hidden [label=\"2 more nodes\", style=dashed];",
+            "n_1 -> n_1_hidden [style=dashed];",
+            "n -> n_1;",
+        ]);
+        let options = DotOptions { max_depth: Some(0), ..DotOptions::default() };
+        assert_eq!(body(&render(&tree, &options))[1], "n_hidden [label=\"4 more nodes\", style=dashed];");
+    }
+
+    #[test]
+    fn roots_test1() {
+        let tree = Parser::default().parse_expression("a + b * c");
+        let options = DotOptions { roots: vec![NodeId::root().child(1)], ..DotOptions::default() };
+        assert_eq!(body(&render(&tree, &options))[0], "n_1 [label=\"Token\\n*\"];");
+        // Roots inside other roots aren't drawn twice
+        let options = DotOptions { roots: vec![NodeId::root().child(1), NodeId::root().child(1).child(0)], ..DotOptions::default() };
+        assert_eq!(body(&render(&tree, &options)).len(), 5);
+        // Nor are roots that are given twice
+        let options = DotOptions { roots: vec![NodeId::root().child(1), NodeId::root().child(1)], ..DotOptions::default() };
+        assert_eq!(body(&render(&tree, &options)).len(), 5);
+        // Roots that aren't in the tree are skipped
+        let options = DotOptions { roots: vec![NodeId::root().child(5)], ..DotOptions::default() };
+        assert_eq!(body(&render(&tree, &options)), Vec::<&str>::new());
+    }
+}
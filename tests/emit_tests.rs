@@ -0,0 +1,174 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod emit_tests {
+    use serde_json::Value;
+    use solfix::codes;
+    use solfix::diagnostics::{ Diagnostic, Span, Suggestion };
+    use solfix::emit::{ self, FileDiagnostics, Format };
+    use solfix::parse_4_25::parse;
+    use solfix::report::{ self, RenderOptions };
+    use solfix::source_map::SourceMap;
+
+    /*** Helpers ***/
+
+    // Parses source as path and collects its diagnostics
+    fn check(sources: &mut SourceMap, path: &str, source: &str) -> FileDiagnostics {
+        let (file, tree) = parse(sources, path, String::from(source));
+        let diagnostics = report::diagnostics(sources.file(file), &tree);
+        FileDiagnostics { file, diagnostics }
+    }
+
+    // A file with an error that has a note, help and a suggestion, which the parser doesn't give
+    fn annotated(sources: &mut SourceMap, path: &str) -> FileDiagnostics {
+        let file = sources.add(path, String::from("contract A {\n    uint x = 1\n}\n"));
+        let diagnostic = Diagnostic::error(String::from("expected `;`, found `}`"), Span::new(27, 27))
+            .with_code(codes::EXPECTED_SEMICOLON)
+            .with_note(String::from("statements end with `;`"))
+            .with_help(String::from("add a `;`"))
+            .with_suggestion(Suggestion { message: String::from("insert `;`"), span: Span::new(27, 27), replacement: String::from(";") });
+        FileDiagnostics { file, diagnostics: vec![diagnostic] }
+    }
+
+    /*** Format Tests ***/
+
+    #[test]
+    fn format_test1() {
+        assert_eq!(Format::from_name("sarif"), Some(Format::Sarif));
+        assert_eq!(Format::from_name("github"), Some(Format::Github));
+        assert_eq!(Format::from_name("xml"), None);
+        let mut sources = SourceMap::new();
+        let file = check(&mut sources, "A.sol", "contract A {\n    uint x = 1\n}\n");
+        let human = Format::Human.emit(&sources, std::slice::from_ref(&file), &RenderOptions::default());
+        assert_eq!(human, format!("{}\n", report::render(&file.diagnostics[0], sources.file(file.file), &RenderOptions::default())));
+    }
+
+    /*** SARIF Tests ***/
+
+    #[test]
+    fn sarif_test1() {
+        let mut sources = SourceMap::new();
+        let file = check(&mut sources, "A.sol", "contract A {\n    uint x = 1\n}\n");
+        let log = emit::sarif(&sources, &[file]);
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "solfix");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), codes::catalog().len());
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "SF0001");
+        assert_eq!(run["tool"]["driver"]["informationUri"], "https://github.com/jalextowle/solidity-fix");
+        assert_eq!(run["columnKind"], "unicodeCodePoints");
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "SF0001");
+        assert_eq!(result["ruleIndex"], 0);
+        assert_eq!(result["level"], "error");
+        assert_eq!(result["message"]["text"], "expected `;`, found `}`");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "A.sol");
+        assert_eq!(location["region"]["startLine"], 2);
+        assert_eq!(location["region"]["startColumn"], 15);
+        assert_eq!(location["region"]["charOffset"], 27);
+        assert_eq!(result.get("fixes"), None);
+    }
+
+    #[test]
+    fn sarif_test2() {
+        let mut sources = SourceMap::new();
+        let file = annotated(&mut sources, "A.sol");
+        let log = emit::sarif(&sources, &[file]);
+        let result = &log["runs"][0]["results"][0];
+        assert_eq!(result["message"]["text"], "expected `;`, found `}`\nnote: statements end with `;`\nhelp: add a `;`");
+        let fix = &result["fixes"][0];
+        assert_eq!(fix["description"]["text"], "insert `;`");
+        let replacement = &fix["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["deletedRegion"]["charOffset"], 27);
+        assert_eq!(replacement["deletedRegion"]["charLength"], 0);
+        assert_eq!(replacement["insertedContent"]["text"], ";");
+    }
+
+    #[test]
+    fn sarif_test3() {
+        let mut sources = SourceMap::new();
+        let file = check(&mut sources, "A.sol", "contract A {}\n");
+        let log = emit::sarif(&sources, &[file]);
+        assert_eq!(log["runs"][0]["results"], Value::Array(vec![]));
+    }
+
+    #[test]
+    fn sarif_test4() {
+        // Each rule's default level is the severity of its code
+        let log = emit::sarif(&SourceMap::new(), &[]);
+        let rules = log["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap();
+        let level = |code: codes::Code| rules.iter().find(|rule| rule["id"] == code.to_string()).unwrap()["defaultConfiguration"]["level"].clone();
+        assert_eq!(level(codes::EXPECTED_SEMICOLON), "error");
+        assert_eq!(level(codes::MISSPELLED_KEYWORD), "warning");
+    }
+
+    /*** JSON Tests ***/
+
+    #[test]
+    fn json_test1() {
+        let mut sources = SourceMap::new();
+        let files = [check(&mut sources, "A.sol", "contract A {\n    uint x = 1\n}\n"), annotated(&mut sources, "B.sol")];
+        let text = emit::json_lines(&sources, &files);
+        let lines = text.lines().map(|line| serde_json::from_str::<Value>(line).unwrap()).collect::<Vec<Value>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["file"], "A.sol");
+        assert_eq!(lines[0]["line"], 2);
+        assert_eq!(lines[0]["column"], 15);
+        assert_eq!(lines[0]["severity"], "error");
+        assert_eq!(lines[0]["code"], "SF0001");
+        assert_eq!(lines[0]["help"], Value::Null);
+        assert_eq!(lines[1]["notes"][0], "statements end with `;`");
+        assert_eq!(lines[1]["suggestions"][0]["replacement"], ";");
+        assert_eq!(lines[1]["suggestions"][0]["line"], 2);
+    }
+
+    /*** Checkstyle Tests ***/
+
+    #[test]
+    fn checkstyle_test1() {
+        let mut sources = SourceMap::new();
+        let files = [check(&mut sources, "A.sol", "contract A {\n    uint x = 1\n}\n"), check(&mut sources, "B.sol", "contract B {}\n")];
+        let xml = emit::checkstyle(&sources, &files);
+        let expected = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<checkstyle version=\"4.3\">\n",
+            "  <file name=\"A.sol\">\n",
+            "    <error line=\"2\" column=\"15\" severity=\"error\" message=\"expected `;`, found `}`\" source=\"solfix.SF0001\"/>\n",
+            "  </file>\n",
+            "  <file name=\"B.sol\">\n",
+            "  </file>\n",
+            "</checkstyle>\n"
+        );
+        assert_eq!(xml, expected);
+    }
+
+    #[test]
+    fn checkstyle_test2() {
+        let mut sources = SourceMap::new();
+        let mut file = annotated(&mut sources, "<A & \"B\">.sol");
+        file.diagnostics[0].code = None;
+        let xml = emit::checkstyle(&sources, &[file]);
+        assert!(xml.contains("<file name=\"&lt;A &amp; &quot;B&quot;&gt;.sol\">"));
+        assert!(xml.contains("message=\"expected `;`, found `}`\"/>"));
+    }
+
+    /*** GitHub Tests ***/
+
+    #[test]
+    fn github_test1() {
+        let mut sources = SourceMap::new();
+        let file = check(&mut sources, "A.sol", "contract A {\n    uint x = 1\n}\n");
+        let text = emit::github(&sources, &[file]);
+        assert_eq!(text, "::error file=A.sol,line=2,col=15,endLine=2,endColumn=15,title=SF0001::expected `;`, found `}`\n");
+    }
+
+    #[test]
+    fn github_test2() {
+        let mut sources = SourceMap::new();
+        let mut file = annotated(&mut sources, "a, b.sol");
+        file.diagnostics[0].message = String::from("100% wrong");
+        let text = emit::github(&sources, &[file]);
+        assert_eq!(text, "::error file=a%2C b.sol,line=2,col=15,endLine=2,endColumn=15,title=SF0001::100%25 wrong%0Anote: statements end with `;`%0Ahelp: add a `;`\n");
+    }
+}
@@ -0,0 +1,205 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod incremental_tests {
+    use solfix::diagnostics::Span;
+    use solfix::incremental::{ ParsedSource, TextEdit };
+    use solfix::navigation::NodeId;
+    use solfix::parse_4_25::{ NonTerminal, ParseTree };
+    use solfix::parser::{ Parser, ParserOptions };
+    use std::fs;
+
+    /*** Helpers ***/
+
+    const SOURCE: &str = "pragma solidity ^0.4.25;
+
+contract Token is Owned {
+    struct Account { uint256 balance; uint8 flags; }
+    mapping(address => Account) accounts;
+    event Transfer(address from, address to, uint256 value);
+
+    /* The total supply is fixed */
+    function total() public view returns (uint256) {
+        return 1000 * 10 ** 18;
+    }
+
+    function transfer(address to, uint256 value) public {
+        accounts[msg.sender].balance -= value;
+        accounts[to].balance += value;
+        emit Transfer(msg.sender, to, value);
+    }
+}
+";
+
+    const REPLACEMENTS: [&str; 16] = [
+        "", " ", "\n", "{", "}", "(", ")", ";", "0", "1 + 2", "uint8 x;", "function g() { }",
+        "/*", "*/", "// note\n", "mapping(uint => uint) m;",
+    ];
+
+    // A small linear congruential generator, so that the edit sequences are the same on every run
+    struct Random(u64);
+
+    impl Random {
+        fn below(&mut self, bound: usize) -> usize {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((self.0 >> 33) as usize) % bound.max(1)
+        }
+    }
+
+    fn random_edit(random: &mut Random, source: &str) -> TextEdit {
+        let len = source.chars().count();
+        let start = random.below(len + 1);
+        let end = start + random.below((len - start).min(8) + 1);
+        TextEdit::new(Span::new(start, end), REPLACEMENTS[random.below(REPLACEMENTS.len())])
+    }
+
+    fn assert_edits_match(parser: &Parser, source: &str, seed: u64, edits: usize) {
+        let mut random = Random(seed);
+        let mut parsed = parser.parse_reusable(source);
+        for _ in 0..edits {
+            let edit = random_edit(&mut random, &parsed.source());
+            parsed = parser.reparse(&parsed, &edit);
+            let expected = parser.parse_source_unit(&parsed.source());
+            assert_eq!(&expected, parsed.tree(), "after {:?} the source is\n{}", edit, parsed.source());
+        }
+    }
+
+    fn reparse_at(parsed: &ParsedSource, needle: &str, replacement: &str) -> ParsedSource {
+        let source = parsed.source();
+        let start = source.find(needle).unwrap();
+        let edit = TextEdit::new(Span::new(start, start + needle.len()), replacement);
+        Parser::default().reparse(parsed, &edit)
+    }
+
+    fn function_id(parsed: &ParsedSource, name: &str) -> NodeId {
+        parsed.tree().preorder().find(|(_, tree)| tree.function_name() == Some(name)).unwrap().0
+    }
+
+    fn function<'a>(parsed: &'a ParsedSource, name: &str) -> &'a ParseTree {
+        parsed.tree().node(&function_id(parsed, name)).unwrap()
+    }
+
+    /*** Random Edits ***/
+
+    #[test]
+    fn random_edits_test1() {
+        assert_edits_match(&Parser::default(), SOURCE, 1, 30);
+    }
+
+    #[test]
+    fn random_edits_test2() {
+        let source = fs::read_to_string("contracts/SafeMath.sol").unwrap();
+        assert_edits_match(&Parser::default(), &source, 2, 8);
+    }
+
+    #[test]
+    fn random_edits_test3() {
+        let options = ParserOptions { error_recovery: false, max_depth: 3, ..ParserOptions::default() };
+        assert_edits_match(&Parser::new(options), SOURCE, 3, 30);
+    }
+
+    /*** Reuse ***/
+
+    #[test]
+    fn reuse_test1() {
+        let parsed = Parser::default().parse_reusable(SOURCE);
+        assert_eq!(parsed.reused_nodes(), 0);
+        // Only the body of transfer reads the edited statement
+        let edited = reparse_at(&parsed, "accounts[to]", "accounts[from]");
+        assert_eq!(edited.reused_nodes(), 4);
+        assert_eq!(&Parser::default().parse_source_unit(&edited.source()), edited.tree());
+    }
+
+    #[test]
+    fn reuse_test2() {
+        let parsed = Parser::default().parse_reusable("contract A { function f() { while (a) { } } }");
+        // The diagnostics of reused members are moved along with them
+        let edited = reparse_at(&parsed, "contract A", "contract A /* edited */");
+        assert!(edited.reused_nodes() > 0);
+        let expected = Parser::default().parse_source_unit(&edited.source());
+        assert_eq!(expected.diagnostics()[0].span, Span::new(41, 46));
+        assert_eq!(&expected, edited.tree());
+    }
+
+    #[test]
+    fn reuse_test3() {
+        let parsed = Parser::default().parse_reusable(SOURCE);
+        let options = ParserOptions { max_depth: 8, ..ParserOptions::default() };
+        let edit = TextEdit::new(Span::new(0, 0), " ");
+        let edited = Parser::new(options).reparse(&parsed, &edit);
+        assert_eq!(edited.reused_nodes(), 0);
+    }
+
+    #[test]
+    fn reuse_test4() {
+        // Editing a token in a large source only lexes the declarations around it again
+        let functions = (0..50).map(|i| format!("    function f{}(uint a) public {{ x = a + {}; }}\n", i, i)).collect::<String>();
+        let source = format!("contract A {{\n    struct S {{ uint a; }}\n{}}}\n", functions);
+        let parsed = Parser::default().parse_reusable(&source);
+        assert_eq!(parsed.rescanned_chars(), source.len());
+        let edited = reparse_at(&parsed, "x = a + 25;", "x = a + 26;");
+        // From the `function` keyword of f25 to that of f26, and everything but f25 is reused
+        assert_eq!(edited.rescanned_chars(), "function f25(uint a) public { x = a + 25; }\n    ".len());
+        assert_eq!(edited.reused_nodes(), 50);
+        assert_eq!(&Parser::default().parse_source_unit(&edited.source()), edited.tree());
+        // Renaming a declaration is found too, and the struct is no longer a mapping key
+        let edited = reparse_at(&edited, "struct S", "struct T");
+        let edited = reparse_at(&edited, "function f5(uint a)", "function f5(mapping(S => uint) a)");
+        assert_eq!(&Parser::default().parse_source_unit(&edited.source()), edited.tree());
+    }
+
+    /*** Stable Ids ***/
+
+    #[test]
+    fn stable_id_test1() {
+        let parsed = Parser::default().parse_reusable(SOURCE);
+        let transfer = parsed.stable_id(&function_id(&parsed, "transfer")).unwrap();
+        let body_id = parsed.tree().id_of(function(&parsed, "transfer").function_body().unwrap()).unwrap();
+        let body = parsed.stable_id(&body_id).unwrap();
+        let statement = parsed.stable_id(&body_id.child(0)).unwrap();
+        assert_eq!(parsed.stable_node(&body).unwrap().root, NonTerminal::Block);
+        assert_eq!(parsed.stable_parent(&statement), Some(body.clone()));
+        assert_eq!(parsed.stable_parent(&body), Some(transfer.clone()));
+        // Adding a member before transfer moves it in the tree, but its stable id still finds it
+        let start = SOURCE.find("function transfer").unwrap();
+        let edited = Parser::default().reparse(&parsed, &TextEdit::new(Span::new(start, start), "function g() { }\n    "));
+        assert_ne!(function_id(&edited, "transfer"), function_id(&parsed, "transfer"));
+        assert_eq!(edited.node_id(&transfer), Some(function_id(&edited, "transfer")));
+        assert_eq!(edited.stable_node(&statement), parsed.stable_node(&statement));
+        // Editing the body parses transfer again, and it keeps its id along with its body
+        let edited = reparse_at(&edited, "accounts[to]", "accounts[owner]");
+        assert_eq!(edited.stable_node(&transfer), Some(function(&edited, "transfer")));
+        assert_eq!(edited.stable_parent(&body), Some(transfer.clone()));
+        assert_eq!(edited.stable_node(&statement), parsed.stable_node(&statement));
+        // Renaming transfer doesn't change its first token, so it keeps its id too
+        let edited = reparse_at(&edited, "transfer(", "moved(");
+        assert_eq!(edited.stable_node(&transfer), Some(function(&edited, "moved")));
+        let g = edited.stable_id(&function_id(&edited, "g")).unwrap();
+        assert!(g != transfer && g != body);
+        // Replacing its first token gives the member a new id, but its body is still reused
+        let edited = reparse_at(&edited, "function moved", "function other");
+        assert_eq!(edited.stable_node(&transfer), None);
+        let other = edited.stable_id(&function_id(&edited, "other")).unwrap();
+        assert_eq!(edited.stable_parent(&body), Some(other));
+        assert_eq!(edited.stable_node(&statement), parsed.stable_node(&statement));
+    }
+
+    #[test]
+    fn stable_id_test2() {
+        // The nodes that an edit doesn't touch are found by their stable ids after it
+        let parser = Parser::default();
+        let mut random = Random(4);
+        let mut parsed = parser.parse_reusable(SOURCE);
+        for _ in 0..20 {
+            let ids = parsed.tree().preorder().map(|(id, _)| parsed.stable_id(&id).unwrap()).collect::<Vec<_>>();
+            let edit = random_edit(&mut random, &parsed.source());
+            let edited = parser.reparse(&parsed, &edit);
+            for id in ids.iter().filter(|id| edited.stable_node(id).is_some()) {
+                assert_eq!(edited.stable_id(&edited.node_id(id).unwrap()).as_ref(), Some(id));
+            }
+            let reused = ids.iter().filter(|id| edited.stable_node(id).is_some_and(|node| parsed.stable_node(id) == Some(node))).count();
+            assert!(reused > 0 || edited.tree().leaves.is_empty(), "after {:?} the source is\n{}", edit, edited.source());
+            parsed = edited;
+        }
+    }
+}
@@ -9,7 +9,7 @@ mod lexer_tests {
     }
 
     fn expect_next_token(s: &Vec<char>, cur: &mut usize, t: lex_4_25::Token) {
-        match lex_4_25::next_token(&s, cur) {
+        match lex_4_25::next_token(s, cur) {
             ref next if *next == t => (),
             actual => fail_test(t, actual)
         };
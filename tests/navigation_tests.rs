@@ -0,0 +1,134 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod navigation_tests {
+    use solfix::lex_4_25;
+    use solfix::navigation::NodeId;
+    use solfix::parse_4_25::{ NonTerminal, parse, ParseTree };
+    use solfix::source_map::SourceMap;
+
+    /*** Helpers ***/
+
+    const SOURCE: &str = "
+contract Owned {
+    address owner;
+    function owned() public { owner = msg.sender; }
+}
+
+contract Token is Owned {
+    uint256 supply;
+    constructor() public { }
+    function total() public view returns (uint256) { return supply; }
+    function burn(uint256 amount) public;
+}
+";
+
+    fn token_contract(tree: &ParseTree) -> &ParseTree {
+        tree.find_all(|node| node.contract_name() == Some("Token"))[0]
+    }
+
+    /*** Traversal ***/
+
+    #[test]
+    fn preorder_test1() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract A { uint a; }"));
+        let roots = tree.preorder().map(|(_, node)| node.root.clone()).collect::<Vec<_>>();
+        assert_eq!(roots[0], NonTerminal::SourceUnit);
+        assert_eq!(roots[1], NonTerminal::ContractDefinition(lex_4_25::Token::Contract));
+        assert_eq!(roots[2], NonTerminal::Identifier(String::from("A")));
+        assert_eq!(roots[3], NonTerminal::ContractPart);
+        assert_eq!(roots[4], NonTerminal::StateVariableDeclaration);
+        assert_eq!(roots.len(), tree.postorder().count());
+    }
+
+    #[test]
+    fn postorder_test1() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract A { uint a; }"));
+        let roots = tree.postorder().map(|(_, node)| node.root.clone()).collect::<Vec<_>>();
+        assert_eq!(roots[0], NonTerminal::Identifier(String::from("A")));
+        assert_eq!(roots[roots.len() - 3], NonTerminal::ContractPart);
+        assert_eq!(roots[roots.len() - 2], NonTerminal::ContractDefinition(lex_4_25::Token::Contract));
+        assert_eq!(roots[roots.len() - 1], NonTerminal::SourceUnit);
+    }
+
+    #[test]
+    fn find_all_test1() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from(SOURCE));
+        let functions = token_contract(&tree).find_all(|node| node.root == NonTerminal::FunctionDefinition);
+        let names = functions.iter().map(|function| function.function_name()).collect::<Vec<_>>();
+        assert_eq!(names, vec![Some("total"), Some("burn")]);
+    }
+
+    #[test]
+    fn children_of_test1() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from(SOURCE));
+        let kind = NonTerminal::ContractDefinition(lex_4_25::Token::Contract);
+        assert_eq!(tree.children_of(&kind).len(), 2);
+        assert!(tree.children_of(&NonTerminal::ContractPart).is_empty());
+    }
+
+    #[test]
+    fn first_descendant_test1() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from(SOURCE));
+        let block = token_contract(&tree).first_descendant(&NonTerminal::Block).unwrap();
+        assert!(block.leaves.is_empty());
+        assert!(block.first_descendant(&NonTerminal::Block).is_none());
+    }
+
+    /*** Node Ids ***/
+
+    #[test]
+    fn node_id_test1() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from(SOURCE));
+        let (id, supply) = tree.preorder()
+            .find(|(_, node)| node.root == NonTerminal::Identifier(String::from("supply")))
+            .unwrap();
+        assert_eq!(id.path(), &[1, 2, 0, 1]);
+        assert!(std::ptr::eq(tree.node(&id).unwrap(), supply));
+        assert_eq!(tree.id_of(supply), Some(id.clone()));
+        assert_eq!(tree.parent_of(&id).unwrap().root, NonTerminal::StateVariableDeclaration);
+        // Ids don't depend on where the tree is stored
+        let copy = tree.clone();
+        assert_eq!(copy.node(&id), Some(supply));
+        assert!(copy.id_of(supply).is_none());
+    }
+
+    #[test]
+    fn node_id_test2() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from(SOURCE));
+        assert_eq!(NodeId::root().parent(), None);
+        assert_eq!(tree.node(&NodeId::root()), Some(&tree));
+        assert_eq!(tree.parent_of(&NodeId::root()), None);
+        assert_eq!(tree.node(&NodeId::root().child(5)), None);
+        assert_eq!(NodeId::root().child(1).child(2).parent(), Some(NodeId::root().child(1)));
+    }
+
+    /*** Accessors ***/
+
+    #[test]
+    fn contract_accessors_test1() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from(SOURCE));
+        let names = tree.leaves.iter().map(|contract| contract.contract_name()).collect::<Vec<_>>();
+        assert_eq!(names, vec![Some("Owned"), Some("Token")]);
+        let members = token_contract(&tree).contract_members();
+        assert_eq!(members.len(), 4);
+        assert_eq!(members[1].root, NonTerminal::ConstructorDefinition);
+        assert_eq!(members[0].contract_name(), None);
+    }
+
+    #[test]
+    fn function_accessors_test1() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from(SOURCE));
+        let members = token_contract(&tree).contract_members();
+        let (constructor, total, burn) = (members[1], members[2], members[3]);
+        assert_eq!(constructor.function_name(), None);
+        assert!(constructor.function_body().is_some());
+        assert!(constructor.function_returns().is_none());
+        assert_eq!(total.function_name(), Some("total"));
+        assert!(total.function_parameters().unwrap().leaves.is_empty());
+        assert_eq!(total.function_returns().unwrap().leaves.len(), 1);
+        assert_eq!(burn.function_parameters().unwrap().leaves.len(), 1);
+        assert!(burn.function_body().is_none());
+        assert_eq!(members[0].function_parameters(), None);
+    }
+}
@@ -3,13 +3,16 @@ extern crate solfix;
 #[cfg(test)] mod parse_expression_tests {
     use solfix::lex_4_25;
     use solfix::parse_4_25::{ NonTerminal, parse_expression, ParseTree };
+    use solfix::parser::{ Context, ParserOptions };
 
     /*** Helpers ***/
 
+    // ParseTree stores its leaves boxed, so the helper takes them in the same form
+    #[allow(clippy::vec_box)]
     fn boxed_parse_tree(root: NonTerminal, leaves: Vec<Box<ParseTree>>) -> Box<ParseTree> {
         Box::new(ParseTree {
-            root: root,
-            leaves: leaves,
+            root,
+            leaves,
         })
     }
 
@@ -34,10 +37,6 @@ extern crate solfix;
         }
     }
 
-    trait PrettyAssertions<T> {
-        fn assert_equal_pretty(self, other: T);
-    }
-
     macro_rules! assert_eq_pretty {
         ($i1:ident, $i2:ident) => {
             assert_eq!($i1, $i2, "Expected\n========\n{:#?}\nActual\n======\n{:#?}", $i1, $i2);
@@ -48,7 +47,7 @@ extern crate solfix;
 
     #[test]
     fn addition_parsing_test() {
-        let actual_tree = parse_expression(&str_to_chars("1500 + 0x000"), &mut 0);
+        let actual_tree = parse_expression(&str_to_chars("1500 + 0x000"), &mut 0, &Context::new(&ParserOptions::default()));
         let expected_tree = ParseTree {
             root: NonTerminal::Token(lex_4_25::Token::Plus),
             leaves: vec![
@@ -61,7 +60,7 @@ extern crate solfix;
 
     #[test]
     fn multiplication_parsing_test() {
-        let actual_tree = parse_expression(&str_to_chars("1500 * 0x000"), &mut 0);
+        let actual_tree = parse_expression(&str_to_chars("1500 * 0x000"), &mut 0, &Context::new(&ParserOptions::default()));
         let expected_tree = ParseTree {
             root: NonTerminal::Token(lex_4_25::Token::Multiply),
             leaves: vec![
@@ -74,7 +73,7 @@ extern crate solfix;
 
     #[test]
     fn arithmetic_parsing_test1() {
-        let actual_tree = parse_expression(&str_to_chars("(800 + 1500) * 0x000"), &mut 0);
+        let actual_tree = parse_expression(&str_to_chars("(800 + 1500) * 0x000"), &mut 0, &Context::new(&ParserOptions::default()));
         let expected_tree = ParseTree {
             root: NonTerminal::Token(lex_4_25::Token::Multiply),
             leaves: vec![
@@ -93,7 +92,7 @@ extern crate solfix;
 
     #[test]
     fn arithmetic_parsing_test2() {
-        let actual_tree = parse_expression(&str_to_chars("800 + 1500 * 0x000"), &mut 0);
+        let actual_tree = parse_expression(&str_to_chars("800 + 1500 * 0x000"), &mut 0, &Context::new(&ParserOptions::default()));
         let expected_tree = ParseTree {
             root: NonTerminal::Token(lex_4_25::Token::Plus),
             leaves: vec![
@@ -112,7 +111,7 @@ extern crate solfix;
 
     #[test]
     fn arithmetic_parsing_test3() {
-        let actual_tree = parse_expression(&str_to_chars("800 * 1500 + 0x000"), &mut 0);
+        let actual_tree = parse_expression(&str_to_chars("800 * 1500 + 0x000"), &mut 0, &Context::new(&ParserOptions::default()));
         let expected_tree = ParseTree {
             root: NonTerminal::Token(lex_4_25::Token::Plus),
             leaves: vec![
@@ -131,7 +130,7 @@ extern crate solfix;
 
     #[test]
     fn arithmetic_parsing_test4() {
-        let actual_tree = parse_expression(&str_to_chars("800 * 1500 + 0x000 * 0x800"), &mut 0);
+        let actual_tree = parse_expression(&str_to_chars("800 * 1500 + 0x000 * 0x800"), &mut 0, &Context::new(&ParserOptions::default()));
         let expected_tree = ParseTree {
             root: NonTerminal::Token(lex_4_25::Token::Plus),
             leaves: vec![
@@ -156,7 +155,7 @@ extern crate solfix;
 
     #[test]
     fn arithmetic_parsing_test5() {
-        let actual_tree = parse_expression(&str_to_chars("1 - 800 * 1500 + 0x000 * 0x800 / 5 ** 1800"), &mut 0);
+        let actual_tree = parse_expression(&str_to_chars("1 - 800 * 1500 + 0x000 * 0x800 / 5 ** 1800"), &mut 0, &Context::new(&ParserOptions::default()));
         let expected_tree = ParseTree {
             root: NonTerminal::Token(lex_4_25::Token::Plus),
             leaves: vec![
@@ -197,11 +196,155 @@ extern crate solfix;
         assert_eq_pretty!(expected_tree, actual_tree);
     }
 
+    #[test]
+    fn arithmetic_parsing_test6() {
+        let actual_tree = parse_expression(&str_to_chars("2 * (3 - 1)"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Multiply),
+            leaves: vec![
+                lex_4_25::Token::DecimalNumber(String::from("2")).to_boxed_leaf(),
+                boxed_parse_tree(
+                    NonTerminal::Token(lex_4_25::Token::Minus),
+                    vec![
+                        lex_4_25::Token::DecimalNumber(String::from("3")).to_boxed_leaf(),
+                        lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf(),
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn arithmetic_parsing_test7() {
+        let actual_tree = parse_expression(&str_to_chars("1 - 2 - 3 - 4"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Minus),
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::Token(lex_4_25::Token::Minus),
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::Token(lex_4_25::Token::Minus),
+                            vec![
+                                lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf(),
+                                lex_4_25::Token::DecimalNumber(String::from("2")).to_boxed_leaf(),
+                            ]
+                        ),
+                        lex_4_25::Token::DecimalNumber(String::from("3")).to_boxed_leaf(),
+                    ]
+                ),
+                lex_4_25::Token::DecimalNumber(String::from("4")).to_boxed_leaf(),
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn arithmetic_parsing_test8() {
+        let actual_tree = parse_expression(&str_to_chars("-a ** 2 + b"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Plus),
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::Token(lex_4_25::Token::Power),
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::Token(lex_4_25::Token::Minus),
+                            vec![
+                                NonTerminal::Identifier(String::from("a")).to_boxed_leaf()
+                            ]
+                        ),
+                        lex_4_25::Token::DecimalNumber(String::from("2")).to_boxed_leaf(),
+                    ]
+                ),
+                NonTerminal::Identifier(String::from("b")).to_boxed_leaf()
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn arithmetic_parsing_test9() {
+        let chain = vec!["1"; 200].join(" + ");
+        let actual_tree = parse_expression(&str_to_chars(&chain), &mut 0, &Context::new(&ParserOptions::default()));
+        assert!(!actual_tree.has_errors());
+        // Long chains are folded to the left
+        let mut depth = 0;
+        let mut tree = &actual_tree;
+        while let NonTerminal::Token(lex_4_25::Token::Plus) = tree.root {
+            assert_eq!(tree.leaves[1].root, NonTerminal::Token(lex_4_25::Token::DecimalNumber(String::from("1"))));
+            tree = &tree.leaves[0];
+            depth += 1;
+        }
+        assert_eq!(depth, 199);
+    }
+
+    /*** Assignment ***/
+
+    #[test]
+    fn assignment_parsing_test1() {
+        let actual_tree = parse_expression(&str_to_chars("a = b += c || d"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Assignment),
+            leaves: vec![
+                NonTerminal::Identifier(String::from("a")).to_boxed_leaf(),
+                boxed_parse_tree(
+                    NonTerminal::Token(lex_4_25::Token::PlusEquals),
+                    vec![
+                        NonTerminal::Identifier(String::from("b")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::Token(lex_4_25::Token::LogicalOr),
+                            vec![
+                                NonTerminal::Identifier(String::from("c")).to_boxed_leaf(),
+                                NonTerminal::Identifier(String::from("d")).to_boxed_leaf()
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn conditional_parsing_test1() {
+        let actual_tree = parse_expression(&str_to_chars("x = a && b ? 1 : c ? 2 : 3"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Assignment),
+            leaves: vec![
+                NonTerminal::Identifier(String::from("x")).to_boxed_leaf(),
+                boxed_parse_tree(
+                    NonTerminal::Token(lex_4_25::Token::Question),
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::Token(lex_4_25::Token::LogicalAnd),
+                            vec![
+                                NonTerminal::Identifier(String::from("a")).to_boxed_leaf(),
+                                NonTerminal::Identifier(String::from("b")).to_boxed_leaf()
+                            ]
+                        ),
+                        lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::Token(lex_4_25::Token::Question),
+                            vec![
+                                NonTerminal::Identifier(String::from("c")).to_boxed_leaf(),
+                                lex_4_25::Token::DecimalNumber(String::from("2")).to_boxed_leaf(),
+                                lex_4_25::Token::DecimalNumber(String::from("3")).to_boxed_leaf()
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
     /*** Function call ***/
 
     #[test]
     fn function_call_test1() {
-        let actual_tree = parse_expression(&str_to_chars("Identifier()"), &mut 0);
+        let actual_tree = parse_expression(&str_to_chars("Identifier()"), &mut 0, &Context::new(&ParserOptions::default()));
         let expected_tree = ParseTree {
             root: NonTerminal::FunctionCall,
             leaves: vec![
@@ -224,7 +367,7 @@ extern crate solfix;
 
     #[test]
     fn function_call_test2() {
-        let actual_tree = parse_expression(&str_to_chars("add(1, 2)"), &mut 0);
+        let actual_tree = parse_expression(&str_to_chars("add(1, 2)"), &mut 0, &Context::new(&ParserOptions::default()));
         let expected_tree = ParseTree {
             root: NonTerminal::FunctionCall,
             leaves: vec![
@@ -245,4 +388,375 @@ extern crate solfix;
         };
         assert_eq_pretty!(expected_tree, actual_tree);
     }
+
+    #[test]
+    fn function_call_test3() {
+        let actual_tree = parse_expression(&str_to_chars("token.transfer.value(1).gas(2)(to)"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::FunctionCall,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::FunctionCall,
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::MemberAccess,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::FunctionCall,
+                                    vec![
+                                        boxed_parse_tree(
+                                            NonTerminal::MemberAccess,
+                                            vec![
+                                                boxed_parse_tree(
+                                                    NonTerminal::MemberAccess,
+                                                    vec![
+                                                        NonTerminal::Identifier(String::from("token")).to_boxed_leaf(),
+                                                        NonTerminal::Identifier(String::from("transfer")).to_boxed_leaf()
+                                                    ]
+                                                ),
+                                                NonTerminal::Identifier(String::from("value")).to_boxed_leaf()
+                                            ]
+                                        ),
+                                        boxed_parse_tree(
+                                            NonTerminal::FunctionCallArguments,
+                                            vec![
+                                                boxed_parse_tree(
+                                                    NonTerminal::ExpressionList,
+                                                    vec![
+                                                        lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf()
+                                                    ]
+                                                )
+                                            ]
+                                        )
+                                    ]
+                                ),
+                                NonTerminal::Identifier(String::from("gas")).to_boxed_leaf()
+                            ]
+                        ),
+                        boxed_parse_tree(
+                            NonTerminal::FunctionCallArguments,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::ExpressionList,
+                                    vec![
+                                        lex_4_25::Token::DecimalNumber(String::from("2")).to_boxed_leaf()
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                ),
+                boxed_parse_tree(
+                    NonTerminal::FunctionCallArguments,
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::ExpressionList,
+                            vec![
+                                NonTerminal::Identifier(String::from("to")).to_boxed_leaf()
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn function_call_test4() {
+        let actual_tree = parse_expression(&str_to_chars("balanceOf(owner) + 1"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Plus),
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::FunctionCall,
+                    vec![
+                        NonTerminal::Identifier(String::from("balanceOf")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::FunctionCallArguments,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::ExpressionList,
+                                    vec![
+                                        NonTerminal::Identifier(String::from("owner")).to_boxed_leaf()
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                ),
+                lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf()
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn index_access_test1() {
+        let actual_tree = parse_expression(&str_to_chars("balances[owner] - amounts[1 + i]"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Minus),
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::IndexAccess,
+                    vec![
+                        NonTerminal::Identifier(String::from("balances")).to_boxed_leaf(),
+                        NonTerminal::Identifier(String::from("owner")).to_boxed_leaf()
+                    ]
+                ),
+                boxed_parse_tree(
+                    NonTerminal::IndexAccess,
+                    vec![
+                        NonTerminal::Identifier(String::from("amounts")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::Token(lex_4_25::Token::Plus),
+                            vec![
+                                lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf(),
+                                NonTerminal::Identifier(String::from("i")).to_boxed_leaf()
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    /*** New ***/
+
+    #[test]
+    fn new_expression_test1() {
+        let actual_tree = parse_expression(&str_to_chars("new Token(1)"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::FunctionCall,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::NewExpression,
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::UserDefinedTypeName,
+                            vec![
+                                NonTerminal::Identifier(String::from("Token")).to_boxed_leaf()
+                            ]
+                        )
+                    ]
+                ),
+                boxed_parse_tree(
+                    NonTerminal::FunctionCallArguments,
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::ExpressionList,
+                            vec![
+                                lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf()
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn new_expression_test2() {
+        let actual_tree = parse_expression(&str_to_chars("new uint[](n)"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::FunctionCall,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::NewExpression,
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::ArrayTypeName,
+                            vec![
+                                lex_4_25::Token::Uint.to_boxed_leaf()
+                            ]
+                        )
+                    ]
+                ),
+                boxed_parse_tree(
+                    NonTerminal::FunctionCallArguments,
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::ExpressionList,
+                            vec![
+                                NonTerminal::Identifier(String::from("n")).to_boxed_leaf()
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    /*** Elementary Type Name Expressions ***/
+
+    #[test]
+    fn elementary_type_name_expression_test1() {
+        let actual_tree = parse_expression(&str_to_chars("address(this)"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::FunctionCall,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::ElementaryTypeNameExpression,
+                    vec![
+                        lex_4_25::Token::Address.to_boxed_leaf()
+                    ]
+                ),
+                boxed_parse_tree(
+                    NonTerminal::FunctionCallArguments,
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::ExpressionList,
+                            vec![
+                                NonTerminal::Identifier(String::from("this")).to_boxed_leaf()
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn elementary_type_name_expression_test2() {
+        let actual_tree = parse_expression(&str_to_chars("x * uint256(y)"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Multiply),
+            leaves: vec![
+                NonTerminal::Identifier(String::from("x")).to_boxed_leaf(),
+                boxed_parse_tree(
+                    NonTerminal::FunctionCall,
+                    vec![
+                        boxed_parse_tree(
+                            NonTerminal::ElementaryTypeNameExpression,
+                            vec![
+                                lex_4_25::Token::Uint256.to_boxed_leaf()
+                            ]
+                        ),
+                        boxed_parse_tree(
+                            NonTerminal::FunctionCallArguments,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::ExpressionList,
+                                    vec![
+                                        NonTerminal::Identifier(String::from("y")).to_boxed_leaf()
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    /*** Tuples ***/
+
+    #[test]
+    fn tuple_expression_test1() {
+        let actual_tree = parse_expression(&str_to_chars("(a, b) = (b, a)"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Assignment),
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::TupleExpression,
+                    vec![
+                        NonTerminal::Identifier(String::from("a")).to_boxed_leaf(),
+                        NonTerminal::Identifier(String::from("b")).to_boxed_leaf()
+                    ]
+                ),
+                boxed_parse_tree(
+                    NonTerminal::TupleExpression,
+                    vec![
+                        NonTerminal::Identifier(String::from("b")).to_boxed_leaf(),
+                        NonTerminal::Identifier(String::from("a")).to_boxed_leaf()
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn tuple_expression_test2() {
+        let actual_tree = parse_expression(&str_to_chars("(, b, ) = f()"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Assignment),
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::TupleExpression,
+                    vec![
+                        NonTerminal::Empty.to_boxed_leaf(),
+                        NonTerminal::Identifier(String::from("b")).to_boxed_leaf(),
+                        NonTerminal::Empty.to_boxed_leaf()
+                    ]
+                ),
+                boxed_parse_tree(
+                    NonTerminal::FunctionCall,
+                    vec![
+                        NonTerminal::Identifier(String::from("f")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::FunctionCallArguments,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::ExpressionList,
+                                    vec![
+                                        NonTerminal::Expression.to_boxed_leaf()
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn tuple_expression_test3() {
+        let actual_tree = parse_expression(&str_to_chars("()"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = NonTerminal::TupleExpression.to_leaf();
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    /*** Inline Arrays ***/
+
+    #[test]
+    fn inline_array_expression_test1() {
+        let actual_tree = parse_expression(&str_to_chars("[1, 2, 3][i]"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::IndexAccess,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::InlineArrayExpression,
+                    vec![
+                        lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf(),
+                        lex_4_25::Token::DecimalNumber(String::from("2")).to_boxed_leaf(),
+                        lex_4_25::Token::DecimalNumber(String::from("3")).to_boxed_leaf()
+                    ]
+                ),
+                NonTerminal::Identifier(String::from("i")).to_boxed_leaf()
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn inline_array_expression_test2() {
+        let actual_tree = parse_expression(&str_to_chars("[1, , 3]"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = ParseTree {
+            root: NonTerminal::InlineArrayExpression,
+            leaves: vec![
+                lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf(),
+                NonTerminal::Invalid(Box::new(NonTerminal::Expression)).to_boxed_leaf(),
+                lex_4_25::Token::DecimalNumber(String::from("3")).to_boxed_leaf()
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
 }
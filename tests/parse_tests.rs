@@ -2,15 +2,20 @@ extern crate solfix;
 
 #[cfg(test)]
 mod parse_tests {
+    use solfix::codes;
+    use solfix::diagnostics::{ Severity, Span };
     use solfix::lex_4_25;
     use solfix::parse_4_25::{ NonTerminal, parse, ParseTree };
+    use solfix::source_map::SourceMap;
 
     /*** Helpers ***/
 
+    // ParseTree stores its leaves boxed, so the helper takes them in the same form
+    #[allow(clippy::vec_box)]
     fn boxed_parse_tree(root: NonTerminal, leaves: Vec<Box<ParseTree>>) -> Box<ParseTree> {
         Box::new(ParseTree {
-            root: root,
-            leaves: leaves,
+            root,
+            leaves,
         })
     }
 
@@ -30,10 +35,6 @@ mod parse_tests {
         }
     }
 
-    trait PrettyAssertions<T> {
-        fn assert_equal_pretty(self, other: T);
-    }
-
     macro_rules! assert_eq_pretty {
         ($i1:ident, $i2:ident) => {
             assert_eq!($i1, $i2, "Expected\n========\n{:#?}\nActual\n======\n{:#?}", $i1, $i2);
@@ -44,7 +45,7 @@ mod parse_tests {
 
     #[test]
     fn pragma_test1() {
-        let actual_tree = parse(String::from("pragma solidity 0.4.25;"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("pragma solidity 0.4.25;"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -62,7 +63,7 @@ mod parse_tests {
 
     #[test]
     fn pragma_test2() {
-        let actual_tree = parse(String::from("pragma solidity ^0.4.25;"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("pragma solidity ^0.4.25;"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -83,7 +84,7 @@ mod parse_tests {
 
     #[test]
     fn contract_test1() {
-        let actual_tree = parse(String::from("contract A {}"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract A {}"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -101,7 +102,7 @@ mod parse_tests {
 
     #[test]
     fn contract_test2() {
-        let actual_tree = parse(String::from("contract B is A {}"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract B is A {}"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -135,7 +136,7 @@ mod parse_tests {
 
     #[test]
     fn contract_enum_test1() {
-        let actual_tree = parse(String::from("contract Enum { enum Foo { } }"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Enum { enum Foo { } }"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -164,7 +165,7 @@ mod parse_tests {
 
     #[test]
     fn contract_enum_test2() {
-        let actual_tree = parse(String::from("contract Enum { enum Foo { Bar, Baz } }"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Enum { enum Foo { Bar, Baz } }"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -199,7 +200,7 @@ mod parse_tests {
 
     #[test]
     fn contract_event_test1() {
-        let actual_tree = parse(String::from("contract Event { event emptyEvent(); }"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Event { event emptyEvent(); }"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -228,7 +229,7 @@ mod parse_tests {
 
     #[test]
     fn contract_event_test2() {
-        let actual_tree = parse(String::from("contract Event { event Transfer(address indexed from, address indexed to, uint256 indexed value); }"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Event { event Transfer(address indexed from, address indexed to, uint256 indexed value); }"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -285,7 +286,7 @@ mod parse_tests {
 
     #[test]
     fn contract_function_test1() {
-        let actual_tree = parse(String::from("contract Function { function doNothing() internal pure { } }"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Function { function doNothing() internal pure { } }"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -317,7 +318,7 @@ mod parse_tests {
 
     #[test]
     fn contract_function_test2() {
-        let actual_tree = parse(String::from("contract Function { function emitEvent() internal { emit someEvent(1 + 1); } }"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Function { function emitEvent() internal { emit someEvent(1 + 1); } }"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -380,7 +381,7 @@ mod parse_tests {
 
     #[test]
     fn contract_modifier_test1() {
-        let actual_tree = parse(String::from("contract Modifier { modifier doNothing { _;} }"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Modifier { modifier doNothing { _;} }"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -414,7 +415,7 @@ mod parse_tests {
 
     #[test]
     fn contract_using_for_test1() {
-        let actual_tree = parse(String::from("contract Using { using SafeMath for uint256; }"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Using { using SafeMath for uint256; }"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -443,7 +444,7 @@ mod parse_tests {
 
     #[test]
     fn contract_using_for_test2() {
-        let actual_tree = parse(String::from("contract Using { using SafeMath for *; }"));
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Using { using SafeMath for *; }"));
         let expected_tree = ParseTree {
             root: NonTerminal::SourceUnit,
             leaves: vec![
@@ -469,4 +470,502 @@ mod parse_tests {
         };
         assert_eq_pretty!(expected_tree, actual_tree);
     }
+
+    #[test]
+    fn contract_state_variable_test1() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Book { Order[] orders; }"));
+        let expected_tree = ParseTree {
+            root: NonTerminal::SourceUnit,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::ContractDefinition(lex_4_25::Token::Contract),
+                    vec![
+                        NonTerminal::Identifier(String::from("Book")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::ContractPart,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::StateVariableDeclaration,
+                                    vec![
+                                        boxed_parse_tree(
+                                            NonTerminal::ArrayTypeName,
+                                            vec![
+                                                boxed_parse_tree(
+                                                    NonTerminal::UserDefinedTypeName,
+                                                    vec![
+                                                        NonTerminal::Identifier(String::from("Order")).to_boxed_leaf()
+                                                    ]
+                                                )
+                                            ]
+                                        ),
+                                        NonTerminal::Identifier(String::from("orders")).to_boxed_leaf()
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn contract_struct_test1() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Book { struct Info { address owner; uint[] amounts; } }"));
+        let expected_tree = ParseTree {
+            root: NonTerminal::SourceUnit,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::ContractDefinition(lex_4_25::Token::Contract),
+                    vec![
+                        NonTerminal::Identifier(String::from("Book")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::ContractPart,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::StructDefinition,
+                                    vec![
+                                        NonTerminal::Identifier(String::from("Info")).to_boxed_leaf(),
+                                        boxed_parse_tree(
+                                            NonTerminal::VariableDeclaration,
+                                            vec![
+                                                lex_4_25::Token::Address.to_boxed_leaf(),
+                                                NonTerminal::Identifier(String::from("owner")).to_boxed_leaf()
+                                            ]
+                                        ),
+                                        boxed_parse_tree(
+                                            NonTerminal::VariableDeclaration,
+                                            vec![
+                                                boxed_parse_tree(
+                                                    NonTerminal::ArrayTypeName,
+                                                    vec![
+                                                        lex_4_25::Token::Uint.to_boxed_leaf()
+                                                    ]
+                                                ),
+                                                NonTerminal::Identifier(String::from("amounts")).to_boxed_leaf()
+                                            ]
+                                        )
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn contract_mapping_test1() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from(
+            "contract Book { mapping (address => mapping (Info => uint)) infos; struct Info { uint id; } }"
+        ));
+        let diagnostics = actual_tree.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "struct `Info` cannot be used as a mapping key");
+        assert_eq!(diagnostics[0].span, Span::new(45, 49));
+    }
+
+    #[test]
+    fn contract_mapping_test2() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from(
+            "contract Book { enum Side { Buy, Sell } struct Info { uint id; } mapping (Side => Info) infos; }"
+        ));
+        assert!(actual_tree.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn contract_function_test3() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Function { constructor(address owner, bytes memory data) public { } }"));
+        let expected_tree = ParseTree {
+            root: NonTerminal::SourceUnit,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::ContractDefinition(lex_4_25::Token::Contract),
+                    vec![
+                        NonTerminal::Identifier(String::from("Function")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::ContractPart,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::ConstructorDefinition,
+                                    vec![
+                                        boxed_parse_tree(
+                                            NonTerminal::ParameterList,
+                                            vec![
+                                                boxed_parse_tree(
+                                                    NonTerminal::Parameter,
+                                                    vec![
+                                                        lex_4_25::Token::Address.to_boxed_leaf(),
+                                                        NonTerminal::Identifier(String::from("owner")).to_boxed_leaf()
+                                                    ]
+                                                ),
+                                                boxed_parse_tree(
+                                                    NonTerminal::Parameter,
+                                                    vec![
+                                                        lex_4_25::Token::Bytes.to_boxed_leaf(),
+                                                        NonTerminal::StorageLocation(lex_4_25::Token::Memory).to_boxed_leaf(),
+                                                        NonTerminal::Identifier(String::from("data")).to_boxed_leaf()
+                                                    ]
+                                                )
+                                            ]
+                                        ),
+                                        lex_4_25::Token::Public.to_boxed_leaf(),
+                                        NonTerminal::Block.to_boxed_leaf()
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn contract_function_test4() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Function { function () external payable { } }"));
+        let expected_tree = ParseTree {
+            root: NonTerminal::SourceUnit,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::ContractDefinition(lex_4_25::Token::Contract),
+                    vec![
+                        NonTerminal::Identifier(String::from("Function")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::ContractPart,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::FallbackFunctionDefinition,
+                                    vec![
+                                        NonTerminal::ParameterList.to_boxed_leaf(),
+                                        lex_4_25::Token::External.to_boxed_leaf(),
+                                        NonTerminal::StateMutability(lex_4_25::Token::Payable).to_boxed_leaf(),
+                                        NonTerminal::Block.to_boxed_leaf()
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn contract_function_test5() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from(
+            "contract Function { function check(bytes calldata) external returns (bool ok) { } }"
+        ));
+        let expected_tree = ParseTree {
+            root: NonTerminal::SourceUnit,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::ContractDefinition(lex_4_25::Token::Contract),
+                    vec![
+                        NonTerminal::Identifier(String::from("Function")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::ContractPart,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::FunctionDefinition,
+                                    vec![
+                                        NonTerminal::Identifier(String::from("check")).to_boxed_leaf(),
+                                        boxed_parse_tree(
+                                            NonTerminal::ParameterList,
+                                            vec![
+                                                boxed_parse_tree(
+                                                    NonTerminal::Parameter,
+                                                    vec![
+                                                        lex_4_25::Token::Bytes.to_boxed_leaf(),
+                                                        NonTerminal::StorageLocation(lex_4_25::Token::Calldata).to_boxed_leaf()
+                                                    ]
+                                                )
+                                            ]
+                                        ),
+                                        lex_4_25::Token::External.to_boxed_leaf(),
+                                        lex_4_25::Token::Returns.to_boxed_leaf(),
+                                        boxed_parse_tree(
+                                            NonTerminal::ParameterList,
+                                            vec![
+                                                boxed_parse_tree(
+                                                    NonTerminal::Parameter,
+                                                    vec![
+                                                        lex_4_25::Token::Bool.to_boxed_leaf(),
+                                                        NonTerminal::Identifier(String::from("ok")).to_boxed_leaf()
+                                                    ]
+                                                )
+                                            ]
+                                        ),
+                                        NonTerminal::Block.to_boxed_leaf()
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn contract_function_test6() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from(
+            "interface Token { function totalSupply() external view returns (uint256); }"
+        ));
+        let expected_tree = ParseTree {
+            root: NonTerminal::SourceUnit,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::ContractDefinition(lex_4_25::Token::Interface),
+                    vec![
+                        NonTerminal::Identifier(String::from("Token")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::ContractPart,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::FunctionDefinition,
+                                    vec![
+                                        NonTerminal::Identifier(String::from("totalSupply")).to_boxed_leaf(),
+                                        NonTerminal::ParameterList.to_boxed_leaf(),
+                                        lex_4_25::Token::External.to_boxed_leaf(),
+                                        NonTerminal::StateMutability(lex_4_25::Token::View).to_boxed_leaf(),
+                                        lex_4_25::Token::Returns.to_boxed_leaf(),
+                                        boxed_parse_tree(
+                                            NonTerminal::ParameterList,
+                                            vec![
+                                                boxed_parse_tree(
+                                                    NonTerminal::Parameter,
+                                                    vec![
+                                                        lex_4_25::Token::Uint256.to_boxed_leaf()
+                                                    ]
+                                                )
+                                            ]
+                                        )
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+        let contract = &actual_tree.leaves[0];
+        assert!(!contract.leaves[1].leaves[0].is_implemented());
+        assert!(contract.is_abstract());
+    }
+
+    #[test]
+    fn contract_function_test7() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from(
+            "contract Base { function f() public; function g() public { } }"
+        ));
+        assert!(actual_tree.diagnostics().is_empty());
+        let contract = &actual_tree.leaves[0];
+        let part = &contract.leaves[1];
+        assert!(!part.leaves[0].is_implemented());
+        assert!(part.leaves[1].is_implemented());
+        assert!(contract.is_abstract());
+    }
+
+    #[test]
+    fn contract_function_test8() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Concrete { constructor() public { } function () external { } }"));
+        assert!(!actual_tree.leaves[0].is_abstract());
+    }
+
+    #[test]
+    fn contract_function_test9() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Pair { function get() public { return (x, y); } }"));
+        let expected_tree = ParseTree {
+            root: NonTerminal::SourceUnit,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::ContractDefinition(lex_4_25::Token::Contract),
+                    vec![
+                        NonTerminal::Identifier(String::from("Pair")).to_boxed_leaf(),
+                        boxed_parse_tree(
+                            NonTerminal::ContractPart,
+                            vec![
+                                boxed_parse_tree(
+                                    NonTerminal::FunctionDefinition,
+                                    vec![
+                                        NonTerminal::Identifier(String::from("get")).to_boxed_leaf(),
+                                        NonTerminal::ParameterList.to_boxed_leaf(),
+                                        lex_4_25::Token::Public.to_boxed_leaf(),
+                                        boxed_parse_tree(
+                                            NonTerminal::Block,
+                                            vec![
+                                                boxed_parse_tree(
+                                                    NonTerminal::Return,
+                                                    vec![
+                                                        boxed_parse_tree(
+                                                            NonTerminal::TupleExpression,
+                                                            vec![
+                                                                NonTerminal::Identifier(String::from("x")).to_boxed_leaf(),
+                                                                NonTerminal::Identifier(String::from("y")).to_boxed_leaf()
+                                                            ]
+                                                        )
+                                                    ]
+                                                )
+                                            ]
+                                        )
+                                    ]
+                                )
+                            ]
+                        )
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn contract_function_test10() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("interface Empty { } interface Token { event Transfer(); }"));
+        assert!(actual_tree.diagnostics().is_empty());
+        assert!(!actual_tree.leaves[0].is_abstract());
+        assert!(actual_tree.leaves[1].is_abstract());
+    }
+
+    #[test]
+    fn contract_function_test11() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Broken { function f() public }"));
+        let function = actual_tree.leaves[0].first_descendant(&NonTerminal::FunctionDefinition).unwrap();
+        assert!(!function.is_implemented());
+        assert!(actual_tree.leaves[0].is_abstract());
+    }
+
+    /*** Reserved Keywords ***/
+
+    #[test]
+    fn reserved_keyword_test1() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Reserved { uint256 type; }"));
+        let diagnostics = actual_tree.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "`type` is a reserved keyword and cannot be used as an identifier"
+        );
+        assert_eq!(diagnostics[0].span, Span::new(28, 32));
+    }
+
+    #[test]
+    fn reserved_keyword_test2() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Reserved { event Moved(address indexed of); }"));
+        let diagnostics = actual_tree.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "`of` is a reserved keyword and cannot be used as an identifier"
+        );
+    }
+
+    #[test]
+    fn reserved_keyword_test3() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Ownable { address public owner; }"));
+        assert!(actual_tree.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn reserved_keyword_test4() {
+        // The span of the keyword doesn't include the comment before it
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract A { uint /* x */ type; }"));
+        let diagnostics = actual_tree.diagnostics();
+        assert_eq!(diagnostics[0].message, "`type` is a reserved keyword and cannot be used as an identifier");
+        assert_eq!(diagnostics[0].span, Span::new(26, 30));
+    }
+
+    /*** Suggestions ***/
+
+    #[test]
+    fn suggestion_test1() {
+        let input = String::from("contract A { fucntion foo() {} }");
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", input.clone());
+        let diagnostics = actual_tree.diagnostics();
+        // The misspelled keyword also leaves the parser expecting the ends of state variables
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].message, "expected `;`, found `(`");
+        assert_eq!(diagnostics[0].code, Some(codes::EXPECTED_SEMICOLON));
+        let diagnostics = &diagnostics[1..];
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].message, "unexpected identifier `fucntion`");
+        assert_eq!(diagnostics[0].help, Some(String::from("did you mean `function`?")));
+        let fixed = diagnostics[0].suggestions[0].apply(&input.chars().collect::<Vec<char>>());
+        assert_eq!(fixed.iter().collect::<String>(), "contract A { function foo() {} }");
+    }
+
+    #[test]
+    fn suggestion_test2() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract A { function f() public retruns (uint256) { } }"));
+        let diagnostics = actual_tree.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].span, Span::new(33, 40));
+        assert_eq!(diagnostics[0].suggestions[0].replacement, "returns");
+    }
+
+    #[test]
+    fn suggestion_test3() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from(
+            "contract A { modifier retruns { _; } function f() public retruns { } }"
+        ));
+        assert!(actual_tree.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn suggestion_test4() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract A { uin256 x; }"));
+        let diagnostics = actual_tree.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].suggestions[0].replacement, "uint256");
+    }
+
+    #[test]
+    fn suggestion_test5() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract A { enum Uint { One } Uint x; }"));
+        assert!(actual_tree.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn suggestion_test6() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract A { function f() public { retrun 1; } }"));
+        let diagnostics = actual_tree.diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].message, "expected `;`, found `1`");
+        assert_eq!(diagnostics[0].code, Some(codes::EXPECTED_SEMICOLON));
+        let diagnostics = &diagnostics[1..];
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].suggestions[0].replacement, "return");
+    }
+
+    #[test]
+    fn suggestion_test7() {
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", String::from(
+            "contract A { Unti x; Bytes y; struct Unit { uint a; } }"
+        ));
+        let diagnostics = actual_tree.diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].help, Some(String::from("did you mean `Unit`?")));
+        assert_eq!(diagnostics[1].suggestions[0].replacement, "bytes");
+    }
+
+    #[test]
+    fn suggestion_test8() {
+        // A comment before the misspelled word isn't part of the word that is looked up
+        let input = String::from("contract A { /*c*/ fucntion foo() {} }");
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", input.clone());
+        let diagnostic = actual_tree.diagnostics().into_iter().find(|diagnostic| diagnostic.code == Some(codes::MISSPELLED_KEYWORD)).unwrap();
+        assert_eq!(diagnostic.span, Span::new(19, 27));
+        let fixed = diagnostic.suggestions[0].apply(&input.chars().collect::<Vec<char>>());
+        assert_eq!(fixed.iter().collect::<String>(), "contract A { /*c*/ function foo() {} }");
+    }
 }
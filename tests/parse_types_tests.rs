@@ -2,43 +2,17 @@ extern crate solfix;
 
 #[cfg(test)]
 mod types_tests {
-    use solfix::lex_4_25;
-    use solfix::parse_4_25::{ NonTerminal, parse_type_name, ParseTree };
+    use solfix::parse_4_25::parse_type_name;
+    use solfix::parser::{ Context, ParserOptions };
+    use solfix::tree;
 
     /*** Helpers ***/
 
-    fn boxed_parse_tree(root: NonTerminal, leaves: Vec<Box<ParseTree>>) -> Box<ParseTree> {
-        Box::new(ParseTree {
-            root: root,
-            leaves: leaves,
-        })
-    }
-
     fn str_to_chars(string: &str) -> Vec<char> {
         let chars = String::from(string).chars().collect::<Vec<char>>();
         chars
     }
 
-    trait TestUtils {
-        fn to_boxed_leaf(self) -> Box<ParseTree>;
-    }
-
-    impl TestUtils for lex_4_25::Token {
-        fn to_boxed_leaf(self) -> Box<ParseTree> {
-            Box::new(NonTerminal::Token(self).to_leaf())
-        }
-    }
-
-    impl TestUtils for NonTerminal {
-        fn to_boxed_leaf(self) -> Box<ParseTree> {
-            Box::new(self.to_leaf())
-        }
-    }
-
-    trait PrettyAssertions<T> {
-        fn assert_equal_pretty(self, other: T);
-    }
-
     macro_rules! assert_eq_pretty {
         ($i1:ident, $i2:ident) => {
             assert_eq!($i1, $i2, "Expected\n========\n{:#?}\nActual\n======\n{:#?}", $i1, $i2);
@@ -49,80 +23,159 @@ mod types_tests {
 
     #[test]
     fn elementary_type_test1() {
-        let actual_tree = parse_type_name(&str_to_chars("address"), &mut 0);
-        let expected_tree = *lex_4_25::Token::Address.to_boxed_leaf();
+        let actual_tree = parse_type_name(&str_to_chars("address"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(Token(Address));
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn elementary_type_test2() {
+        let actual_tree = parse_type_name(&str_to_chars("fixed128x18"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(ElementaryTypeName [Token(FixedMxN(128, 18))]);
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn elementary_type_test3() {
+        let actual_tree = parse_type_name(&str_to_chars("ufixed"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(ElementaryTypeName [Token(Ufixed)]);
         assert_eq_pretty!(expected_tree, actual_tree);
     }
 
     #[test]
     fn user_defined_type_test1() {
-        let actual_tree = parse_type_name(&str_to_chars("Address.Enum"), &mut 0);
-        let expected_tree = ParseTree {
-            root: NonTerminal::UserDefinedTypeName,
-            leaves: vec![
-                NonTerminal::Identifier(String::from("Address")).to_boxed_leaf(),
-                NonTerminal::Identifier(String::from("Enum")).to_boxed_leaf()
-            ]
-        };
+        let actual_tree = parse_type_name(&str_to_chars("Address.Enum"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(UserDefinedTypeName [Identifier("Address"), Identifier("Enum")]);
         assert_eq_pretty!(expected_tree, actual_tree);
     }
 
     #[test]
     fn mapping_test1() {
-        let actual_tree = parse_type_name(&str_to_chars("mapping (uint256 => uint256)"), &mut 0);
-        let expected_tree = ParseTree {
-            root: NonTerminal::Mapping,
-            leaves: vec! [
-                lex_4_25::Token::Uint256.to_boxed_leaf(),
-                lex_4_25::Token::Uint256.to_boxed_leaf()
+        let actual_tree = parse_type_name(&str_to_chars("mapping (uint256 => uint256)"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(Mapping [Token(Uint256), Token(Arrow), Token(Uint256)]);
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn mapping_test2() {
+        let actual_tree = parse_type_name(&str_to_chars("mapping(address => mapping(uint => Info[]))"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(Mapping [
+            Token(Address),
+            Token(Arrow),
+            Mapping [
+                Token(Uint),
+                Token(Arrow),
+                ArrayTypeName [UserDefinedTypeName [Identifier("Info")]]
             ]
-        };
+        ]);
         assert_eq_pretty!(expected_tree, actual_tree);
+        let value = actual_tree.value_type().unwrap();
+        assert_eq!(actual_tree.key_type().unwrap().root, tree!(Token(Address)).root);
+        assert_eq!(value.key_type().unwrap().root, tree!(Token(Uint)).root);
+        assert_eq!(value.value_type().unwrap().root, tree!(ArrayTypeName).root);
+    }
+
+    #[test]
+    fn mapping_test3() {
+        let actual_tree = parse_type_name(&str_to_chars("mapping (Color => bool)"), &mut 0, &Context::new(&ParserOptions::default()));
+        assert!(actual_tree.diagnostics().is_empty());
+        assert_eq!(actual_tree.key_type().unwrap().root, tree!(UserDefinedTypeName).root);
+    }
+
+    #[test]
+    fn mapping_test4() {
+        let invalid_keys = vec![
+            ("mapping (uint[] => bool)", "arrays cannot be used as mapping keys"),
+            ("mapping (mapping (uint => uint) => bool)", "mappings cannot be used as mapping keys"),
+            ("mapping (function () external => bool)", "function types cannot be used as mapping keys")
+        ];
+        for (input, message) in invalid_keys {
+            let actual_tree = parse_type_name(&str_to_chars(input), &mut 0, &Context::new(&ParserOptions::default()));
+            let diagnostics = actual_tree.diagnostics();
+            assert_eq!(diagnostics.len(), 1, "{}", input);
+            assert_eq!(diagnostics[0].message, message);
+            assert_eq!(actual_tree.value_type().unwrap().root, tree!(Token(Bool)).root);
+        }
+    }
+
+    #[test]
+    fn type_name_source_test1() {
+        let type_names = vec![
+            "uint256",
+            "fixed128x18[]",
+            "Foo.Bar[2][]",
+            "mapping(address => mapping(uint => Info[]))",
+            "bytes32[(1 + 1) * 2]",
+            "function(uint256[] memory, bool) external payable returns (function() internal)"
+        ];
+        for type_name in type_names {
+            let tree = parse_type_name(&str_to_chars(type_name), &mut 0, &Context::new(&ParserOptions::default()));
+            let source = tree.type_name_source();
+            assert_eq!(source, type_name);
+            assert_eq!(parse_type_name(&str_to_chars(&source), &mut 0, &Context::new(&ParserOptions::default())), tree);
+        }
     }
 
     #[test]
     fn array_type_name_test1() {
-        let actual_tree = parse_type_name(&str_to_chars("uint256[1 + 1]"), &mut 0);
-        let expected_tree = ParseTree {
-            root: NonTerminal::ArrayTypeName,
-            leaves: vec! [
-                lex_4_25::Token::Uint256.to_boxed_leaf(),
-                boxed_parse_tree(
-                    NonTerminal::Token(lex_4_25::Token::Plus),
-                    vec![
-                        lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf(),
-                        lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf()
-                    ]
-                )
-            ]
-        };
+        let actual_tree = parse_type_name(&str_to_chars("uint256[1 + 1]"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(ArrayTypeName [
+            Token(Uint256),
+            Token(Plus) [Token(DecimalNumber("1")), Token(DecimalNumber("1"))]
+        ]);
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn array_type_name_test2() {
+        let actual_tree = parse_type_name(&str_to_chars("uint[]"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(ArrayTypeName [Token(Uint)]);
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn array_type_name_test3() {
+        let actual_tree = parse_type_name(&str_to_chars("bool[][3][]"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(ArrayTypeName [
+            ArrayTypeName [ArrayTypeName [Token(Bool)], Token(DecimalNumber("3"))]
+        ]);
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn array_type_name_test4() {
+        let actual_tree = parse_type_name(&str_to_chars("Foo.Bar[2]"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(ArrayTypeName [
+            UserDefinedTypeName [Identifier("Foo"), Identifier("Bar")],
+            Token(DecimalNumber("2"))
+        ]);
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn array_type_name_test5() {
+        let actual_tree = parse_type_name(&str_to_chars("fixed128x18[2][]"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(ArrayTypeName [
+            ArrayTypeName [ElementaryTypeName [Token(FixedMxN(128, 18))], Token(DecimalNumber("2"))]
+        ]);
         assert_eq_pretty!(expected_tree, actual_tree);
     }
 
     #[test]
     fn function_type_name_test1() {
-        let actual_tree = parse_type_name(&str_to_chars("function () internal"), &mut 0);
-        let expected_tree = ParseTree {
-            root: NonTerminal::FunctionTypeName,
-            leaves: vec! [
-                NonTerminal::FunctionTypeParameterList.to_boxed_leaf(),
-                lex_4_25::Token::Internal.to_boxed_leaf()
-            ]
-        };
+        let actual_tree = parse_type_name(&str_to_chars("function () internal"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(FunctionTypeName [FunctionTypeParameterList, Token(Internal)]);
         assert_eq_pretty!(expected_tree, actual_tree);
     }
 
     #[test]
     fn function_type_name_test2() {
-        let actual_tree = parse_type_name(&str_to_chars("function () internal pure"), &mut 0);
-        let expected_tree = ParseTree {
-            root: NonTerminal::FunctionTypeName,
-            leaves: vec! [
-                NonTerminal::FunctionTypeParameterList.to_boxed_leaf(),
-                lex_4_25::Token::Internal.to_boxed_leaf(),
-                NonTerminal::StateMutability(lex_4_25::Token::Pure).to_boxed_leaf()
-            ]
-        };
+        let actual_tree = parse_type_name(&str_to_chars("function () internal pure"), &mut 0, &Context::new(&ParserOptions::default()));
+        let expected_tree = tree!(FunctionTypeName [
+            FunctionTypeParameterList,
+            Token(Internal),
+            StateMutability(Pure)
+        ]);
         assert_eq_pretty!(expected_tree, actual_tree);
     }
 
@@ -130,48 +183,18 @@ mod types_tests {
     fn function_type_name_test3() {
         let actual_tree = parse_type_name(
             &str_to_chars("function (uint256, bytes32) internal pure returns (bool) "),
-            &mut 0
+            &mut 0,
+            &Context::new(&ParserOptions::default())
         );
-        let expected_tree = ParseTree {
-            root: NonTerminal::FunctionTypeName,
-            leaves: vec![
-                boxed_parse_tree(
-                    NonTerminal::FunctionTypeParameterList,
-                    vec![
-                        boxed_parse_tree(
-                            NonTerminal::FunctionTypeParameter,
-                            vec![
-                                lex_4_25::Token::Uint256.to_boxed_leaf()
-                            ]
-                        ),
-                        boxed_parse_tree(
-                            NonTerminal::FunctionTypeParameter,
-                            vec![
-                                lex_4_25::Token::Bytes32.to_boxed_leaf()
-                            ]
-                        )
-                    ]
-                ),
-                lex_4_25::Token::Internal.to_boxed_leaf(),
-                NonTerminal::StateMutability(lex_4_25::Token::Pure).to_boxed_leaf(),
-                boxed_parse_tree(
-                    NonTerminal::Token(lex_4_25::Token::Returns),
-                    vec![
-                        boxed_parse_tree(
-                            NonTerminal::FunctionTypeParameterList,
-                            vec![
-                                boxed_parse_tree(
-                                    NonTerminal::FunctionTypeParameter,
-                                    vec![
-                                        lex_4_25::Token::Bool.to_boxed_leaf()
-                                    ]
-                                )
-                            ]
-                        )
-                    ]
-                )
-            ]
-        };
+        let expected_tree = tree!(FunctionTypeName [
+            FunctionTypeParameterList [
+                FunctionTypeParameter [Token(Uint256)],
+                FunctionTypeParameter [Token(Bytes32)]
+            ],
+            Token(Internal),
+            StateMutability(Pure),
+            Token(Returns) [FunctionTypeParameterList [FunctionTypeParameter [Token(Bool)]]]
+        ]);
         assert_eq_pretty!(expected_tree, actual_tree);
     }
 
@@ -182,90 +205,24 @@ mod types_tests {
                 "function (function (uint256) internal) external payable returns \
                 (function (bool) external returns (uint256)) "
             ),
-            &mut 0
+            &mut 0,
+            &Context::new(&ParserOptions::default())
         );
-        let expected_tree = ParseTree {
-            root: NonTerminal::FunctionTypeName,
-            leaves: vec![
-                boxed_parse_tree(
-                    NonTerminal::FunctionTypeParameterList,
-                    vec![
-                        boxed_parse_tree(
-                            NonTerminal::FunctionTypeParameter,
-                            vec![
-                                boxed_parse_tree(
-                                    NonTerminal::FunctionTypeName,
-                                    vec! [
-                                        boxed_parse_tree(
-                                            NonTerminal::FunctionTypeParameterList,
-                                            vec![
-                                                boxed_parse_tree(
-                                                    NonTerminal::FunctionTypeParameter,
-                                                    vec![
-                                                        lex_4_25::Token::Uint256.to_boxed_leaf()
-                                                    ]
-                                                )
-                                            ]
-                                        ),
-                                        lex_4_25::Token::Internal.to_boxed_leaf()
-                                    ]
-                                )
-                            ]
-                        )
-                    ]
-                ),
-                lex_4_25::Token::External.to_boxed_leaf(),
-                NonTerminal::StateMutability(lex_4_25::Token::Payable).to_boxed_leaf(),
-                boxed_parse_tree(
-                    NonTerminal::Token(lex_4_25::Token::Returns),
-                    vec![
-                        boxed_parse_tree(
-                            NonTerminal::FunctionTypeParameterList,
-                            vec![
-                                boxed_parse_tree(
-                                    NonTerminal::FunctionTypeParameter,
-                                    vec![
-                                        boxed_parse_tree(
-                                            NonTerminal::FunctionTypeName,
-                                            vec! [
-                                                boxed_parse_tree(
-                                                    NonTerminal::FunctionTypeParameterList,
-                                                    vec![
-                                                        boxed_parse_tree(
-                                                            NonTerminal::FunctionTypeParameter,
-                                                            vec![
-                                                                lex_4_25::Token::Bool.to_boxed_leaf()
-                                                            ]
-                                                        )
-                                                    ]
-                                                ),
-                                                lex_4_25::Token::External.to_boxed_leaf(),
-                                                boxed_parse_tree(
-                                                    NonTerminal::Token(lex_4_25::Token::Returns),
-                                                    vec![
-                                                        boxed_parse_tree(
-                                                            NonTerminal::FunctionTypeParameterList,
-                                                            vec![
-                                                                boxed_parse_tree(
-                                                                    NonTerminal::FunctionTypeParameter,
-                                                                    vec![
-                                                                        lex_4_25::Token::Uint256.to_boxed_leaf()
-                                                                    ]
-                                                                )
-                                                            ]
-                                                        )
-                                                    ]
-                                                )
-                                            ]
-                                        )
-                                    ]
-                                )
-                            ]
-                        )
-                    ]
-                )
-            ]
-        };
+        let parameter = tree!(FunctionTypeName [
+            FunctionTypeParameterList [FunctionTypeParameter [Token(Uint256)]],
+            Token(Internal)
+        ]);
+        let returned = tree!(FunctionTypeName [
+            FunctionTypeParameterList [FunctionTypeParameter [Token(Bool)]],
+            Token(External),
+            Token(Returns) [FunctionTypeParameterList [FunctionTypeParameter [Token(Uint256)]]]
+        ]);
+        let expected_tree = tree!(FunctionTypeName [
+            FunctionTypeParameterList [FunctionTypeParameter [{ parameter }]],
+            Token(External),
+            StateMutability(Payable),
+            Token(Returns) [FunctionTypeParameterList [FunctionTypeParameter [{ returned }]]]
+        ]);
         assert_eq_pretty!(expected_tree, actual_tree);
     }
 }
@@ -2,124 +2,33 @@ extern crate solfix;
 
 #[cfg(test)]
 mod parser_integration_tests {
-    use solfix::lex_4_25;
-    use solfix::parse_4_25::{ NonTerminal, parse, ParseTree };
+    use solfix::parse_4_25::{ parse, ParseTree };
+    use solfix::source_map::SourceMap;
+    use std::env;
     use std::fs;
 
     /*** Helpers ***/
 
-    fn boxed_parse_tree(root: NonTerminal, leaves: Vec<Box<ParseTree>>) -> Box<ParseTree> {
-        Box::new(ParseTree {
-            root: root,
-            leaves: leaves,
-        })
-    }
-
-    trait TestUtils {
-        fn to_boxed_leaf(self) -> Box<ParseTree>;
-    }
-
-    impl TestUtils for lex_4_25::Token {
-        fn to_boxed_leaf(self) -> Box<ParseTree> {
-            Box::new(NonTerminal::Token(self).to_leaf())
-        }
-    }
-
-    impl TestUtils for NonTerminal {
-        fn to_boxed_leaf(self) -> Box<ParseTree> {
-            Box::new(self.to_leaf())
-        }
-    }
-
-    trait PrettyAssertions<T> {
-        fn assert_equal_pretty(self, other: T);
-    }
-
-    macro_rules! assert_eq_pretty {
-        ($i1:ident, $i2:ident) => {
-            assert_eq!($i1, $i2, "Expected\n========\n{:#?}\nActual\n======\n{:#?}", $i1, $i2);
+    // Compares the parse tree of a contract in the contracts directory with its golden file,
+    // which holds the tree as an S-expression. Setting UPDATE_GOLDEN rewrites the golden file
+    // instead. Only contracts that parse without errors have golden files.
+    fn check_golden(name: &str) {
+        let input = fs::read_to_string(format!("./contracts/{}.sol", name))
+            .expect("Test file not found");
+        let (_, actual_tree) = parse(&mut SourceMap::new(), "A.sol", input);
+        assert!(!actual_tree.has_errors(), "{}", name);
+        let golden = format!("./tests/golden/{}.tree", name);
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&golden, actual_tree.to_sexpr() + "\n").unwrap();
         }
+        let expected = fs::read_to_string(&golden).expect("Golden file not found");
+        let expected_tree = ParseTree::from_sexpr(&expected).unwrap();
+        assert_eq!(expected_tree, actual_tree, "Expected\n========\n{}\nActual\n======\n{}", expected, actual_tree.to_sexpr());
     }
 
     #[test]
     fn ownable_test() {
-        let input = fs::read_to_string("./contracts/Ownable.sol")
-            .expect("Test file not found: ./contracts/Ownable.sol");
-        let actual_tree = parse(input);
-        let expected_tree = ParseTree {
-            root: NonTerminal::SourceUnit,
-            leaves: vec![
-                boxed_parse_tree(
-                    NonTerminal::PragmaDirective,
-                    vec![
-                        NonTerminal::Identifier(String::from("solidity")).to_boxed_leaf(),
-                        lex_4_25::Token::BitwiseXor.to_boxed_leaf(),
-                        lex_4_25::Token::Version(String::from("0.4.25")).to_boxed_leaf()
-                    ]
-                ),
-                boxed_parse_tree(
-                    NonTerminal::ContractDefinition(lex_4_25::Token::Contract),
-                    vec![
-                        NonTerminal::Identifier(String::from("Ownable")).to_boxed_leaf(),
-                        boxed_parse_tree(
-                            NonTerminal::ContractPart,
-                            vec![
-                                boxed_parse_tree(
-                                    NonTerminal::StateVariableDeclaration,
-                                    vec![
-                                        lex_4_25::Token::Address.to_boxed_leaf(),
-                                        lex_4_25::Token::Public.to_boxed_leaf(),
-                                        NonTerminal::Identifier(String::from("owner")).to_boxed_leaf()
-                                    ]
-                                ),
-                                boxed_parse_tree(
-                                    NonTerminal::ModifierDefinition,
-                                    vec![
-                                        NonTerminal::Identifier(String::from("onlyOwner")).to_boxed_leaf(),
-                                        boxed_parse_tree(
-                                            NonTerminal::Block,
-                                            vec![
-                                                boxed_parse_tree(
-                                                    NonTerminal::FunctionCall,
-                                                    vec![
-                                                        NonTerminal::Identifier(String::from("require")).to_boxed_leaf(),
-                                                        boxed_parse_tree(
-                                                            NonTerminal::FunctionCallArguments,
-                                                            vec![
-                                                                boxed_parse_tree(
-                                                                    NonTerminal::ExpressionList,
-                                                                    vec![
-                                                                        boxed_parse_tree(
-                                                                            NonTerminal::Token(lex_4_25::Token::Equals),
-                                                                            vec![
-                                                                                boxed_parse_tree(
-                                                                                    NonTerminal::MemberAccess,
-                                                                                    vec![
-                                                                                        NonTerminal::Identifier(String::from("msg")).to_boxed_leaf(),
-                                                                                        NonTerminal::Identifier(String::from("sender")).to_boxed_leaf()
-                                                                                    ]
-                                                                                ),
-                                                                                NonTerminal::Identifier(String::from("owner")).to_boxed_leaf()
-                                                                            ]
-                                                                        )
-                                                                    ]
-                                                                )
-                                                            ]
-                                                        )
-                                                    ]
-                                                ),
-                                                NonTerminal::Identifier(String::from("_")).to_boxed_leaf()
-                                            ]
-                                        )
-                                    ]
-                                ),
-                            ]
-                        )
-                    ]
-                )
-            ]
-        };
-        assert_eq_pretty!(expected_tree, actual_tree);
+        check_golden("Ownable");
     }
 
     /*
@@ -0,0 +1,231 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod parser_tests {
+    use solfix::codes;
+    use solfix::diagnostics::Span;
+    use solfix::lex_4_25;
+    use solfix::parse_4_25::{ NonTerminal, parse, ParseTree };
+    use solfix::parser::{ Parser, ParserOptions };
+    use solfix::source_map::SourceMap;
+
+    /*** Helpers ***/
+
+    // ParseTree stores its leaves boxed, so the helper takes them in the same form
+    #[allow(clippy::vec_box)]
+    fn boxed_parse_tree(root: NonTerminal, leaves: Vec<Box<ParseTree>>) -> Box<ParseTree> {
+        Box::new(ParseTree {
+            root,
+            leaves,
+        })
+    }
+
+    trait TestUtils {
+        fn to_boxed_leaf(self) -> Box<ParseTree>;
+    }
+
+    impl TestUtils for lex_4_25::Token {
+        fn to_boxed_leaf(self) -> Box<ParseTree> {
+            Box::new(NonTerminal::Token(self).to_leaf())
+        }
+    }
+
+    impl TestUtils for NonTerminal {
+        fn to_boxed_leaf(self) -> Box<ParseTree> {
+            Box::new(self.to_leaf())
+        }
+    }
+
+    macro_rules! assert_eq_pretty {
+        ($i1:ident, $i2:ident) => {
+            assert_eq!($i1, $i2, "Expected\n========\n{:#?}\nActual\n======\n{:#?}", $i1, $i2);
+        }
+    }
+
+    /*** Entry Points ***/
+
+    #[test]
+    fn parse_contract_part_test1() {
+        let actual_tree = Parser::default().parse_contract_part("function totalSupply() external view;");
+        let expected_tree = ParseTree {
+            root: NonTerminal::FunctionDefinition,
+            leaves: vec![
+                NonTerminal::Identifier(String::from("totalSupply")).to_boxed_leaf(),
+                NonTerminal::ParameterList.to_boxed_leaf(),
+                lex_4_25::Token::External.to_boxed_leaf(),
+                NonTerminal::StateMutability(lex_4_25::Token::View).to_boxed_leaf()
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn parse_contract_part_test2() {
+        // Names that the fragment declares aren't reported as misspelled keywords
+        let actual_tree = Parser::default().parse_contract_part("struct Bytes { Bytes[] parts; }");
+        assert!(actual_tree.diagnostics().is_empty());
+        let actual_tree = Parser::default().parse_contract_part("struct Node { Bytes[] parts; }");
+        assert_eq!(actual_tree.diagnostics()[0].suggestions[0].replacement, "bytes");
+    }
+
+    #[test]
+    fn parse_contract_part_test3() {
+        // Struct keys are found from the structs that the fragment declares
+        let actual_tree = Parser::default().parse_contract_part("struct Info { mapping (Info => uint) children; }");
+        let diagnostics = actual_tree.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "struct `Info` cannot be used as a mapping key");
+        assert_eq!(diagnostics[0].span, Span::new(23, 27));
+    }
+
+    #[test]
+    fn parse_statement_test1() {
+        let actual_tree = Parser::default().parse_statement("return (a, b);");
+        let expected_tree = ParseTree {
+            root: NonTerminal::Return,
+            leaves: vec![
+                boxed_parse_tree(
+                    NonTerminal::TupleExpression,
+                    vec![
+                        NonTerminal::Identifier(String::from("a")).to_boxed_leaf(),
+                        NonTerminal::Identifier(String::from("b")).to_boxed_leaf()
+                    ]
+                )
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn parse_expression_test1() {
+        let actual_tree = Parser::default().parse_expression("1 + 2 3");
+        let expected_tree = ParseTree {
+            root: NonTerminal::Token(lex_4_25::Token::Plus),
+            leaves: vec![
+                lex_4_25::Token::DecimalNumber(String::from("1")).to_boxed_leaf(),
+                lex_4_25::Token::DecimalNumber(String::from("2")).to_boxed_leaf(),
+                NonTerminal::InvalidPair(
+                    Box::new(NonTerminal::Token(lex_4_25::Token::EOF)),
+                    Box::new(NonTerminal::Token(lex_4_25::Token::DecimalNumber(String::from("3"))))
+                ).to_boxed_leaf()
+            ]
+        };
+        assert_eq_pretty!(expected_tree, actual_tree);
+    }
+
+    #[test]
+    fn parse_type_name_test1() {
+        let actual_tree = Parser::default().parse_type_name("mapping (address => uint256[])");
+        assert_eq!(actual_tree.root, NonTerminal::Mapping);
+        assert!(!actual_tree.has_errors());
+    }
+
+    /*** Options ***/
+
+    #[test]
+    fn version_test1() {
+        let options = ParserOptions { version: (0, 4, 20), ..ParserOptions::default() };
+        let source = "contract A { constructor() public { emit Created(); } }";
+        let diagnostics = Parser::new(options).parse_source_unit(source).diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].message, "`constructor` requires Solidity 0.4.22 or later");
+        assert_eq!(diagnostics[1].message, "`emit` requires Solidity 0.4.21 or later");
+        // The options only apply while the Parser is running.
+        assert!(parse(&mut SourceMap::new(), "A.sol", String::from(source)).1.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn error_recovery_test1() {
+        let source = "contract A { uint256 public; uint256 x; }";
+        let recovered = Parser::default().parse_source_unit(source);
+        assert!(recovered.leaves[0].leaves[1].leaves.len() > 1);
+        let options = ParserOptions { error_recovery: false, ..ParserOptions::default() };
+        let truncated = Parser::new(options).parse_source_unit(source);
+        assert!(truncated.has_errors());
+        assert_eq!(truncated.leaves.len(), 1);
+        assert_eq!(truncated.leaves[0].leaves[1].leaves.len(), 1);
+    }
+
+    #[test]
+    fn error_recovery_test2() {
+        let source = "contract A { function f() { while (a) { b = 0; } } }";
+        let diagnostics = Parser::default().parse_source_unit(source).diagnostics();
+        assert_eq!(diagnostics[0].message, "`while` statements are not supported yet");
+        assert_eq!(diagnostics[0].span, Span::new(28, 33));
+    }
+
+    #[test]
+    fn error_recovery_test3() {
+        let options = ParserOptions { error_recovery: false, ..ParserOptions::default() };
+        let source = "contract A { function f() pubic { } uint256 x; } contract B { }";
+        let actual_tree = Parser::new(options).parse_source_unit(source);
+        // Warnings don't stop the parse.
+        assert_eq!(actual_tree.diagnostics().len(), 1);
+        assert!(!actual_tree.has_errors());
+        assert_eq!(actual_tree.leaves.len(), 2);
+        assert_eq!(actual_tree.leaves[0].contract_members().len(), 2);
+    }
+
+    #[test]
+    fn error_recovery_test4() {
+        let options = ParserOptions { error_recovery: false, ..ParserOptions::default() };
+        let source = "contract A { function f() { g(; h(; } } contract B { uint256 public; }";
+        let truncated = Parser::new(options).parse_source_unit(source);
+        assert!(truncated.has_errors());
+        // Parsing stops in the statement that has the first error.
+        assert_eq!(truncated.leaves.len(), 1);
+        let body = truncated.leaves[0].contract_members()[0].function_body().unwrap();
+        assert_eq!(body.leaves.len(), 1);
+    }
+
+    #[test]
+    fn max_depth_test1() {
+        let options = ParserOptions { max_depth: 4, ..ParserOptions::default() };
+        let actual_tree = Parser::new(options).parse_expression("f((((x + 1) * 2)), y)");
+        let diagnostics = actual_tree.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "the input is nested more than 4 levels deep");
+        // The expression that is too deep is skipped, and parsing carries on after it. The call
+        // counts as a level, so that is `(x + 1) * 2`.
+        assert_eq!(diagnostics[0].span, Span::new(4, 15));
+        let arguments = &actual_tree.leaves[1].leaves[0];
+        assert_eq!(arguments.leaves.len(), 2);
+        assert_eq!(arguments.leaves[1].root, NonTerminal::Identifier(String::from("y")));
+    }
+
+    #[test]
+    fn max_depth_test2() {
+        let options = ParserOptions { max_depth: 3, ..ParserOptions::default() };
+        let source = "mapping (uint => mapping (uint => mapping (uint => mapping (uint => bool))))";
+        let diagnostics = Parser::new(options).parse_type_name(source).diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span, Span::new(51, 73));
+    }
+
+    #[test]
+    fn max_depth_test3() {
+        // Deeply nested input is reported instead of overflowing the stack.
+        let source = format!("{}x{}", "(".repeat(100000), ")".repeat(100000));
+        let diagnostics = Parser::default().parse_expression(&source).diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "the input is nested more than 256 levels deep");
+    }
+
+    #[test]
+    fn max_depth_test4() {
+        // Each operation of a chain nests the chain a level deeper, so long chains are reported
+        // instead of building trees too deep to traverse or drop
+        let sources = [
+            format!("{};", vec!["1"; 10000].join(" + ")),
+            format!("{}1;", "!".repeat(10000)),
+            format!("1{};", "[0]".repeat(10000)),
+        ];
+        for source in sources.iter() {
+            let tree = Parser::default().parse_expression(source);
+            assert!(tree.has_errors());
+            let diagnostics = tree.diagnostics();
+            assert!(diagnostics.iter().all(|diagnostic| diagnostic.code == Some(codes::NESTED_TOO_DEEPLY)));
+            assert_eq!(diagnostics.last().unwrap().span.end, source.len() - 1);
+        }
+    }
+}
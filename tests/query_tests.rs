@@ -0,0 +1,159 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod query_tests {
+    use solfix::cst;
+    use solfix::diagnostics::Span;
+    use solfix::parse_4_25::{ parse, NonTerminal, ParseTree };
+    use solfix::query::{ Match, Pattern, PatternKind };
+    use solfix::source_map::SourceMap;
+
+    /*** Helpers ***/
+
+    const SOURCE: &str = "
+contract Bank {
+    mapping(address => uint256) balances;
+
+    function withdraw(uint256 amount) public {
+        require(tx.origin == owner);
+        msg.sender.call.value(amount)();
+        balances[msg.sender] = 0;
+    }
+
+    function pay(address to, uint256 amount) public {
+        to.transfer(amount);
+        emit Paid(to, amount);
+        balances[to] = 0;
+    }
+
+    function check() internal view returns (bool) {
+        return owner == owner;
+    }
+}
+";
+
+    // Returns the source text of each match and the text that each of its metavariables is bound to
+    fn find(pattern: &str) -> Vec<(String, Vec<(String, String)>)> {
+        let input = SOURCE.chars().collect::<Vec<char>>();
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from(SOURCE));
+        let syntax = cst::build(&input, &tree, true);
+        let text = |found: &Match| found.span(&syntax).unwrap().text(&input);
+        Pattern::new(pattern).unwrap().find_matches(&tree).iter().map(|found| {
+            let bindings = found.bindings.iter().map(|(name, (id, _))| {
+                (name.clone(), syntax.node(id).unwrap().span().text(&input))
+            }).collect();
+            (text(found), bindings)
+        }).collect()
+    }
+
+    fn binding(name: &str, text: &str) -> (String, String) {
+        (String::from(name), String::from(text))
+    }
+
+    /*** Patterns ***/
+
+    #[test]
+    fn pattern_test1() {
+        assert_eq!(Pattern::new("$X.call.value($V)()").unwrap().kind(), PatternKind::Expression);
+        assert_eq!(Pattern::new("$X = 0;").unwrap().kind(), PatternKind::Statements);
+        assert_eq!(Pattern::new("... $X = 0;").unwrap().kind(), PatternKind::Statements);
+        assert_eq!(Pattern::new("function $F(...) { ... }").unwrap().kind(), PatternKind::ContractMember);
+        assert_eq!(Pattern::new("...").unwrap().kind(), PatternKind::Expression);
+    }
+
+    #[test]
+    fn pattern_test2() {
+        let error = Pattern::new("f(").unwrap_err();
+        assert_eq!(error.span, Span::new(0, 2));
+        assert!(Pattern::new("").is_err());
+    }
+
+    #[test]
+    fn pattern_test3() {
+        // Ellipses in strings and comments are left alone
+        let pattern = Pattern::new("f(\"...\") /* ... */").unwrap();
+        let arguments = &pattern.tree().leaves[1].leaves[0];
+        let string = NonTerminal::Token(solfix::lex_4_25::Token::StringLiteral(String::from("\"...\"")));
+        assert_eq!(arguments.leaves[0].root, string);
+    }
+
+    /*** Expressions ***/
+
+    #[test]
+    fn expression_test1() {
+        let matches = find("$X.call.value($V)()");
+        assert_eq!(matches, vec![(
+            String::from("msg.sender.call.value(amount)()"),
+            vec![binding("$V", "amount"), binding("$X", "msg.sender")]
+        )]);
+    }
+
+    #[test]
+    fn expression_test2() {
+        let matches = find("require(tx.origin == $A)");
+        assert_eq!(matches, vec![(String::from("require(tx.origin == owner)"), vec![binding("$A", "owner")])]);
+    }
+
+    #[test]
+    fn expression_test3() {
+        // A metavariable that appears twice matches equal nodes
+        let matches = find("$A == $A");
+        assert_eq!(matches, vec![(String::from("owner == owner"), vec![binding("$A", "owner")])]);
+    }
+
+    #[test]
+    fn expression_test4() {
+        let texts = find("Paid(...)").into_iter().map(|(text, _)| text).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["Paid(to, amount)"]);
+        let texts = find("$F($X, ...)").into_iter().map(|(text, _)| text).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["require(tx.origin == owner)", "msg.sender.call.value(amount)", "to.transfer(amount)", "Paid(to, amount)"]);
+        // Metavariables don't match the missing arguments of a call
+        assert_eq!(find("msg.sender.call.value(amount)($X)"), vec![]);
+    }
+
+    /*** Statements ***/
+
+    #[test]
+    fn statements_test1() {
+        let matches = find("$X.transfer($V); ... $B = 0;");
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].0.starts_with("to.transfer(amount);"));
+        assert_eq!(matches[0].1, vec![binding("$B", "balances[to]"), binding("$V", "amount"), binding("$X", "to")]);
+    }
+
+    #[test]
+    fn statements_test2() {
+        // Without an ellipsis the statements have to be next to each other
+        assert_eq!(find("$X.transfer($V); $B = 0;"), vec![]);
+        assert_eq!(find("$X.call.value($V)(); $B = 0;").len(), 1);
+    }
+
+    /*** Contract Members ***/
+
+    #[test]
+    fn member_test1() {
+        let names = find("function $F(...) public { ... }").into_iter()
+            .map(|(_, bindings)| bindings[0].1.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["withdraw", "pay"]);
+    }
+
+    #[test]
+    fn member_test2() {
+        let matches = find("function $F(address $TO, ...) public { ... }");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].1, vec![binding("$F", "pay"), binding("$TO", "to")]);
+    }
+
+    #[test]
+    fn match_test1() {
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from(SOURCE));
+        let matches = Pattern::new("balances[$K] = 0;").unwrap().find_matches(&tree);
+        let keys = matches.iter().map(|found| found.bindings["$K"].1).collect::<Vec<&ParseTree>>();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[1].root, NonTerminal::Identifier(String::from("to")));
+        for found in &matches {
+            assert!(std::ptr::eq(tree.node(&found.nodes[0].0).unwrap(), found.nodes[0].1));
+        }
+    }
+}
@@ -0,0 +1,149 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod report_tests {
+    use solfix::diagnostics::{ Diagnostic, Span, Suggestion };
+    use solfix::parse_4_25::parse;
+    use solfix::report::{ self, RenderOptions };
+    use solfix::source_map::{ FileId, SourceFile, SourceMap };
+
+    /*** Helpers ***/
+
+    fn source_file(source: &str) -> SourceFile {
+        SourceFile::new(FileId(0), "A.sol", String::from(source))
+    }
+
+    // Returns the messages of the diagnostics of source along with their spans
+    fn collect(source: &str) -> Vec<(String, Span)> {
+        let mut sources = SourceMap::new();
+        let (id, tree) = parse(&mut sources, "A.sol", String::from(source));
+        report::diagnostics(sources.file(id), &tree).into_iter().map(|diagnostic| (diagnostic.message, diagnostic.span)).collect()
+    }
+
+    /*** Collecting Tests ***/
+
+    #[test]
+    fn diagnostics_test1() {
+        let source = "contract A {\n    uint x = 1;\n";
+        let diagnostics = collect(source);
+        assert_eq!(diagnostics, vec![(String::from("expected `}`, found end of file"), Span::new(28, 28))]);
+    }
+
+    #[test]
+    fn diagnostics_test2() {
+        let source = "contract A {\n    function f() public {\n        x = ;\n    }\n}\n";
+        let diagnostics = collect(source);
+        assert_eq!(diagnostics, vec![(String::from("expected expression, found `;`"), Span::new(50, 50))]);
+    }
+
+    #[test]
+    fn diagnostics_test3() {
+        let source = "contract A {\n    uint x = 1\n    function f() public { y = ; }\n}\n";
+        let diagnostics = collect(source);
+        assert_eq!(diagnostics[0], (String::from("expected `;`, found `function`"), Span::new(27, 27)));
+        // The source unit is reported first in the tree, but its error is at the end of the input
+        let starts = diagnostics.iter().map(|(_, span)| span.start).collect::<Vec<usize>>();
+        let mut sorted = starts.clone();
+        sorted.sort();
+        assert_eq!(starts, sorted);
+        assert_eq!(diagnostics.last().unwrap().0, "expected pragma, import or contract, found `}`");
+    }
+
+    #[test]
+    fn diagnostics_test4() {
+        let source = "contract A {\n    uint constant;\n}\n";
+        let diagnostics = collect(source);
+        assert_eq!(diagnostics, vec![(String::from("expected identifier, found `;`"), Span::new(30, 30))]);
+    }
+
+    #[test]
+    fn diagnostics_test5() {
+        let source = "pragma solidity ^0.4.25;\ncontract A {}\n";
+        assert!(collect(source).is_empty());
+    }
+
+    /*** Rendering Tests ***/
+
+    #[test]
+    fn render_test1() {
+        let source = "contract A {\n    uint x = 1\n    function f() public {}\n}\n";
+        let mut sources = SourceMap::new();
+        let (id, tree) = parse(&mut sources, "A.sol", String::from(source));
+        let file = sources.file(id);
+        let diagnostics = report::diagnostics(file, &tree);
+        let rendered = report::render(&diagnostics[0], file, &RenderOptions::default());
+        let expected = [
+            "error[SF0001]: expected `;`, found `function`",
+            " --> A.sol:2:15",
+            "  |",
+            "2 |     uint x = 1",
+            "  |               ^",
+            ""
+        ].join("\n");
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn render_test2() {
+        let file = source_file("contract A {\n\tfucntion f() public {}\n}\n");
+        let diagnostic = Diagnostic::warning(String::from("unexpected identifier `fucntion`"), Span::new(14, 22))
+            .with_note(String::from("a note"))
+            .with_help(String::from("did you mean `function`?"))
+            .with_suggestion(Suggestion {
+                message: String::from("replace `fucntion` with `function`"),
+                span: Span::new(14, 22),
+                replacement: String::from("function")
+            });
+        let rendered = report::render(&diagnostic, &file, &RenderOptions::default());
+        let expected = [
+            "warning: unexpected identifier `fucntion`",
+            " --> A.sol:2:2",
+            "  |",
+            "2 |     fucntion f() public {}",
+            "  |     ^^^^^^^^",
+            "  |",
+            "  = note: a note",
+            "  = help: did you mean `function`?",
+            "help: replace `fucntion` with `function`",
+            "  |",
+            "2 |     function f() public {}",
+            "  |     ~~~~~~~~",
+            ""
+        ].join("\n");
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn render_test3() {
+        let file = source_file("a\nb\n\nc\nd\ne\nf\ng\nh\nbcd\n");
+        let diagnostic = Diagnostic::error(String::from("spans lines"), Span::new(13, 19));
+        let rendered = report::render(&diagnostic, &file, &RenderOptions::default());
+        let expected = [
+            "error: spans lines",
+            "  --> A.sol:8:1",
+            "   |",
+            " 8 | g",
+            "   | ^",
+            " 9 | h",
+            "   | ^",
+            "10 | bcd",
+            "   | ^^",
+            ""
+        ].join("\n");
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn render_test4() {
+        let file = source_file("uint x");
+        let diagnostic = Diagnostic::error(String::from("expected `;`, found end of file"), Span::new(6, 6));
+        let plain = report::render(&diagnostic, &file, &RenderOptions::default());
+        let colored = report::render(&diagnostic, &file, &RenderOptions { color: true });
+        assert!(!plain.contains('\x1b'));
+        assert!(colored.starts_with("\x1b[1;31merror:\x1b[0m"));
+        // Without its escape codes, coloured output is the same as plain output
+        let stripped = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap().replace_all(&colored, "");
+        assert_eq!(stripped, plain);
+        assert!(plain.ends_with("1 | uint x\n  |       ^\n"));
+    }
+}
@@ -0,0 +1,143 @@
+extern crate solfix;
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use solfix::codes;
+    use solfix::cst;
+    use solfix::diagnostics::{ Diagnostic, Span };
+    use solfix::lex_4_25;
+    use solfix::parse_4_25::{ parse, NonTerminal, ParseTree };
+    use solfix::source_map::SourceMap;
+    use serde_json::json;
+    use std::fs;
+
+    /*** Helpers ***/
+
+    fn round_trip(tree: &ParseTree) -> ParseTree {
+        let json = serde_json::to_string(tree).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    /*** Parse Trees ***/
+
+    #[test]
+    fn round_trip_test1() {
+        let input = fs::read_to_string("./contracts/Ownable.sol")
+            .expect("Test file not found: ./contracts/Ownable.sol");
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", input);
+        assert_eq!(round_trip(&tree), tree);
+    }
+
+    #[test]
+    fn round_trip_test2() {
+        // Trees with errors keep their diagnostics
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract A { function f() { while (a) { } } uint x = ; }"));
+        assert!(tree.has_errors());
+        assert_eq!(round_trip(&tree), tree);
+    }
+
+    #[test]
+    fn schema_test1() {
+        let input = fs::read_to_string("./contracts/Ownable.sol").unwrap();
+        let (_, tree) = parse(&mut SourceMap::new(), "Ownable.sol", input);
+        let json = serde_json::to_value(tree).unwrap();
+        assert_eq!(json["kind"], "SourceUnit");
+        assert_eq!(json["children"][0], json!({
+            "kind": "PragmaDirective",
+            "children": [
+                { "kind": "Identifier", "value": "solidity", "children": [] },
+                { "kind": "Token", "value": { "kind": "BitwiseXor" }, "children": [] },
+                { "kind": "Token", "value": { "kind": "Version", "value": "0.4.25" }, "children": [] }
+            ]
+        }));
+        let contract = &json["children"][1];
+        assert_eq!(contract["kind"], "ContractDefinition");
+        assert_eq!(contract["value"], json!({ "kind": "Contract" }));
+    }
+
+    #[test]
+    fn schema_test2() {
+        let invalid = NonTerminal::Token(lex_4_25::Token::Semicolon).to_invalid().to_leaf();
+        assert_eq!(serde_json::to_value(&invalid).unwrap(), json!({
+            "kind": "Invalid",
+            "value": { "kind": "Token", "value": { "kind": "Semicolon" } },
+            "children": []
+        }));
+        let fixed = NonTerminal::Token(lex_4_25::Token::FixedMxN(128, 18)).to_leaf();
+        assert_eq!(serde_json::to_value(&fixed).unwrap()["value"]["value"], json!([128, 18]));
+        let error = Diagnostic::error(String::from("expected `;`"), Span::new(3, 4));
+        assert_eq!(serde_json::to_value(&error).unwrap(), json!({
+            "severity": "Error",
+            "message": "expected `;`",
+            "span": { "start": 3, "end": 4 },
+            "notes": [],
+            "help": null,
+            "suggestions": []
+        }));
+    }
+
+    #[test]
+    fn schema_test3() {
+        let error = Diagnostic::error(String::from("expected `;`"), Span::new(3, 4)).with_code(codes::EXPECTED_SEMICOLON);
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["code"], json!("SF0001"));
+        assert_eq!(serde_json::from_value::<Diagnostic>(value).unwrap(), error);
+        let unknown = json!({
+            "severity": "Error",
+            "code": "E0308",
+            "message": "expected `;`",
+            "span": { "start": 3, "end": 4 },
+            "notes": [],
+            "help": null,
+            "suggestions": []
+        });
+        assert!(serde_json::from_value::<Diagnostic>(unknown).is_err());
+    }
+
+    #[test]
+    fn spanned_tree_test1() {
+        let input = "contract A { function f() { g(1); } }".chars().collect::<Vec<char>>();
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", input.iter().collect());
+        let json = serde_json::to_value(cst::SpannedTree::new(&input, &tree)).unwrap();
+        assert_eq!((&json["kind"], &json["start"], &json["end"]), (&json!("SourceUnit"), &json!(0), &json!(37)));
+        let contract = &json["children"][0];
+        assert_eq!(contract["value"], json!({ "kind": "Contract" }));
+        assert_eq!((&contract["start"], &contract["end"]), (&json!(0), &json!(37)));
+        assert_eq!(contract["children"][0], json!({ "kind": "Identifier", "value": "A", "start": 9, "end": 10, "children": [] }));
+        let function = &contract["children"][1]["children"][0];
+        assert_eq!((&function["kind"], &function["start"], &function["end"]), (&json!("FunctionDefinition"), &json!(13), &json!(35)));
+        // The offsets are ignored when the JSON is read back in
+        assert_eq!(serde_json::from_value::<ParseTree>(json).unwrap(), tree);
+    }
+
+    #[test]
+    fn spanned_tree_test2() {
+        let input = fs::read_to_string("./contracts/Ownable.sol").unwrap();
+        let (_, tree) = parse(&mut SourceMap::new(), "Ownable.sol", input.clone());
+        let json = serde_json::to_value(cst::SpannedTree::new(&input.chars().collect(), &tree)).unwrap();
+        let pragma = &json["children"][0];
+        assert_eq!((&pragma["start"], &pragma["end"]), (&json!(0), &json!(24)));
+        assert_eq!(serde_json::from_value::<ParseTree>(json).unwrap(), tree);
+    }
+
+    /*** Syntax Trees ***/
+
+    #[test]
+    fn syntax_tree_test1() {
+        let input = "contract A { }".chars().collect::<Vec<char>>();
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", input.iter().collect());
+        let json = serde_json::to_value(cst::build(&input, &tree, true)).unwrap();
+        let contract = &json["children"][0];
+        assert_eq!(contract["node"]["kind"], "ContractDefinition");
+        assert_eq!(contract["span"], json!({ "start": 0, "end": 14 }));
+        assert_eq!(contract["children"][0], json!({
+            "token": { "kind": "Contract" },
+            "text": "contract",
+            "span": { "start": 0, "end": 8 }
+        }));
+        let identifier = contract["children"].as_array().unwrap().iter()
+            .find(|child| child["node"]["kind"] == "Identifier")
+            .cloned();
+        assert_eq!(identifier.map(|identifier| identifier["span"].clone()), Some(json!({ "start": 9, "end": 10 })));
+    }
+}
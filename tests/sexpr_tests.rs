@@ -0,0 +1,136 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod sexpr_tests {
+    use regex::Regex;
+    use std::fs;
+    use solfix::codes;
+    use solfix::diagnostics::{ Diagnostic, Span, Suggestion };
+    use solfix::lex_4_25::Token;
+    use solfix::parse_4_25::{ parse, NonTerminal, ParseTree };
+    use solfix::parser::Parser;
+    use solfix::source_map::SourceMap;
+
+    /*** Helpers ***/
+
+    fn round_trip(tree: &ParseTree) {
+        assert_eq!(&ParseTree::from_sexpr(&tree.to_sexpr()).unwrap(), tree, "{}", tree.to_sexpr());
+    }
+
+    fn read_error(text: &str) -> (String, Span) {
+        let error = ParseTree::from_sexpr(text).unwrap_err();
+        (error.message, error.span)
+    }
+
+    // Returns the names of the variants of an enum that don't hold any values
+    fn unit_variants(path: &str, name: &str) -> Vec<String> {
+        let source = fs::read_to_string(path).unwrap();
+        let body = Regex::new(&format!(r"(?s)pub enum {} \{{(.*?)\n\}}", name)).unwrap().captures(&source).unwrap()[1].to_string();
+        let variant = Regex::new(r"(?m)^\s*([A-Z][A-Za-z0-9]*)\s*,").unwrap();
+        variant.captures_iter(&body).map(|captures| captures[1].to_string()).collect()
+    }
+
+    /*** Printing ***/
+
+    #[test]
+    fn print_test1() {
+        let tree = Parser::default().parse_expression("f(\"x\")");
+        assert_eq!(
+            tree.to_sexpr(),
+            "(FunctionCall (Identifier f) (FunctionCallArguments (ExpressionList (Token StringLiteral \"\\\"x\\\"\"))))"
+        );
+    }
+
+    #[test]
+    fn print_test2() {
+        // Nodes that don't fit on a line have their leaves on lines of their own
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from("contract Wide { function withdraw(uint256 amount, address recipient) public returns (bool) {} }"));
+        assert_eq!(tree.to_sexpr(), "(SourceUnit
+  (ContractDefinition Contract
+    (Identifier Wide)
+    (ContractPart
+      (FunctionDefinition
+        (Identifier withdraw)
+        (ParameterList
+          (Parameter (Token Uint256) (Identifier amount))
+          (Parameter (Token Address) (Identifier recipient)))
+        (Token Public)
+        (Token Returns)
+        (ParameterList (Parameter (Token Bool)))
+        (Block)))))");
+    }
+
+    #[test]
+    fn print_test3() {
+        let diagnostic = Diagnostic::error(String::from("unexpected identifier `ff`"), Span::new(3, 5))
+            .with_note(String::from("a note"))
+            .with_help(String::from("did you mean `if`?"))
+            .with_suggestion(Suggestion { message: String::from("replace"), span: Span::new(3, 5), replacement: String::from("if") });
+        let tree = ParseTree {
+            root: NonTerminal::Block,
+            leaves: vec![
+                Box::new(NonTerminal::Error(Box::new(diagnostic)).to_leaf()),
+                Box::new(NonTerminal::InvalidPair(Box::new(NonTerminal::Block), Box::new(NonTerminal::Token(Token::EOF))).to_leaf()),
+            ]
+        };
+        assert_eq!(tree.to_sexpr(), "(Block
+  (Error Error \"unexpected identifier `ff`\" 3 5 (note \"a note\") (help \"did you mean `if`?\") (suggestion replace 3 5 if))
+  (InvalidPair (Block) (Token EOF)))");
+        round_trip(&tree);
+    }
+
+    #[test]
+    fn print_test4() {
+        let diagnostic = Diagnostic::warning(String::from("unexpected identifier `pubic`"), Span::new(3, 8))
+            .with_code(codes::MISSPELLED_KEYWORD);
+        let tree = NonTerminal::Error(Box::new(diagnostic)).to_leaf();
+        assert_eq!(tree.to_sexpr(), "(Error Warning \"unexpected identifier `pubic`\" 3 8 (code SF0014))");
+        round_trip(&tree);
+    }
+
+    /*** Reading ***/
+
+    #[test]
+    fn read_test1() {
+        let inputs = [
+            "contract A is B(1) { using L for *; uint[2] public x = 1 ether; event E(uint indexed a) anonymous; }",
+            "library L { function f(uint a, bytes memory b) internal pure returns (uint) { return a ** 2; } }",
+            "contract A { function f() { x = -y ? z : w; } }",
+            "contract A { uint x }",
+            "contract A { ufixed128x18 x; fixed y; function f() external payable { ff; } }",
+        ];
+        for input in inputs.iter() {
+            round_trip(&parse(&mut SourceMap::new(), "A.sol", String::from(*input)).1);
+        }
+    }
+
+    #[test]
+    fn read_test2() {
+        // Any whitespace separates the parts of a node
+        let tree = ParseTree::from_sexpr("(Token  Plus\n\t(Identifier a)(Token DecimalNumber \"1\"))").unwrap();
+        assert_eq!(tree, Parser::default().parse_expression("a + 1"));
+    }
+
+    #[test]
+    fn read_test3() {
+        assert_eq!(read_error("(Block (Statment))"), (String::from("`Statment` isn't a kind of nonterminal"), Span::new(8, 16)));
+        assert_eq!(read_error("(Token Plos)"), (String::from("`Plos` isn't a kind of token"), Span::new(7, 11)));
+        assert_eq!(read_error("(Block (Break)"), (String::from("expected `(` or `)`"), Span::new(14, 14)));
+        assert_eq!(read_error("(Block) (Break)"), (String::from("expected the end of the text after the tree"), Span::new(8, 9)));
+        assert_eq!(read_error("(Identifier \"a)"), (String::from("unterminated string"), Span::new(12, 15)));
+        assert_eq!(read_error("(Token FixedMxN 8 x)"), (String::from("expected a number"), Span::new(18, 19)));
+    }
+
+    #[test]
+    fn names_test1() {
+        // Every variant that doesn't hold a value can be read back by name
+        for name in unit_variants("src/parse_4_25.rs", "NonTerminal") {
+            let tree = ParseTree::from_sexpr(&format!("({})", name)).unwrap();
+            assert_eq!(tree.to_sexpr(), format!("({})", name));
+        }
+        for name in unit_variants("src/lex_4_25.rs", "Token") {
+            let tree = ParseTree::from_sexpr(&format!("(Token {})", name)).unwrap();
+            assert_eq!(tree.to_sexpr(), format!("(Token {})", name));
+        }
+    }
+}
@@ -0,0 +1,281 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod solc_tests {
+    use std::env;
+    use std::fs;
+    use serde_json::Value;
+    use serde_json::json;
+    use solfix::diagnostics::Span;
+    use solfix::parse_4_25::{ parse, NonTerminal, ParseTree };
+    use solfix::solc::{ export_compact_ast, import_ast, import_sources };
+    use solfix::source_map::SourceMap;
+
+    /*** Helpers ***/
+
+    fn export(source: &str) -> Value {
+        let mut sources = SourceMap::new();
+        let (id, tree) = parse(&mut sources, "test.sol", String::from(source));
+        export_compact_ast(&tree, sources.file(id))
+    }
+
+    // Compares the AST of a contract in the contracts directory with its golden file. Setting
+    // UPDATE_GOLDEN rewrites the golden file instead. Only contracts that parse without errors
+    // have golden files, so that a golden file never records a misparse.
+    fn check_golden(name: &str) {
+        let path = format!("contracts/{}.sol", name);
+        let source = fs::read_to_string(&path).unwrap();
+        let mut sources = SourceMap::new();
+        let (id, tree) = parse(&mut sources, &path, source);
+        assert!(!tree.has_errors(), "{}", path);
+        let actual = export_compact_ast(&tree, sources.file(id));
+        let golden = format!("tests/golden/{}.ast.json", name);
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&golden, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+        }
+        let expected = serde_json::from_str::<Value>(&fs::read_to_string(&golden).unwrap()).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    // Returns every node of an AST, in the order that they appear in the JSON
+    fn nodes(ast: &Value) -> Vec<&Value> {
+        let mut nodes = vec![];
+        match ast {
+            Value::Object(fields) => {
+                if fields.contains_key("nodeType") {
+                    nodes.push(ast);
+                }
+                for value in fields.values() {
+                    nodes.extend(self::nodes(value));
+                }
+            }
+            Value::Array(values) => {
+                for value in values {
+                    nodes.extend(self::nodes(value));
+                }
+            }
+            _ => ()
+        }
+        nodes
+    }
+
+    fn find<'a>(ast: &'a Value, node_type: &str) -> Vec<&'a Value> {
+        nodes(ast).into_iter().filter(|node| node["nodeType"] == node_type).collect()
+    }
+
+    /*** Golden Files ***/
+
+    #[test]
+    fn golden_test1() {
+        check_golden("Ownable");
+    }
+
+    /*** Nodes ***/
+
+    #[test]
+    fn id_test1() {
+        // Like solc, a node's leaves get lower ids than the node itself
+        let ast = export("contract A { function f(uint a) public returns (uint) { return a * 2; } }");
+        let ids = nodes(&ast).iter().map(|node| node["id"].as_u64().unwrap()).collect::<Vec<u64>>();
+        let mut unique = ids.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), ids.len());
+        assert_eq!(ast["id"].as_u64(), unique.last().cloned());
+        let function = &find(&ast, "FunctionDefinition")[0];
+        assert!(function["body"]["id"].as_u64() < function["id"].as_u64());
+        assert!(function["parameters"]["id"].as_u64() < function["body"]["id"].as_u64());
+    }
+
+    #[test]
+    fn src_test1() {
+        // Offsets count bytes, and statements end before their semicolons
+        let source = "contract A { string s = \"é\"; function f() public { s = \"ü\"; } }";
+        let ast = export(source);
+        let statement = &find(&ast, "ExpressionStatement")[0];
+        assert_eq!(statement["src"], "52:8:0");
+        assert_eq!(&source.as_bytes()[52..60], "s = \"ü\"".as_bytes());
+        let literal = &find(&ast, "Literal")[0];
+        assert_eq!(literal["value"], "é");
+        assert_eq!(literal["hexValue"], "c3a9");
+    }
+
+    #[test]
+    fn pragma_test1() {
+        let ast = export("pragma solidity ^0.4.25;");
+        let pragma = &ast["nodes"][0];
+        assert_eq!(pragma["literals"], json!(["solidity", "^", "0.4", ".25"]));
+        assert_eq!(pragma["src"], "0:24:0");
+    }
+
+    #[test]
+    fn function_test1() {
+        let ast = export("contract A { constructor() public {} function A() {} function() external payable {} function f() constant; }");
+        let functions = find(&ast, "FunctionDefinition");
+        let fields = functions.iter().map(|function| {
+            (function["name"].as_str().unwrap(), function["isConstructor"].as_bool().unwrap(), function["stateMutability"].as_str().unwrap())
+        }).collect::<Vec<_>>();
+        assert_eq!(fields, vec![("", true, "nonpayable"), ("A", true, "nonpayable"), ("", false, "payable"), ("f", false, "view")]);
+        assert_eq!(functions[3]["implemented"], false);
+        assert_eq!(functions[3]["body"], Value::Null);
+        assert_eq!(ast["nodes"][0]["fullyImplemented"], false);
+    }
+
+    #[test]
+    fn expression_test1() {
+        let ast = export("contract A { function f() public { x++; --x; delete x; y = uint8(x) + 1 ether; } }");
+        let unary = find(&ast, "UnaryOperation").iter().map(|operation| {
+            (operation["operator"].as_str().unwrap(), operation["prefix"].as_bool().unwrap())
+        }).collect::<Vec<_>>();
+        assert_eq!(unary, vec![("++", false), ("--", true), ("delete", true)]);
+        assert_eq!(find(&ast, "FunctionCall")[0]["kind"], "typeConversion");
+        let literal = &find(&ast, "Literal")[0];
+        assert_eq!((&literal["value"], &literal["subdenomination"]), (&Value::from("1"), &Value::from("ether")));
+        assert_eq!(find(&ast, "Assignment")[0]["operator"], "=");
+    }
+
+    /*** Names ***/
+
+    #[test]
+    fn reference_test1() {
+        let ast = export("contract A { uint x; struct S { uint a; } function f(uint y) public { x = y + z; } S s; }");
+        let declarations = find(&ast, "VariableDeclaration");
+        let identifiers = find(&ast, "Identifier");
+        assert_eq!(identifiers[0]["referencedDeclaration"], declarations[0]["id"]);
+        assert_eq!(identifiers[1]["referencedDeclaration"], declarations[2]["id"]);
+        assert_eq!(identifiers[2]["referencedDeclaration"], Value::Null);
+        let structure = &find(&ast, "StructDefinition")[0];
+        assert_eq!(find(&ast, "UserDefinedTypeName")[0]["referencedDeclaration"], structure["id"]);
+        assert_eq!(declarations[1]["scope"], structure["id"]);
+        assert_eq!(declarations[1]["src"], "32:6:0");
+        assert_eq!(declarations[2]["scope"], find(&ast, "FunctionDefinition")[0]["id"]);
+    }
+
+    #[test]
+    fn linearization_test1() {
+        let ast = export("contract A {} contract B is A {} contract C is A {} contract D is B, C { function B() {} }");
+        let ids = ast["nodes"].as_array().unwrap().iter().map(|contract| contract["id"].clone()).collect::<Vec<Value>>();
+        assert_eq!(ast["nodes"][3]["linearizedBaseContracts"], Value::from(vec![ids[3].clone(), ids[2].clone(), ids[1].clone(), ids[0].clone()]));
+        assert_eq!(ast["nodes"][3]["contractDependencies"], Value::from(vec![ids[0].clone(), ids[1].clone(), ids[2].clone()]));
+        // Base contracts aren't hidden by members with the same name
+        assert_eq!(ast["nodes"][3]["baseContracts"][0]["baseName"]["referencedDeclaration"], ids[1]);
+        assert_eq!(ast["exportedSymbols"]["D"], Value::from(vec![ids[3].clone()]));
+    }
+
+    /*** Import ***/
+
+    #[test]
+    fn import_test1() {
+        // The AST that solfix exports is imported as the tree that it was exported from
+        let source = fs::read_to_string("contracts/Ownable.sol").unwrap();
+        let golden = fs::read_to_string("tests/golden/Ownable.ast.json").unwrap();
+        let (_, tree) = parse(&mut SourceMap::new(), "contracts/Ownable.sol", source);
+        assert_eq!(import_ast(&serde_json::from_str(&golden).unwrap(), None), tree);
+    }
+
+    #[test]
+    fn import_test2() {
+        let source = "pragma solidity ^0.4.25;
+contract A is B(1) {
+    using L for *;
+    uint256 public constant x = 1 ether;
+    mapping(address => uint[2]) m;
+    event E(address indexed who, uint256) anonymous;
+    enum K { P, Q }
+    struct S { uint a; bytes32 b; }
+    modifier only(uint a) { _; }
+    function f(uint256 a, S storage s) public view only(1) returns (uint256 r) {
+        return a + x;
+    }
+    function g(function (uint) external returns (bool) h) external;
+    function() external payable { f(); }
+    constructor() public {}
+    function h(bytes memory b) internal pure returns (uint, bool) {
+        a.b[1] = c ? d : e;
+        g(1, \"s\", true, 0x10);
+        new C();
+        (a, , b) = [1, 2];
+        uint(a)--;
+        emit E(a, b);
+        return;
+    }
+}";
+        let (_, tree) = parse(&mut SourceMap::new(), "A.sol", String::from(source));
+        assert!(!tree.has_errors());
+        assert_eq!(import_ast(&export(source), None), tree);
+    }
+
+    #[test]
+    fn import_test3() {
+        // The archives of `solc --combined-json ast` hold legacy ASTs
+        let output = fs::read_to_string("tests/fixtures/Ownable.combined.json").unwrap();
+        let sources = import_sources(&serde_json::from_str(&output).unwrap(), &SourceMap::new());
+        let source = fs::read_to_string("contracts/Ownable.sol").unwrap();
+        let (_, tree) = parse(&mut SourceMap::new(), "contracts/Ownable.sol", source);
+        assert_eq!(sources, vec![(String::from("contracts/Ownable.sol"), tree)]);
+    }
+
+    #[test]
+    fn import_test4() {
+        // Statements that the parser doesn't support yet are imported with nonterminals of their own
+        let ast = json!({
+            "nodeType": "ForStatement",
+            "src": "0:30:0",
+            "initializationExpression": null,
+            "condition": { "nodeType": "Identifier", "name": "c", "src": "6:1:0" },
+            "loopExpression": null,
+            "body": { "nodeType": "Block", "statements": [{ "nodeType": "Break", "src": "12:5:0" }], "src": "10:9:0" }
+        });
+        let block = ParseTree { root: NonTerminal::Block, leaves: vec![Box::new(NonTerminal::Break.to_leaf())] };
+        let expected = ParseTree {
+            root: NonTerminal::ForStatement,
+            leaves: vec![
+                Box::new(NonTerminal::Empty.to_leaf()),
+                Box::new(NonTerminal::Identifier(String::from("c")).to_leaf()),
+                Box::new(NonTerminal::Empty.to_leaf()),
+                Box::new(block),
+            ]
+        };
+        assert_eq!(import_ast(&ast, None), expected);
+    }
+
+    #[test]
+    fn import_test5() {
+        let ast = json!({ "nodeType": "Block", "src": "0:20:0", "statements": [{ "nodeType": "Unknown", "src": "2:3:0" }] });
+        let tree = import_ast(&ast, None);
+        assert!(tree.has_errors());
+        let diagnostics = tree.diagnostics();
+        assert_eq!(diagnostics[0].message, "solc AST nodes of type `Unknown` can't be imported");
+        // Without the source, the error's byte offsets can't be converted to characters
+        assert_eq!(diagnostics[0].span, Span::new(0, 0));
+        assert_eq!(diagnostics[0].notes, vec![String::from("the node is at bytes 2 to 5 of its source")]);
+        let ast = json!({ "nodeType": "Assignment", "operator": "=", "src": "0:1:0" });
+        assert_eq!(import_ast(&ast, None).diagnostics()[0].message, "the `leftHandSide` of a Assignment node is missing");
+    }
+
+    #[test]
+    fn import_test6() {
+        // A compact AST of Ownable written out by hand in the format of `solc --ast-compact-json`
+        // 0.4.25, with the types and global declarations that solc resolves
+        let ast = fs::read_to_string("tests/fixtures/Ownable.compact.json").unwrap();
+        let source = fs::read_to_string("contracts/Ownable.sol").unwrap();
+        let (_, tree) = parse(&mut SourceMap::new(), "contracts/Ownable.sol", source);
+        assert_eq!(import_ast(&serde_json::from_str(&ast).unwrap(), None), tree);
+    }
+
+    #[test]
+    fn import_test7() {
+        // With the source, the error's span counts characters like the parser's diagnostics
+        let source = "contract A { string s = \"é\"; function f() public { } }";
+        let mut sources = SourceMap::new();
+        let id = sources.add("A.sol", String::from(source));
+        let start = source.find("function").unwrap();
+        let ast = json!({ "nodeType": "Block", "src": "0:55:0", "statements": [{ "nodeType": "Unknown", "src": format!("{}:8:0", start) }] });
+        let diagnostics = import_ast(&ast, Some(sources.file(id))).diagnostics();
+        assert_eq!(diagnostics[0].span, Span::new(start - 1, start + 7));
+        assert!(diagnostics[0].notes.is_empty());
+        let output = json!({ "sourceList": ["A.sol"], "sources": { "A.sol": { "AST": ast } } });
+        let imported = import_sources(&output, &sources);
+        assert_eq!(imported[0].1.diagnostics()[0].span, Span::new(start - 1, start + 7));
+    }
+}
@@ -0,0 +1,113 @@
+extern crate solfix;
+
+#[cfg(test)]
+mod source_map_tests {
+    use solfix::diagnostics::Span;
+    use solfix::parse_4_25::parse;
+    use solfix::source_map::{ Encoding, FileId, LineColumn, SourceFile, SourceMap };
+
+    /*** Helpers ***/
+
+    // A file with characters that take up two and three UTF-8 bytes and two UTF-16 code units
+    fn unicode_file() -> SourceFile {
+        SourceFile::new(FileId(0), "A.sol", String::from("// é\nstring s = \"€😀\";\n\nx"))
+    }
+
+    /*** SourceMap Tests ***/
+
+    #[test]
+    fn source_map_test1() {
+        let mut sources = SourceMap::new();
+        let a = sources.add("A.sol", String::from("contract A {}"));
+        let b = sources.add("B.sol", String::from("contract B {}"));
+        assert_eq!((a, b), (FileId(0), FileId(1)));
+        assert_eq!(sources.file(b).path(), "B.sol");
+        assert_eq!(sources.file(b).text(), "contract B {}");
+        assert_eq!(sources.find("A.sol"), Some(a));
+        assert_eq!(sources.find("C.sol"), None);
+        assert_eq!(sources.files().len(), 2);
+    }
+
+    #[test]
+    fn source_map_test2() {
+        // Parsing registers the input, and the spans of the tree are offsets into its characters
+        let mut sources = SourceMap::new();
+        sources.add("A.sol", String::from("contract A {}"));
+        let (id, tree) = parse(&mut sources, "B.sol", String::from("contract B {\n    uint x = 1\n}\n"));
+        assert_eq!(id, FileId(1));
+        assert_eq!(sources.file(id).path(), "B.sol");
+        assert!(tree.has_errors());
+        assert_eq!(sources.file(id).chars().len(), 30);
+    }
+
+    /*** Line Tests ***/
+
+    #[test]
+    fn line_test1() {
+        let file = unicode_file();
+        assert_eq!(file.line_count(), 4);
+        assert_eq!(file.line(0), 0);
+        assert_eq!(file.line(4), 0);
+        assert_eq!(file.line(5), 1);
+        assert_eq!(file.line(23), 3);
+        assert_eq!(file.line(100), 3);
+        assert_eq!(file.line_span(0), Span::new(0, 4));
+        assert_eq!(file.line_span(1), Span::new(5, 21));
+        assert_eq!(file.line_span(2), Span::new(22, 22));
+        assert_eq!(file.line_span(3), Span::new(23, 24));
+    }
+
+    #[test]
+    fn line_test2() {
+        let file = SourceFile::new(FileId(0), "A.sol", String::new());
+        assert_eq!(file.line_count(), 1);
+        assert_eq!(file.line_span(0), Span::new(0, 0));
+        assert_eq!(file.line_column(0, Encoding::Utf16), LineColumn::new(0, 0));
+        assert_eq!(file.offset(LineColumn::new(0, 3), Encoding::Utf8), Some(0));
+        assert_eq!(file.offset(LineColumn::new(1, 0), Encoding::Utf8), None);
+    }
+
+    /*** Encoding Tests ***/
+
+    #[test]
+    fn encoding_test1() {
+        let file = unicode_file();
+        // The character after `😀`, which is `"`
+        assert_eq!(file.line_column(19, Encoding::Utf32), LineColumn::new(1, 14));
+        assert_eq!(file.line_column(19, Encoding::Utf16), LineColumn::new(1, 15));
+        assert_eq!(file.line_column(19, Encoding::Utf8), LineColumn::new(1, 19));
+        assert_eq!(file.line_column(23, Encoding::Utf8), LineColumn::new(3, 0));
+        // Offsets past the end are the end of the file
+        assert_eq!(file.line_column(50, Encoding::Utf32), LineColumn::new(3, 1));
+    }
+
+    #[test]
+    fn encoding_test2() {
+        let file = unicode_file();
+        for encoding in [Encoding::Utf8, Encoding::Utf16, Encoding::Utf32].iter() {
+            for offset in 0..=file.chars().len() {
+                assert_eq!(file.offset(file.line_column(offset, *encoding), *encoding), Some(offset));
+            }
+        }
+        // Columns inside of `😀` don't name a character
+        assert_eq!(file.offset(LineColumn::new(1, 14), Encoding::Utf16), None);
+        assert_eq!(file.offset(LineColumn::new(1, 17), Encoding::Utf8), None);
+        // Columns past the end of a line are the end of the line
+        assert_eq!(file.offset(LineColumn::new(0, 40), Encoding::Utf16), Some(4));
+        assert_eq!(file.offset(LineColumn::new(4, 0), Encoding::Utf16), None);
+    }
+
+    #[test]
+    fn encoding_test3() {
+        let file = unicode_file();
+        let text = file.text();
+        for offset in 0..=file.chars().len() {
+            let byte = file.byte_offset(offset);
+            assert_eq!(text[..byte].chars().count(), offset);
+            assert_eq!(file.char_offset(byte), Some(offset));
+        }
+        // The second byte of `é` and a byte past the end aren't the start of a character
+        assert_eq!(file.char_offset(4), None);
+        assert_eq!(file.char_offset(text.len() + 1), None);
+    }
+}